@@ -0,0 +1,3 @@
+-- The programming language used for the most recent attempt. Nullable, since
+-- older attempts (and attempts logged without --lang) don't record one.
+ALTER TABLE progress ADD COLUMN language TEXT;
@@ -0,0 +1,3 @@
+-- Manual priority for the `next` queue. Higher values are suggested first;
+-- pinned problems carry a positive priority, everything else stays at 0.
+ALTER TABLE problems ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
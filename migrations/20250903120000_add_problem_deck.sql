@@ -0,0 +1 @@
+ALTER TABLE problems ADD COLUMN deck TEXT NOT NULL DEFAULT 'default';
@@ -0,0 +1,19 @@
+-- One row per logged attempt. `progress` keeps only the latest state, so this
+-- is the only record of how earlier attempts went.
+CREATE TABLE attempt_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    problem_id INTEGER NOT NULL,
+    attempted_on DATE NOT NULL,
+    rating TEXT NOT NULL,
+    language TEXT,
+    -- Which attempt at the problem this was, starting at 1.
+    attempt_number INTEGER NOT NULL,
+    FOREIGN KEY(problem_id) REFERENCES problems(id)
+);
+
+CREATE INDEX idx_attempt_history_problem ON attempt_history(problem_id);
+
+-- Earlier attempts were never stored, but each problem's latest one is.
+INSERT INTO attempt_history (problem_id, attempted_on, rating, language, attempt_number)
+SELECT problem_id, last_attempted, attempt_rating, language, number_of_attempts
+FROM progress;
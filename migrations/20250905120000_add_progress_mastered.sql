@@ -0,0 +1,2 @@
+-- Problems marked mastered are never scheduled for review again.
+ALTER TABLE progress ADD COLUMN mastered BOOLEAN NOT NULL DEFAULT 0;
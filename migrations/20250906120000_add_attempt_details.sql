@@ -0,0 +1,4 @@
+-- How long an attempt took and anything worth remembering about it. Both are
+-- optional, and attempts logged before this migration have neither.
+ALTER TABLE attempt_history ADD COLUMN duration_minutes INTEGER;
+ALTER TABLE attempt_history ADD COLUMN note TEXT;
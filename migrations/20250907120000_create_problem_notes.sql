@@ -0,0 +1,8 @@
+-- One free-form note per problem, set with `track note`. Notes about a single
+-- attempt live on its attempt_history row instead.
+CREATE TABLE problem_notes (
+    problem_id INTEGER PRIMARY KEY,
+    note TEXT NOT NULL,
+    updated_on DATE NOT NULL,
+    FOREIGN KEY(problem_id) REFERENCES problems(id)
+);
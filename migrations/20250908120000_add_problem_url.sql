@@ -0,0 +1,3 @@
+-- The problem's page, from the bank file. Problems built before this column
+-- existed get it the next time their bank is built.
+ALTER TABLE problems ADD COLUMN url TEXT;
@@ -0,0 +1,6 @@
+-- LeetCode ids already looked up for a problem slug, so rebuilding a bank
+-- doesn't run the lookup for problems it has seen before.
+CREATE TABLE resolved_ids (
+    slug TEXT PRIMARY KEY,
+    problem_id INTEGER NOT NULL
+);
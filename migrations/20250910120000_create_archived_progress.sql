@@ -0,0 +1,15 @@
+-- Progress for problems removed with `prune` or `--build --prune`, kept so
+-- it isn't lost when a problem is dropped from its bank.
+CREATE TABLE archived_progress (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    problem_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    deck TEXT NOT NULL,
+    last_attempted DATE NOT NULL,
+    attempt_rating TEXT NOT NULL,
+    next_attempt_date DATE,
+    number_of_attempts INTEGER NOT NULL,
+    language TEXT,
+    mastered BOOLEAN NOT NULL,
+    archived_on DATE NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Which decks each problem is in. A problem shared by two banks (say
+-- blind-75 and neetcode-150) is filed under both, with one progress row.
+CREATE TABLE deck_problems (
+    deck TEXT NOT NULL,
+    problem_id INTEGER NOT NULL,
+    PRIMARY KEY (deck, problem_id)
+);
+
+CREATE INDEX idx_deck_problems_problem ON deck_problems(problem_id);
+
+INSERT INTO deck_problems (deck, problem_id) SELECT deck, id FROM problems;
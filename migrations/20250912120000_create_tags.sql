@@ -0,0 +1,13 @@
+-- Topics from the bank files ("dp", "graph", ...), shared between problems.
+CREATE TABLE tags (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE problem_tags (
+    problem_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL REFERENCES tags(id),
+    PRIMARY KEY (problem_id, tag_id)
+);
+
+CREATE INDEX idx_problem_tags_tag ON problem_tags(tag_id);
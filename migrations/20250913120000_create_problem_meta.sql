@@ -0,0 +1,10 @@
+-- Metadata fetched from LeetCode by `--build --fetch-meta` or
+-- `track refresh-meta`. The canonical difficulty and topic tags go straight
+-- into problems and problem_tags; this keeps the rest and when it was fetched.
+CREATE TABLE problem_meta (
+    problem_id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    acceptance_rate REAL,
+    fetched_on DATE NOT NULL,
+    FOREIGN KEY(problem_id) REFERENCES problems(id)
+);
@@ -0,0 +1,24 @@
+-- Problems taken out of `track next` with `track suspend`, or put back with
+-- `track unsuspend`. Leeches are suspended unless they have a row here.
+CREATE TABLE problem_suspensions (
+    problem_id INTEGER PRIMARY KEY,
+    suspended INTEGER NOT NULL,
+    updated_on DATE NOT NULL,
+    FOREIGN KEY(problem_id) REFERENCES problems(id)
+);
+
+-- Problems failed (ShortFail or LongFail) four or more times.
+CREATE VIEW leeches AS
+SELECT problem_id, COUNT(*) AS failures
+FROM attempt_history
+WHERE rating IN ('ShortFail', 'LongFail')
+GROUP BY problem_id
+HAVING COUNT(*) >= 4;
+
+-- Problems `track next` skips: ones suspended by hand, and leeches nobody
+-- unsuspended.
+CREATE VIEW suspended_problems AS
+SELECT problem_id FROM problem_suspensions WHERE suspended = 1
+UNION
+SELECT problem_id FROM leeches
+WHERE problem_id NOT IN (SELECT problem_id FROM problem_suspensions);
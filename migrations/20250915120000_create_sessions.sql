@@ -0,0 +1,10 @@
+-- Timed practice sessions. `track start` opens one and `track stop` closes
+-- it, logging an attempt with the time it measured. Only one is open (with
+-- no stopped_at) at a time.
+CREATE TABLE sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    problem_id INTEGER NOT NULL,
+    started_at DATETIME NOT NULL,
+    stopped_at DATETIME,
+    FOREIGN KEY(problem_id) REFERENCES problems(id)
+);
@@ -0,0 +1,3 @@
+-- The time actually spent working, for sessions with breaks in them such as
+-- `track pomodoro`. NULL means the whole span from started_at to stopped_at.
+ALTER TABLE sessions ADD COLUMN focused_seconds INTEGER;
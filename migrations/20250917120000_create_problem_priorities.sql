@@ -0,0 +1,8 @@
+-- Priority levels set with `track set-priority`: 1 for high, -1 for low.
+-- Problems without a row are normal. Within a week, `track next` and
+-- `track today` suggest higher levels first.
+CREATE TABLE problem_priorities (
+    problem_id INTEGER PRIMARY KEY,
+    level INTEGER NOT NULL,
+    FOREIGN KEY(problem_id) REFERENCES problems(id)
+);
@@ -0,0 +1,4 @@
+-- Ids used to come from LeetCode's internal questionId, which differs from
+-- the number LeetCode shows for many problems. Lookups now return that
+-- number (questionFrontendId), so the old cache would mix the two.
+DELETE FROM resolved_ids;
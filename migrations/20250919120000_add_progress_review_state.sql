@@ -0,0 +1,50 @@
+-- Where each problem stands in the SM-2 schedule: its run of successful
+-- attempts since the last failure, and its ease factor.
+ALTER TABLE progress ADD COLUMN repetitions INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE progress ADD COLUMN ease REAL NOT NULL DEFAULT 2.5;
+
+-- Each problem replays its history with the default adjustments: Easy
+-- raises the ease by 0.1 and Messy lowers it by 0.14, never below 1.3, and
+-- a failure resets the run. Due dates already set are left as they are.
+--
+-- Problems logged before history was kept start from the single row
+-- 20250904120000 backfilled, numbered with all their attempts so far. Their
+-- earlier ratings are gone, so that row stands for a run of that many
+-- successes unless it was a failure, keeping the intervals they had earned;
+-- only its own rating adjusts the ease.
+WITH RECURSIVE
+    ordered AS (
+        SELECT
+            problem_id,
+            attempt_number,
+            rating IN ('LongFail', 'ShortFail') AS failed,
+            CASE rating WHEN 'Easy' THEN 0.1 WHEN 'Messy' THEN -0.14 ELSE 0 END AS adjustment,
+            ROW_NUMBER() OVER (PARTITION BY problem_id ORDER BY attempt_number, id) AS n
+        FROM attempt_history
+    ),
+    replay (problem_id, n, repetitions, ease) AS (
+        SELECT
+            problem_id,
+            1,
+            CASE WHEN failed THEN 0 ELSE attempt_number END,
+            MAX(1.3, 2.5 + adjustment)
+        FROM ordered
+        WHERE n = 1
+        UNION ALL
+        SELECT
+            r.problem_id,
+            o.n,
+            CASE WHEN o.failed THEN 0 ELSE r.repetitions + 1 END,
+            MAX(1.3, r.ease + o.adjustment)
+        FROM replay r
+        JOIN ordered o ON o.problem_id = r.problem_id AND o.n = r.n + 1
+    ),
+    latest AS (
+        SELECT problem_id, repetitions, ease
+        FROM replay r
+        WHERE n = (SELECT MAX(n) FROM ordered o WHERE o.problem_id = r.problem_id)
+    )
+UPDATE progress
+SET repetitions = latest.repetitions, ease = latest.ease
+FROM latest
+WHERE latest.problem_id = progress.problem_id;
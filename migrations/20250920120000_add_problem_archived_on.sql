@@ -0,0 +1,5 @@
+-- When a problem left its last deck. Archived problems keep their progress,
+-- history, notes, tags and sessions; rejoining a deck clears this. Problems
+-- pruned before this migration were deleted, with only their progress kept
+-- in `archived_progress`.
+ALTER TABLE problems ADD COLUMN archived_on DATE;
@@ -0,0 +1,195 @@
+// src/anki.rs
+
+/// Renders a tab-separated deck suitable for Anki's "Basic" note import.
+///
+/// Each line is one card: the front is `Problem #<id>: <name>` and the back
+/// is the problem's URL followed by its difficulty. Only problems whose latest
+/// rating is below Easy are included, since those are the ones worth drilling.
+pub fn render_anki_deck(progress: &[ProgressView]) -> String {
+    let mut deck = String::new();
+    for item in progress {
+        if item.attempt_rating >= AttemptRating::Easy {
+            continue;
+        }
+
+        let front = format!("Problem #{}: {}", item.problem_id, item.name);
+        let difficulty = item
+            .difficulty
+            .map(|d| format!("{:?}", d))
+            .unwrap_or_else(|| "Unknown".to_string());
+        let url = item.url.clone().unwrap_or_else(|| leetcode_url(&item.name));
+        let back = format!("{} ({})", url, difficulty);
+
+        deck.push_str(&sanitize_field(&front));
+        deck.push('\t');
+        deck.push_str(&sanitize_field(&back));
+        deck.push('\n');
+    }
+    deck
+}
+
+/// Renders the problems due for review by `today` as a tab-separated deck
+/// for `track export --anki`.
+///
+/// The front is `Problem #<id>: <name>` and the problem's URL. The back is its `track note`
+/// and the note from its latest attempt that had one, so each card reminds
+/// you of your last approach. Fields are HTML, and header lines tell Anki
+/// (2.1.55 or later) the separator and that HTML is allowed.
+pub fn render_due_anki_deck(
+    progress: &[ProgressView],
+    attempts: &[AttemptRecord],
+    notes: &HashMap<i64, String>,
+    today: NaiveDate,
+) -> String {
+    // Attempts come oldest first, so later ones overwrite earlier ones.
+    let mut approaches: HashMap<i64, &AttemptRecord> = HashMap::new();
+    for attempt in attempts.iter().filter(|a| a.note.is_some()) {
+        approaches.insert(attempt.problem_id, attempt);
+    }
+
+    let mut deck = String::from("#separator:tab\n#html:true\n");
+    for item in progress {
+        if item.mastered || item.next_attempt_date.is_none_or(|date| date > today) {
+            continue;
+        }
+
+        let url = item.url.clone().unwrap_or_else(|| leetcode_url(&item.name));
+        let front = format!(
+            "Problem #{}: {}<br><a href=\"{url}\">{url}</a>",
+            item.problem_id,
+            escape_html(&item.name),
+            url = escape_html(&url)
+        );
+
+        let mut back = Vec::new();
+        if let Some(note) = notes.get(&item.problem_id) {
+            back.push(format!("<b>Notes:</b> {}", escape_html(note)));
+        }
+        if let Some(attempt) = approaches.get(&item.problem_id) {
+            back.push(format!(
+                "<b>Last approach</b> ({}, {}): {}",
+                attempt.attempted_on,
+                attempt.rating,
+                escape_html(attempt.note.as_deref().unwrap_or_default())
+            ));
+        }
+        if back.is_empty() {
+            back.push(format!(
+                "No notes yet. Last rated {} on {}.",
+                item.attempt_rating, item.last_attempted
+            ));
+        }
+
+        deck.push_str(&sanitize_field(&front));
+        deck.push('\t');
+        deck.push_str(&sanitize_field(&back.join("<br>")));
+        deck.push('\n');
+    }
+    deck
+}
+
+/// Escapes text for an HTML field, turning newlines into line breaks.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+/// Tabs and newlines would split a card into extra fields or notes.
+fn sanitize_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+use crate::db::{AttemptRecord, ProgressView};
+use crate::problem_attempts::AttemptRating;
+use crate::problems::leetcode_url;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::LeetCodeDifficulty;
+
+    fn progress(id: i64, name: &str, rating: AttemptRating) -> ProgressView {
+        ProgressView {
+            problem_id: id,
+            name: name.to_string(),
+            difficulty: Some(LeetCodeDifficulty::Medium),
+            last_attempted: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            attempt_rating: rating,
+            number_of_attempts: 1,
+            mastered: false,
+            next_attempt_date: None,
+            duration_minutes: None,
+            note: None,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn includes_only_problems_rated_below_easy() {
+        let deck = render_anki_deck(&[
+            progress(208, "Implement Trie (Prefix Tree)", AttemptRating::Messy),
+            progress(1, "Two Sum", AttemptRating::Easy),
+        ]);
+
+        assert_eq!(
+            deck,
+            "Problem #208: Implement Trie (Prefix Tree)\thttps://leetcode.com/problems/implement-trie-prefix-tree/ (Medium)\n"
+        );
+    }
+
+    #[test]
+    fn each_card_has_exactly_two_fields() {
+        let deck = render_anki_deck(&[progress(3, "Odd\tName", AttemptRating::ShortFail)]);
+        for line in deck.lines() {
+            assert_eq!(line.split('\t').count(), 2);
+        }
+    }
+
+    #[test]
+    fn prefers_the_stored_url() {
+        let mut item = progress(146, "LRU Cache", AttemptRating::Hard);
+        item.url = Some("https://leetcode.com/problems/lru-cache/description/".to_string());
+
+        let deck = render_anki_deck(&[item]);
+
+        assert!(deck.contains("\thttps://leetcode.com/problems/lru-cache/description/ (Medium)"));
+    }
+
+    #[test]
+    fn due_cards_show_notes_and_the_last_approach() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let mut due = progress(146, "LRU Cache", AttemptRating::Hard);
+        due.next_attempt_date = Some(today);
+        let mut later = progress(1, "Two Sum", AttemptRating::Hard);
+        later.next_attempt_date = Some(NaiveDate::from_ymd_opt(2024, 3, 9).unwrap());
+        let attempt = |note: &str| AttemptRecord {
+            problem_id: 146,
+            name: "LRU Cache".to_string(),
+            difficulty: None,
+            attempt_number: 1,
+            attempted_on: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            rating: AttemptRating::Hard,
+            language: None,
+            duration_minutes: None,
+            note: Some(note.to_string()),
+        };
+        let attempts = [attempt("two stacks"), attempt("map + linked list")];
+        let notes = HashMap::from([(146, "evict the <oldest>".to_string())]);
+
+        let deck = render_due_anki_deck(&[due, later], &attempts, &notes, today);
+
+        let cards: Vec<&str> = deck.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(cards.len(), 1);
+        let (front, back) = cards[0].split_once('\t').unwrap();
+        assert!(front.starts_with("Problem #146: LRU Cache<br>"));
+        assert_eq!(
+            back,
+            "<b>Notes:</b> evict the &lt;oldest&gt;<br><b>Last approach</b> (2024-03-01, Hard): map + linked list"
+        );
+    }
+}
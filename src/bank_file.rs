@@ -0,0 +1,595 @@
+// src/bank_file.rs
+
+/// A bank file opened for editing by the `track bank` commands.
+///
+/// Entries are kept as JSON objects rather than `ProblemBankProblem`s, so a
+/// rewrite keeps fields track doesn't read, and keys are written back in the
+/// order they were found.
+#[derive(Debug, Clone)]
+pub struct BankFile {
+    path: PathBuf,
+    entries: Vec<Value>,
+}
+
+impl BankFile {
+    /// Reads the bank at `path`, which must already be valid.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read, or if it wouldn't build:
+    /// editing a broken bank would only bury the problem.
+    pub fn open(path: &Path) -> anyhow::Result<BankFile> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read bank file {}", path.display()))?;
+        BankFile::parse(path, &contents)
+    }
+
+    /// An empty bank, to be saved to `path`.
+    pub fn new(path: &Path) -> BankFile {
+        BankFile {
+            path: path.to_path_buf(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Like `open`, for contents that have already been read.
+    pub fn parse(path: &Path, contents: &str) -> anyhow::Result<BankFile> {
+        parse_problems(contents).with_context(|| {
+            format!(
+                "{} is invalid; run `track bank validate` for details",
+                path.display()
+            )
+        })?;
+        let entries = serde_json::from_str(contents)?;
+        Ok(BankFile {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    /// Where the bank is saved.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How many problems the bank has.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the bank has no problems.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The `order` a problem added at the end of the bank gets.
+    pub fn next_order(&self) -> i64 {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry["order"].as_i64())
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// The index of the entry for the problem with `id` or, since a bank's
+    /// ids may be placeholders, the one whose URL has the same slug as `url`.
+    /// An `id` of 0 is a placeholder too, so only the URL is matched.
+    pub fn find(&self, id: i64, url: Option<&str>) -> Option<usize> {
+        let slug = url.and_then(url_slug);
+        self.entries
+            .iter()
+            .position(|entry| id != 0 && entry["id"].as_i64() == Some(id))
+            .or_else(|| {
+                let slug = slug?;
+                self.entries
+                    .iter()
+                    .position(|entry| entry["url"].as_str().and_then(url_slug) == Some(slug))
+            })
+    }
+
+    /// The index of the entry `problem` names: its id, its URL slug, its name
+    /// or a unique prefix of its name, ignoring case and punctuation.
+    ///
+    /// # Errors
+    /// Returns an error if no entry matches, or if several match equally well.
+    pub fn lookup(&self, problem: &str) -> anyhow::Result<usize> {
+        let text = problem.trim();
+        let id = text.parse::<i64>().ok().filter(|&id| id != 0);
+        let slug = url_slug(text).unwrap_or(text).to_lowercase();
+        let wanted = normalize(text);
+        let name = |entry: &Value| normalize(entry["name"].as_str().unwrap_or_default());
+        let tiers: [&dyn Fn(&Value) -> bool; 4] = [
+            &|entry| id.is_some() && entry["id"].as_i64() == id,
+            &|entry| entry["url"].as_str().and_then(url_slug) == Some(slug.as_str()),
+            &|entry| name(entry) == wanted,
+            &|entry| !wanted.is_empty() && name(entry).starts_with(&wanted),
+        ];
+
+        for matches_tier in tiers {
+            let found: Vec<usize> = (0..self.entries.len())
+                .filter(|&index| matches_tier(&self.entries[index]))
+                .collect();
+            match found.as_slice() {
+                [] => continue,
+                [only] => return Ok(*only),
+                several => {
+                    let listed: Vec<String> = several
+                        .iter()
+                        .map(|&index| format!("  {}", self.name(index)))
+                        .collect();
+                    anyhow::bail!(
+                        "'{}' matches {} problems in {}. Use a longer name or the URL slug:\n{}",
+                        text,
+                        several.len(),
+                        self.path.display(),
+                        listed.join("\n")
+                    );
+                }
+            }
+        }
+        anyhow::bail!("No problem in {} matches '{}'.", self.path.display(), text)
+    }
+
+    /// The name of the entry at `index`.
+    pub fn name(&self, index: usize) -> &str {
+        self.entries[index]["name"].as_str().unwrap_or_default()
+    }
+
+    /// The id and URL of the entry at `index`, to `find` it in another bank.
+    pub fn key(&self, index: usize) -> (i64, Option<&str>) {
+        let entry = &self.entries[index];
+        (
+            entry["id"].as_i64().unwrap_or_default(),
+            entry["url"].as_str(),
+        )
+    }
+
+    /// The order and week of the entry at `index`.
+    pub fn position(&self, index: usize) -> (i64, Option<i64>) {
+        let entry = &self.entries[index];
+        (
+            entry["order"].as_i64().unwrap_or_default(),
+            entry["week"].as_i64(),
+        )
+    }
+
+    /// Adds `problem` at the end of the bank, with its order, week, id,
+    /// name, difficulty, URL and `tags`.
+    pub fn push(&mut self, problem: &Problem, tags: &[String]) {
+        self.push_entry(&ProblemBankProblem {
+            id: problem.id,
+            order: problem.order,
+            name: problem.name.clone(),
+            difficulty: problem.difficulty,
+            week: problem.week,
+            url: problem.link(),
+            tags: tags.to_vec(),
+        });
+    }
+
+    /// Adds `entry` at the end of the bank as it is.
+    pub fn push_entry(&mut self, entry: &ProblemBankProblem) {
+        let mut object = Map::new();
+        object.insert("order".to_string(), json!(entry.order));
+        if let Some(week) = entry.week {
+            object.insert("week".to_string(), json!(week));
+        }
+        object.insert("id".to_string(), json!(entry.id));
+        object.insert("name".to_string(), json!(entry.name));
+        if let Some(difficulty) = entry.difficulty {
+            object.insert("difficulty".to_string(), json!(difficulty));
+        }
+        object.insert("url".to_string(), json!(entry.url));
+        if !entry.tags.is_empty() {
+            object.insert("tags".to_string(), json!(entry.tags));
+        }
+        self.entries.push(Value::Object(object));
+    }
+
+    /// Sets the week of the entry at `index`.
+    pub fn set_week(&mut self, index: usize, week: i64) {
+        self.entries[index]["week"] = json!(week);
+    }
+
+    /// Moves the entry at `index` to just before the one at `target` in
+    /// `order`, or just after it if `after`, then renumbers the bank. The
+    /// moved entry takes `target`'s week, if it has one, to stay beside it.
+    pub fn move_entry(&mut self, index: usize, target: usize, after: bool) {
+        let week = self.entries[target]["week"].as_i64();
+        let mut ranked: Vec<(usize, Value)> = self.entries.drain(..).enumerate().collect();
+        ranked.sort_by_key(|(_, entry)| entry["order"].as_i64());
+        let from = ranked
+            .iter()
+            .position(|(i, _)| *i == index)
+            .expect("index is in the bank");
+        let mut moved = ranked.remove(from);
+        if let Some(week) = week {
+            moved.1["week"] = json!(week);
+        }
+        let to = ranked
+            .iter()
+            .position(|(i, _)| *i == target)
+            .expect("target is in the bank");
+        ranked.insert(to + usize::from(after), moved);
+        self.entries = ranked.into_iter().map(|(_, entry)| entry).collect();
+        self.number();
+    }
+
+    /// Sorts the entries by `order` and renumbers them from 1 with no gaps.
+    /// Returns how many entries' orders changed.
+    pub fn renumber(&mut self) -> usize {
+        self.sort_by_order();
+        self.number()
+    }
+
+    /// Sets each entry's `order` to its place in the file, counting from 1.
+    /// Returns how many changed.
+    fn number(&mut self) -> usize {
+        let mut changed = 0;
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let order = index as i64 + 1;
+            if entry["order"].as_i64() != Some(order) {
+                entry["order"] = json!(order);
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// Sorts the entries by `order` and puts them into weeks of `per_week`
+    /// entries each, starting from week 1. Returns how many entries' weeks
+    /// changed.
+    pub fn assign_weeks(&mut self, per_week: u32) -> usize {
+        self.sort_by_order();
+        let mut changed = 0;
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let week = (index / per_week.max(1) as usize) as i64 + 1;
+            if entry["week"].as_i64() != Some(week) {
+                entry["week"] = json!(week);
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    /// The highest week in the bank, or 0 if it has none.
+    pub fn weeks(&self) -> i64 {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry["week"].as_i64())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn sort_by_order(&mut self) {
+        self.entries.sort_by_key(|entry| entry["order"].as_i64());
+    }
+
+    /// Removes the entry at `index`, returning its name.
+    pub fn remove(&mut self, index: usize) -> String {
+        let name = self.name(index).to_string();
+        self.entries.remove(index);
+        name
+    }
+
+    /// The bank as pretty-printed JSON, ending in a newline.
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(&self.entries).expect("JSON values serialize");
+        json.push('\n');
+        json
+    }
+
+    /// Writes the bank back to its file.
+    pub fn save(&self) -> anyhow::Result<()> {
+        fs::write(&self.path, self.to_json())
+            .with_context(|| format!("Failed to write bank file {}", self.path.display()))
+    }
+}
+
+/// The fields `diff_banks` compares. Order isn't one of them: inserting one
+/// problem renumbers everything after it.
+const COMPARED_FIELDS: [&str; 5] = ["id", "name", "difficulty", "week", "tags"];
+
+/// How one bank differs from another. Problems are matched by id, or by URL
+/// slug where an id is a placeholder.
+#[derive(Debug, Default)]
+pub struct BankDiff {
+    /// Indices in the new bank of problems the old one doesn't have.
+    pub added: Vec<usize>,
+    /// Indices in the old bank of problems the new one doesn't have.
+    pub removed: Vec<usize>,
+    /// Problems in both banks whose fields differ.
+    pub changed: Vec<ChangedEntry>,
+}
+
+impl BankDiff {
+    /// Whether the banks hold the same problems with the same fields.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A problem in both banks of a `BankDiff` whose fields differ.
+#[derive(Debug)]
+pub struct ChangedEntry {
+    /// Its index in the old bank.
+    pub old: usize,
+    /// Its index in the new bank.
+    pub new: usize,
+    /// One line per changed field, e.g. "week 1 -> 3".
+    pub changes: Vec<String>,
+}
+
+/// What was added to, removed from and changed in `old` to give `new`.
+pub fn diff_banks(old: &BankFile, new: &BankFile) -> BankDiff {
+    let mut diff = BankDiff::default();
+    let mut matched = HashSet::new();
+    for index in 0..old.len() {
+        let (id, url) = old.key(index);
+        let Some(new_index) = new.find(id, url) else {
+            diff.removed.push(index);
+            continue;
+        };
+        matched.insert(new_index);
+        let (old_entry, new_entry) = (&old.entries[index], &new.entries[new_index]);
+        let changes: Vec<String> = COMPARED_FIELDS
+            .iter()
+            .filter(|&&field| old_entry[field] != new_entry[field])
+            .map(|&field| {
+                format!(
+                    "{} {} -> {}",
+                    field,
+                    show_field(&old_entry[field]),
+                    show_field(&new_entry[field])
+                )
+            })
+            .collect();
+        if !changes.is_empty() {
+            diff.changed.push(ChangedEntry {
+                old: index,
+                new: new_index,
+                changes,
+            });
+        }
+    }
+    diff.added = (0..new.len())
+        .filter(|index| !matched.contains(index))
+        .collect();
+    diff
+}
+
+/// A field's value as `diff_banks` shows it: strings without quotes, and
+/// "none" when it's missing.
+fn show_field(value: &Value) -> String {
+    match value {
+        Value::Null => "none".to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Combines `banks` into one bank to be saved at `path`: the first bank's
+/// problems, then those of each later bank that aren't in it yet, matched as
+/// `diff_banks` matches them. Where a problem is in several banks, the
+/// first bank's entry is kept.
+///
+/// Orders run on from 1 with no gaps, and each bank's weeks are shifted to
+/// start after the previous banks' last week, so the banks are studied one
+/// after the other. Returns the bank and how many duplicates were dropped.
+pub fn merge_banks(path: &Path, banks: &[BankFile]) -> (BankFile, usize) {
+    let mut merged = BankFile::new(path);
+    let mut duplicates = 0;
+    for bank in banks {
+        let week_offset = merged.weeks();
+        let mut bank = bank.clone();
+        bank.sort_by_order();
+        for (index, entry) in bank.entries.iter().enumerate() {
+            let (id, url) = bank.key(index);
+            if merged.find(id, url).is_some() {
+                duplicates += 1;
+                continue;
+            }
+            let mut entry = entry.clone();
+            entry["order"] = json!(merged.len() as i64 + 1);
+            if let Some(week) = entry["week"].as_i64() {
+                entry["week"] = json!(week + week_offset);
+            }
+            merged.entries.push(entry);
+        }
+    }
+    (merged, duplicates)
+}
+
+/// The bank file `file` names: `file` itself if it exists, else the file of
+/// that name in `static_dir`, as `--build` looks for it.
+pub fn resolve_bank_path(file: &Path, static_dir: &Path) -> PathBuf {
+    if file.is_file() {
+        file.to_path_buf()
+    } else {
+        static_dir.join(file)
+    }
+}
+
+use crate::db::url_slug;
+use crate::duplicates::normalize;
+use crate::problem_bank::{ProblemBankProblem, parse_problems};
+use crate::problems::Problem;
+use anyhow::Context;
+use serde_json::{Map, Value, json};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::LeetCodeDifficulty;
+
+    const BANK: &str = r#"[
+  {
+    "order": 1,
+    "week": 1,
+    "id": 0,
+    "name": "Two Sum",
+    "url": "https://leetcode.com/problems/two-sum/",
+    "source": "my notes"
+  },
+  {
+    "order": 2,
+    "week": 1,
+    "id": 20,
+    "name": "Valid Parentheses",
+    "url": "https://leetcode.com/problems/valid-parentheses/"
+  }
+]"#;
+
+    #[test]
+    fn finds_entries_by_id_or_url_slug() {
+        let bank = BankFile::parse(Path::new("bank.json"), BANK).unwrap();
+
+        assert_eq!(bank.find(20, None), Some(1));
+        assert_eq!(
+            bank.find(
+                1,
+                Some("https://leetcode.com/problems/two-sum/description/")
+            ),
+            Some(0)
+        );
+        assert_eq!(bank.find(1, None), None);
+        assert_eq!(bank.next_order(), 3);
+    }
+
+    #[test]
+    fn edits_keep_unknown_fields() {
+        let mut bank = BankFile::parse(Path::new("bank.json"), BANK).unwrap();
+        bank.set_week(0, 2);
+        assert_eq!(bank.remove(1), "Valid Parentheses");
+        bank.push(
+            &Problem {
+                id: 207,
+                order: 3,
+                name: "Course Schedule".to_string(),
+                difficulty: Some(LeetCodeDifficulty::Medium),
+                week: Some(5),
+                priority: 0,
+                deck: "mine".to_string(),
+                url: Some("https://leetcode.com/problems/course-schedule/".to_string()),
+            },
+            &["graph".to_string()],
+        );
+
+        let edited = BankFile::parse(Path::new("bank.json"), &bank.to_json()).unwrap();
+        assert_eq!(edited.len(), 2);
+        assert_eq!(edited.entries[0]["week"], 2);
+        assert_eq!(edited.entries[0]["source"], "my notes");
+        assert_eq!(edited.entries[1]["difficulty"], "Medium");
+        assert_eq!(edited.entries[1]["tags"], json!(["graph"]));
+        assert!(
+            bank.to_json()
+                .starts_with("[\n  {\n    \"order\": 1,\n    \"week\": 2,")
+        );
+    }
+
+    #[test]
+    fn looks_entries_up_by_id_slug_or_name() {
+        let bank = BankFile::parse(Path::new("bank.json"), BANK).unwrap();
+
+        assert_eq!(bank.lookup("20").unwrap(), 1);
+        assert_eq!(bank.lookup("two-sum").unwrap(), 0);
+        assert_eq!(bank.lookup("valid paren").unwrap(), 1);
+        assert!(bank.lookup("0").is_err());
+        assert!(bank.lookup("Course Schedule").is_err());
+    }
+
+    #[test]
+    fn moves_entries_and_renumbers() {
+        let mut bank = BankFile::parse(Path::new("bank.json"), BANK).unwrap();
+        bank.push_entry(&ProblemBankProblem {
+            id: 207,
+            order: 10,
+            name: "Course Schedule".to_string(),
+            difficulty: None,
+            week: Some(3),
+            url: "https://leetcode.com/problems/course-schedule/".to_string(),
+            tags: Vec::new(),
+        });
+
+        bank.move_entry(2, 0, false);
+
+        let names: Vec<&str> = (0..bank.len()).map(|index| bank.name(index)).collect();
+        assert_eq!(names, ["Course Schedule", "Two Sum", "Valid Parentheses"]);
+        assert_eq!(bank.position(0), (1, Some(1)));
+        assert_eq!(bank.position(2), (3, Some(1)));
+        assert_eq!(bank.renumber(), 0);
+
+        bank.move_entry(0, 2, true);
+        assert_eq!(bank.name(2), "Course Schedule");
+        assert_eq!(bank.assign_weeks(2), 1);
+        assert_eq!(bank.position(2), (3, Some(2)));
+        assert_eq!(bank.weeks(), 2);
+    }
+
+    const COMPANY_BANK: &str = r#"[
+  {
+    "order": 1,
+    "week": 1,
+    "id": 1,
+    "name": "Two Sum",
+    "difficulty": "Easy",
+    "url": "https://leetcode.com/problems/two-sum/"
+  },
+  {
+    "order": 2,
+    "week": 2,
+    "id": 0,
+    "name": "Course Schedule",
+    "url": "https://leetcode.com/problems/course-schedule/"
+  },
+  {
+    "order": 3,
+    "week": 2,
+    "id": 0,
+    "name": "Valid Parentheses",
+    "url": "https://leetcode.com/problems/valid-parentheses/"
+  }
+]"#;
+
+    #[test]
+    fn diffs_banks_by_id_or_url_slug() {
+        let old = BankFile::parse(Path::new("old.json"), BANK).unwrap();
+        let new = BankFile::parse(Path::new("new.json"), COMPANY_BANK).unwrap();
+
+        let diff = diff_banks(&old, &new);
+
+        assert_eq!(diff.added, [1]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(
+            diff.changed[0].changes,
+            ["id 0 -> 1", "difficulty none -> Easy"]
+        );
+        assert_eq!(diff.changed[1].changes, ["id 20 -> 0", "week 1 -> 2"]);
+        assert!(diff_banks(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn merges_banks_one_after_another() {
+        let first = BankFile::parse(Path::new("first.json"), BANK).unwrap();
+        let second = BankFile::parse(Path::new("second.json"), COMPANY_BANK).unwrap();
+
+        let (merged, duplicates) = merge_banks(Path::new("merged.json"), &[first, second]);
+
+        assert_eq!(duplicates, 2);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.name(2), "Course Schedule");
+        assert_eq!(merged.position(2), (3, Some(3)));
+        assert_eq!(merged.entries[0]["source"], "my notes");
+        BankFile::parse(Path::new("merged.json"), &merged.to_json()).unwrap();
+    }
+
+    #[test]
+    fn refuses_to_edit_an_invalid_bank() {
+        let broken = r#"[{"id": 1, "order": 1, "name": "Two Sum"}]"#;
+        assert!(BankFile::parse(Path::new("bank.json"), broken).is_err());
+    }
+}
@@ -0,0 +1,524 @@
+// src/bank_import.rs
+
+/// Headers of the CSV column holding problem names, lowercased.
+const NAME_HEADERS: [&str; 4] = ["name", "title", "problem", "question"];
+
+/// Headers of the CSV column holding problem URLs, lowercased.
+const URL_HEADERS: [&str; 3] = ["url", "link", "leetcode"];
+
+/// Headers of the CSV column holding topic tags, lowercased.
+const TAG_HEADERS: [&str; 4] = ["tags", "tag", "topics", "topic"];
+
+/// Link texts that say nothing about the problem, such as the "Link" in a
+/// table's link column. Problems linked like this are named from their slug.
+const GENERIC_LINK_TEXTS: [&str; 6] = ["link", "leetcode", "problem", "question", "solve", "here"];
+
+/// How many problems `track bank import-url` puts in each week unless told
+/// otherwise.
+pub const DEFAULT_PROBLEMS_PER_WEEK: u32 = 10;
+
+/// How long fetching a list page may take before it's abandoned.
+const FETCH_TIMEOUT_SECONDS: &str = "30";
+
+/// Turns a CSV list of problems into bank entries, one per row whose URL
+/// column holds a LeetCode problem URL. Other rows are skipped.
+///
+/// `url_column` is 1-based; without it, a column headed `url` or `link` is
+/// used, else the first with a LeetCode URL in it. Columns headed `name`,
+/// `week`, `difficulty` and `tags` fill those fields in when present, and
+/// entries without a name are named after their URL's slug. Ids are left at
+/// 0 for the build to look up.
+///
+/// # Errors
+/// Returns an error if no column holds LeetCode URLs.
+pub fn problems_from_csv(
+    contents: &str,
+    url_column: Option<usize>,
+) -> anyhow::Result<Vec<ProblemBankProblem>> {
+    let rows = parse_csv(contents);
+    let has_header = rows
+        .first()
+        .is_some_and(|row| !row.iter().any(|cell| leetcode_slug(cell).is_some()));
+    let headers: Vec<String> = match (has_header, rows.first()) {
+        (true, Some(row)) => row.iter().map(|h| h.trim().to_lowercase()).collect(),
+        _ => Vec::new(),
+    };
+    let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+    let body = &rows[usize::from(has_header)..];
+
+    let url_column = match url_column {
+        Some(0) => anyhow::bail!("--url-column counts from 1."),
+        Some(column) => column - 1,
+        None => column(&URL_HEADERS)
+            .or_else(|| {
+                body.iter()
+                    .find_map(|row| row.iter().position(|cell| leetcode_slug(cell).is_some()))
+            })
+            .context("No column holds LeetCode problem URLs. Pass --url-column to pick one.")?,
+    };
+    let name_column = column(&NAME_HEADERS);
+    let week_column = column(&["week"]);
+    let difficulty_column = column(&["difficulty", "level"]);
+    let tag_column = column(&TAG_HEADERS);
+
+    let mut builder = BankBuilder::default();
+    for row in body {
+        let cell = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty())
+        };
+        let Some(slug) = cell(Some(url_column)).and_then(leetcode_slug) else {
+            continue;
+        };
+        let tags = cell(tag_column)
+            .map(|tags| {
+                tags.split([';', ',', '|'])
+                    .map(normalize_tag)
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        builder.push(
+            slug,
+            cell(name_column),
+            cell(week_column).and_then(|week| week.parse().ok()),
+            cell(difficulty_column).and_then(parse_difficulty),
+            tags,
+        );
+    }
+    Ok(builder.problems)
+}
+
+/// Turns the LeetCode links in a Markdown file into bank entries, in the
+/// order they first appear. Repeated links are skipped.
+///
+/// Entries are named by their link text, or after their slug when the link
+/// is bare or says something like "Link". A heading with "Week N" in it puts
+/// the links under it in week N, and a line naming a difficulty, such as a
+/// table row with a Medium cell, gives its link that difficulty. Ids are left
+/// at 0 for the build to look up.
+pub fn problems_from_markdown(contents: &str) -> Vec<ProblemBankProblem> {
+    let mut builder = BankBuilder::default();
+    let mut week = None;
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') {
+            if let Some(heading_week) = week_in_heading(trimmed) {
+                week = Some(heading_week);
+            }
+            continue;
+        }
+        let difficulty = line
+            .split(|c: char| !c.is_alphanumeric())
+            .find_map(parse_difficulty);
+        for (text, url) in leetcode_links(line) {
+            let Some(slug) = leetcode_slug(url) else {
+                continue;
+            };
+            let name = text.filter(|text| is_descriptive(text));
+            builder.push(slug, name, week, difficulty, Vec::new());
+        }
+    }
+    builder.problems
+}
+
+/// Turns the LeetCode problem links in an HTML page, such as the NeetCode
+/// roadmap or a LeetCode study plan, into bank entries in page order.
+/// Repeated links are skipped.
+///
+/// Both absolute links and the relative `/problems/<slug>/` ones on
+/// LeetCode's own pages count. Entries are named by their link's text where
+/// it says something, else after their slug. The page gives no weeks or
+/// difficulties; `assign_weeks` can fill the weeks in.
+pub fn problems_from_html(contents: &str) -> Vec<ProblemBankProblem> {
+    let mut builder = BankBuilder::default();
+    for (start, path) in contents.match_indices("/problems/") {
+        let before = &contents[..start];
+        let in_href = in_href(before);
+        if !in_href && !before.ends_with("leetcode.com") && !before.ends_with("leetcode.cn") {
+            continue;
+        }
+        let after = &contents[start + path.len()..];
+        let slug_len = after
+            .find(|c: char| !(c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'))
+            .unwrap_or(after.len());
+        let slug = &after[..slug_len];
+        if slug.is_empty() {
+            continue;
+        }
+        let name = if in_href { anchor_text(after) } else { None };
+        let name = name.filter(|name| is_descriptive(name));
+        builder.push(slug, name.as_deref(), None, None, Vec::new());
+    }
+    builder.problems
+}
+
+/// Puts `problems`, in order, into weeks of `per_week` problems each,
+/// starting from week 1.
+pub fn assign_weeks(problems: &mut [ProblemBankProblem], per_week: u32) {
+    for (index, problem) in problems.iter_mut().enumerate() {
+        problem.week = Some((index / per_week.max(1) as usize) as i64 + 1);
+    }
+}
+
+/// A file name for the bank imported from `url`, after the last part of its
+/// path: `https://leetcode.com/studyplan/top-interview-150/` gives
+/// `top-interview-150.json`.
+pub fn bank_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    // Past the host.
+    let path = path.split_once('/').map_or("", |(_, path)| path);
+    let name = path
+        .split('/')
+        .rfind(|segment| !segment.is_empty())
+        .map(|segment| {
+            // `roadmap.html` is named `roadmap` too.
+            let stem = segment.split('.').next().unwrap_or_default();
+            stem.chars()
+                .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+                .collect::<String>()
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "imported".to_string());
+    format!("{}.json", name)
+}
+
+/// Downloads the page at `url` with curl, following redirects.
+///
+/// # Errors
+/// Returns an error if curl can't be run or the download fails.
+pub async fn fetch_page(url: &str) -> anyhow::Result<String> {
+    let output = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--location",
+            "--compressed",
+            "--max-time",
+            FETCH_TIMEOUT_SECONDS,
+            "--user-agent",
+            concat!("track/", env!("CARGO_PKG_VERSION")),
+            url,
+        ])
+        .output()
+        .await
+        .context("Failed to run curl to fetch the page. Is curl installed?")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "Fetching {} failed ({}): {}",
+            url,
+            output.status,
+            stderr.trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Collects bank entries, skipping slugs it already has.
+#[derive(Default)]
+struct BankBuilder {
+    problems: Vec<ProblemBankProblem>,
+    slugs: HashSet<String>,
+}
+
+impl BankBuilder {
+    fn push(
+        &mut self,
+        slug: &str,
+        name: Option<&str>,
+        week: Option<i64>,
+        difficulty: Option<LeetCodeDifficulty>,
+        tags: Vec<String>,
+    ) {
+        if !self.slugs.insert(slug.to_string()) {
+            return;
+        }
+        self.problems.push(ProblemBankProblem {
+            id: 0,
+            order: self.problems.len() as i64 + 1,
+            name: name.map_or_else(|| name_from_slug(slug), str::to_string),
+            difficulty,
+            week,
+            url: format!("https://leetcode.com/problems/{}/", slug),
+            tags,
+        });
+    }
+}
+
+/// The `[text](url)` links in `line`, plus any bare LeetCode URLs, which come
+/// without text.
+fn leetcode_links(line: &str) -> Vec<(Option<&str>, &str)> {
+    let mut links = Vec::new();
+    let mut rest = line;
+    let mut linked_urls = Vec::new();
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open + 1..];
+        let Some(close) = after.find("](") else {
+            break;
+        };
+        let target = &after[close + 2..];
+        let Some(end) = target.find(')') else {
+            break;
+        };
+        // In `- [x] [Two Sum](...)` the text starts after the checkbox.
+        let text = after[..close].rsplit('[').next().unwrap_or_default().trim();
+        let url = target[..end].trim();
+        links.push((Some(text).filter(|text| !text.is_empty()), url));
+        linked_urls.push(url);
+        rest = &target[end + 1..];
+    }
+    for word in line.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | '<' | '>' | '|'))
+    {
+        if leetcode_slug(word).is_some() && !linked_urls.contains(&word) {
+            links.push((None, word));
+        }
+    }
+    links
+}
+
+/// Whether the text of a link says which problem it is, so isn't a URL or
+/// something like "Link".
+fn is_descriptive(text: &str) -> bool {
+    leetcode_slug(text).is_none() && !GENERIC_LINK_TEXTS.contains(&text.to_lowercase().as_str())
+}
+
+/// Whether the `/problems/` just after `before` is inside an `href`, possibly
+/// after a LeetCode host.
+fn in_href(before: &str) -> bool {
+    let mut before = before;
+    for prefix in [
+        "leetcode.com",
+        "leetcode.cn",
+        "www.",
+        "//",
+        "https:",
+        "http:",
+    ] {
+        before = before.strip_suffix(prefix).unwrap_or(before);
+    }
+    before.ends_with("href=\"") || before.ends_with("href='")
+}
+
+/// The text of the `<a>` whose `href` is followed by `after`, with inner tags
+/// dropped, common entities decoded and spaces collapsed.
+fn anchor_text(after: &str) -> Option<String> {
+    let tag_end = after.find('>')?;
+    if after[..tag_end].contains('<') {
+        return None;
+    }
+    let inner = &after[tag_end + 1..];
+    let inner = &inner[..inner.find("</a>")?];
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in inner.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&amp;", "&")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(text).filter(|text| !text.is_empty())
+}
+
+/// The slug of a LeetCode problem URL in `text`, lowercased, e.g. `two-sum`
+/// for `https://leetcode.com/problems/two-sum/description/`.
+fn leetcode_slug(text: &str) -> Option<&str> {
+    let text = text.trim();
+    if !text.contains("leetcode.com/problems/") && !text.contains("leetcode.cn/problems/") {
+        return None;
+    }
+    url_slug(text).filter(|slug| {
+        slug.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    })
+}
+
+/// A name for a problem known only by its slug: `two-sum` becomes "Two Sum".
+fn name_from_slug(slug: &str) -> String {
+    slug.split('-')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// "Easy", "medium" or "HARD" as a difficulty.
+fn parse_difficulty(text: &str) -> Option<LeetCodeDifficulty> {
+    LeetCodeDifficulty::from_str(text.trim(), true).ok()
+}
+
+/// The N in a heading like "## Week 3: Graphs".
+fn week_in_heading(heading: &str) -> Option<i64> {
+    let lower = heading.to_lowercase();
+    let (_, after) = lower.split_once("week")?;
+    let digits: String = after
+        .trim_start_matches([' ', '#', ':'])
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+/// Splits CSV into rows of fields. Fields may be quoted, with `""` for a
+/// quote, and quoted fields may hold commas and line breaks. Blank lines are
+/// dropped.
+fn parse_csv(contents: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\n' | '\r' if !in_quotes => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|field| !field.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    row.push(field);
+    if row.iter().any(|field| !field.is_empty()) {
+        rows.push(row);
+    }
+    rows
+}
+
+use crate::db::{normalize_tag, url_slug};
+use crate::problem_bank::ProblemBankProblem;
+use crate::problems::LeetCodeDifficulty;
+use anyhow::Context;
+use clap::ValueEnum;
+use std::collections::HashSet;
+use tokio::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_named_columns_from_csv() {
+        let csv = "Problem,Difficulty,Week,Link,Topics\r\n\
+            \"Two Sum, the classic\",Easy,1,https://leetcode.com/problems/two-sum/,Array; Hash Table\r\n\
+            Arrays done!,,,,\r\n\
+            ,medium,2,https://leetcode.com/problems/3sum/description/,\r\n\
+            Two Sum again,Easy,3,https://leetcode.com/problems/two-sum/,\r\n";
+
+        let problems = problems_from_csv(csv, None).unwrap();
+
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].name, "Two Sum, the classic");
+        assert_eq!(problems[0].difficulty, Some(LeetCodeDifficulty::Easy));
+        assert_eq!(problems[0].tags, ["array", "hash table"]);
+        assert_eq!(problems[1].name, "3sum");
+        assert_eq!(problems[1].order, 2);
+        assert_eq!(problems[1].week, Some(2));
+        assert_eq!(problems[1].url, "https://leetcode.com/problems/3sum/");
+    }
+
+    #[test]
+    fn finds_the_url_column_without_a_header() {
+        let csv = "1,Two Sum,https://leetcode.com/problems/two-sum\n2,Add Two Numbers,https://leetcode.com/problems/add-two-numbers/\n";
+
+        let problems = problems_from_csv(csv, None).unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[1].name, "Add Two Numbers");
+        assert!(problems_from_csv(csv, Some(2)).unwrap().is_empty());
+        assert!(problems_from_csv("a,b\n1,2\n", None).is_err());
+    }
+
+    #[test]
+    fn extracts_links_from_markdown() {
+        let markdown = "\
+# My list
+- [x] [Two Sum](https://leetcode.com/problems/two-sum/) (Easy)
+
+## Week 2: Trees
+| Problem | Difficulty | Link |
+|---|---|---|
+| Invert | Easy | [Link](https://leetcode.com/problems/invert-binary-tree/) |
+- https://leetcode.com/problems/lru-cache/ is a Medium
+- Again: [Two Sum](https://leetcode.com/problems/two-sum/) and [notes](https://example.com/two-sum)
+";
+
+        let problems = problems_from_markdown(markdown);
+
+        let names: Vec<&str> = problems.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Two Sum", "Invert Binary Tree", "Lru Cache"]);
+        assert_eq!(problems[0].week, None);
+        assert_eq!(problems[0].difficulty, Some(LeetCodeDifficulty::Easy));
+        assert_eq!(problems[1].week, Some(2));
+        assert_eq!(problems[2].difficulty, Some(LeetCodeDifficulty::Medium));
+        assert_eq!(problems[2].order, 3);
+    }
+
+    #[test]
+    fn extracts_links_from_html() {
+        let html = r#"<h2>Arrays &amp; Hashing</h2>
+<a href="https://leetcode.com/problems/contains-duplicate/" target="_blank"><span>Contains Duplicate</span></a>
+<a href="/problems/two-sum/description/">Two Sum &#39;classic&#39;</a>
+<a href="https://leetcode.com/problems/contains-duplicate/">Again</a>
+<a href="https://leetcode.com/problemset/">All problems</a>
+<script>{"link":"https://leetcode.com/problems/valid-anagram/"}</script>
+<a href="https://neetcode.io/problems/two-sum">NeetCode's page</a>"#;
+
+        let mut problems = problems_from_html(html);
+        assign_weeks(&mut problems, 2);
+
+        let names: Vec<&str> = problems.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Contains Duplicate", "Two Sum 'classic'", "Valid Anagram"]
+        );
+        assert_eq!(problems[1].url, "https://leetcode.com/problems/two-sum/");
+        let weeks: Vec<Option<i64>> = problems.iter().map(|p| p.week).collect();
+        assert_eq!(weeks, [Some(1), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn names_bank_files_after_the_page() {
+        assert_eq!(
+            bank_file_name("https://leetcode.com/studyplan/top-interview-150/?source=x"),
+            "top-interview-150.json"
+        );
+        assert_eq!(
+            bank_file_name("https://neetcode.io/roadmap.html"),
+            "roadmap.json"
+        );
+        assert_eq!(bank_file_name("https://neetcode.io/"), "imported.json");
+    }
+}
@@ -0,0 +1,125 @@
+// src/bulk.rs
+
+/// One attempt parsed from a bulk attempt file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkAttempt {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// How the attempt went.
+    pub rating: AttemptRating,
+    /// When it was attempted. `None` means today.
+    pub date: Option<NaiveDate>,
+}
+
+/// The result of parsing a bulk attempt file. Both lists are keyed by the
+/// 1-based line number each entry came from.
+#[derive(Debug, Default)]
+pub struct ParsedBulkFile {
+    /// The lines that parsed.
+    pub attempts: Vec<(usize, BulkAttempt)>,
+    /// Why each of the other lines didn't.
+    pub errors: Vec<(usize, anyhow::Error)>,
+}
+
+/// Parses the lines of a bulk attempt file.
+///
+/// Each line is `<id> <rating> [date]`, where the rating uses the CLI's 1-5
+/// scale and the date accepts anything `parse_flexible_date` does. Blank lines
+/// and lines starting with `#` are skipped. Every bad line is reported, not
+/// just the first, so the whole file can be fixed in one pass.
+pub fn parse_bulk_attempts(contents: &str, today: NaiveDate) -> ParsedBulkFile {
+    let mut parsed = ParsedBulkFile::default();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match parse_bulk_line(line, today) {
+            Ok(attempt) => parsed.attempts.push((line_number, attempt)),
+            Err(e) => parsed.errors.push((line_number, e)),
+        }
+    }
+
+    parsed
+}
+
+fn parse_bulk_line(line: &str, today: NaiveDate) -> anyhow::Result<BulkAttempt> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let (id, rating, date) = match fields.as_slice() {
+        [id, rating] => (id, rating, None),
+        [id, rating, date] => (id, rating, Some(date)),
+        _ => anyhow::bail!("Expected `<id> <rating> [date]`, got '{}'", line),
+    };
+
+    let problem_id = id
+        .parse::<i64>()
+        .with_context(|| format!("Invalid problem id '{}'", id))?;
+    let rating = rating
+        .parse::<u8>()
+        .ok()
+        .and_then(AttemptRating::from_score)
+        .with_context(|| format!("Invalid rating '{}'. Ratings must be 1-5.", rating))?;
+    let date = date.map(|d| parse_flexible_date(d, today)).transpose()?;
+
+    Ok(BulkAttempt {
+        problem_id,
+        rating,
+        date,
+    })
+}
+
+use crate::dates::parse_flexible_date;
+use crate::problem_attempts::AttemptRating;
+use anyhow::Context;
+use chrono::NaiveDate;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_lines_with_and_without_dates() {
+        let contents = "# solved on the plane\n1 5\n\n20 3 2024-03-01\n";
+        let parsed = parse_bulk_attempts(contents, date("2024-03-10"));
+
+        assert!(parsed.errors.is_empty());
+        assert_eq!(
+            parsed.attempts,
+            vec![
+                (
+                    2,
+                    BulkAttempt {
+                        problem_id: 1,
+                        rating: AttemptRating::Easy,
+                        date: None,
+                    }
+                ),
+                (
+                    4,
+                    BulkAttempt {
+                        problem_id: 20,
+                        rating: AttemptRating::Messy,
+                        date: Some(date("2024-03-01")),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_every_bad_line_with_its_number() {
+        let contents = "1 5\nabc 4\n2 9\n3 4 someday\n4\n";
+        let parsed = parse_bulk_attempts(contents, date("2024-03-10"));
+
+        assert_eq!(parsed.attempts.len(), 1);
+        let lines: Vec<usize> = parsed.errors.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,125 @@
+// src/config.rs
+
+/// The file `Config::load` reads from the working directory.
+pub const CONFIG_FILE: &str = "track.toml";
+
+/// The database file track used to keep in the working directory. One found
+/// there is still used, so upgrading doesn't strand existing progress.
+pub const LEGACY_DB_FILE: &str = "lc_tracking.db";
+
+/// User settings loaded from `track.toml`.
+///
+/// Every field is optional so an empty or missing file is a valid config.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// How many distinct problems to attempt per ISO week.
+    pub weekly_goal: Option<u32>,
+    /// How many new problems `track today` plans per ISO week, on top of
+    /// every due review. Defaults to `DEFAULT_WEEKLY_NEW`.
+    pub weekly_new: Option<u32>,
+    /// Points per difficulty for the weighted score in `--progress`.
+    pub scoring: ScoreWeights,
+    /// How far apart reviews are spaced, per rating.
+    pub schedule: Schedule,
+    /// The sqlite database file, when neither --db nor TRACK_DB is given.
+    pub db: Option<PathBuf>,
+    /// Commands to run when attempts are logged, banks are built or reviews
+    /// are found due.
+    pub hooks: Hooks,
+    /// A URL to POST each logged attempt to as JSON, such as a Discord
+    /// channel's webhook, however the attempt is logged.
+    pub webhook_url: Option<String>,
+}
+
+impl Config {
+    /// Loads `track.toml` from the working directory, falling back to the
+    /// defaults if the file doesn't exist.
+    pub fn load() -> anyhow::Result<Config> {
+        Config::load_from(Path::new(CONFIG_FILE))
+    }
+
+    /// Loads the config at `path`, falling back to the defaults if it doesn't
+    /// exist.
+    pub fn load_from(path: &Path) -> anyhow::Result<Config> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        let config: Config = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file '{}'", path.display()))?;
+        config
+            .schedule
+            .validate()
+            .with_context(|| format!("Invalid config file '{}'", path.display()))?;
+
+        Ok(config)
+    }
+
+    /// The database to use when none was given on the command line: `db`
+    /// from the config, else `lc_tracking.db` if the working directory has
+    /// one, else `track/track.db` in the XDG data directory.
+    pub fn db_path(&self) -> anyhow::Result<PathBuf> {
+        if let Some(db) = &self.db {
+            return Ok(db.clone());
+        }
+        if Path::new(LEGACY_DB_FILE).exists() {
+            return Ok(PathBuf::from(LEGACY_DB_FILE));
+        }
+        xdg_db_path(env::var_os("XDG_DATA_HOME"), env::var_os("HOME")).context(
+            "Couldn't find a data directory: neither XDG_DATA_HOME nor HOME is set. Pass --db or set TRACK_DB.",
+        )
+    }
+}
+
+/// `$XDG_DATA_HOME/track/track.db`, or `$HOME/.local/share/track/track.db`
+/// when XDG_DATA_HOME is unset. As the XDG spec asks, an empty or relative
+/// XDG_DATA_HOME is ignored.
+fn xdg_db_path(xdg_data_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let data_home = xdg_data_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            home.filter(|home| !home.is_empty())
+                .map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+
+    Some(data_home.join("track").join("track.db"))
+}
+
+use crate::hooks::Hooks;
+use crate::schedule::Schedule;
+use crate::scoring::ScoreWeights;
+use anyhow::Context;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os(s: &str) -> Option<OsString> {
+        Some(OsString::from(s))
+    }
+
+    #[test]
+    fn db_lives_under_the_xdg_data_home() {
+        assert_eq!(
+            xdg_db_path(os("/data"), os("/home/me")),
+            Some(PathBuf::from("/data/track/track.db"))
+        );
+    }
+
+    #[test]
+    fn db_falls_back_to_local_share() {
+        let expected = Some(PathBuf::from("/home/me/.local/share/track/track.db"));
+        assert_eq!(xdg_db_path(None, os("/home/me")), expected);
+        assert_eq!(xdg_db_path(os(""), os("/home/me")), expected);
+        assert_eq!(xdg_db_path(os("relative"), os("/home/me")), expected);
+        assert_eq!(xdg_db_path(None, None), None);
+    }
+}
@@ -0,0 +1,114 @@
+// src/dates.rs
+
+/// Parses a date given on the command line.
+///
+/// Accepts an explicit `YYYY-MM-DD` date, `today`, `yesterday`, `tomorrow`, or
+/// a relative offset such as `3d` (three days ago) or `2w` (two weeks ago).
+/// A leading `+` counts forward instead: `+3d` is three days from now.
+pub fn parse_flexible_date(input: &str, today: NaiveDate) -> anyhow::Result<NaiveDate> {
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        _ => {}
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let (offset, direction) = match input.strip_prefix('+') {
+        Some(rest) => (rest, 1),
+        None => (input.as_str(), -1),
+    };
+    let (amount, unit_days) = if let Some(days) = offset.strip_suffix('d') {
+        (days, 1)
+    } else if let Some(weeks) = offset.strip_suffix('w') {
+        (weeks, 7)
+    } else {
+        ("", 0)
+    };
+    // Digits only, so a sign can't sneak in and flip the direction.
+    if !amount.is_empty()
+        && amount.bytes().all(|b| b.is_ascii_digit())
+        && let Ok(amount) = amount.parse::<i64>()
+    {
+        return Ok(today + Duration::days(direction * amount * unit_days));
+    }
+
+    anyhow::bail!(
+        "Could not parse date '{}'. Use YYYY-MM-DD, 'today', 'yesterday', 'tomorrow', or an offset like '3d', '2w' or '+3d'.",
+        input
+    )
+}
+
+/// A clap `value_parser` for date arguments, relative to the local date.
+pub fn parse_date_arg(input: &str) -> Result<NaiveDate, String> {
+    parse_flexible_date(input, Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+use chrono::{Duration, Local, NaiveDate};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_explicit_and_named_dates() {
+        let today = date("2024-03-10");
+        assert_eq!(
+            parse_flexible_date("2024-01-01", today).unwrap(),
+            date("2024-01-01")
+        );
+        assert_eq!(parse_flexible_date("Today", today).unwrap(), today);
+        assert_eq!(
+            parse_flexible_date("yesterday", today).unwrap(),
+            date("2024-03-09")
+        );
+    }
+
+    #[test]
+    fn parses_relative_offsets() {
+        let today = date("2024-03-10");
+        assert_eq!(
+            parse_flexible_date("3d", today).unwrap(),
+            date("2024-03-07")
+        );
+        assert_eq!(
+            parse_flexible_date("2w", today).unwrap(),
+            date("2024-02-25")
+        );
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        let today = date("2024-03-10");
+        assert!(parse_flexible_date("last tuesday", today).is_err());
+        assert!(parse_flexible_date("d", today).is_err());
+        assert!(parse_flexible_date("2024-13-01", today).is_err());
+    }
+
+    #[test]
+    fn parses_future_dates() {
+        let today = date("2024-03-10");
+        assert_eq!(
+            parse_flexible_date("tomorrow", today).unwrap(),
+            date("2024-03-11")
+        );
+        assert_eq!(
+            parse_flexible_date("+3d", today).unwrap(),
+            date("2024-03-13")
+        );
+        assert_eq!(
+            parse_flexible_date("+1w", today).unwrap(),
+            date("2024-03-17")
+        );
+        assert!(parse_flexible_date("-3d", today).is_err());
+    }
+}
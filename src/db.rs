@@ -1,35 +1,450 @@
-use crate::problem_attempts::{AttemptRating, ProblemAttempt};
-use crate::problems::LeetCodeDifficulty;
 use crate::Problem;
+use crate::duplicates::normalize;
+use crate::meta::{DailyChallenge, ProblemMeta};
+use crate::problem_attempts::{AttemptRating, ProblemAttempt, ProgressStatus};
+use crate::problems::{LeetCodeDifficulty, PriorityLevel};
+use crate::schedule::{self, Schedule};
+use crate::snapshot::{SNAPSHOT_VERSION, Snapshot, SnapshotAttempt, SnapshotNote, SnapshotProblem};
+use crate::stats::{StatsReport, compute_stats};
 use anyhow::Context;
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
 use sqlx::FromRow;
 use sqlx::SqlitePool;
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::{Acquire, Executor, Sqlite, SqliteConnection};
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[derive(Debug, FromRow)]
+/// A problem's progress joined with its name and difficulty, as listed by
+/// `track --progress`.
+#[derive(Debug, FromRow, serde::Serialize)]
 pub struct ProgressView {
+    /// The LeetCode ID of the problem.
     pub problem_id: i64,
+    /// The problem's name.
     pub name: String,
+    /// The problem's difficulty, if the bank gave one.
     pub difficulty: Option<LeetCodeDifficulty>,
+    /// When the problem was last attempted.
     pub last_attempted: NaiveDate,
+    /// The rating of the latest attempt.
     pub attempt_rating: AttemptRating,
+    /// How many times the problem has been attempted.
     pub number_of_attempts: i64,
+    /// Set by `track master`.
+    pub mastered: bool,
+    /// When the problem is due for review. `None` if it's not scheduled.
+    pub next_attempt_date: Option<NaiveDate>,
+    /// How long the latest attempt took, if it was recorded.
+    pub duration_minutes: Option<i64>,
+    /// The problem's note, or failing that the latest attempt's.
+    pub note: Option<String>,
+    /// The problem's page, if the bank gave one.
+    pub url: Option<String>,
+}
+
+impl ProgressView {
+    /// Where the problem stands, from its latest rating and attempt count.
+    pub fn status(&self) -> ProgressStatus {
+        if self.mastered {
+            return ProgressStatus::Mastered;
+        }
+        ProgressStatus::classify(self.attempt_rating, self.number_of_attempts)
+    }
+}
+
+/// What `merge_database` changed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Problems only the other database had.
+    pub problems_added: u64,
+    /// Problems the other database stores under a different id. They're
+    /// matched up by name and their data is filed under the id used here.
+    pub problems_matched_by_name: u64,
+    /// Progress rows only the other database had.
+    pub progress_added: u64,
+    /// Progress rows the other database had further along.
+    pub progress_updated: u64,
+    /// History rows copied across.
+    pub history_added: u64,
+    /// Problem notes copied across.
+    pub notes_added: u64,
+}
+
+/// How often problems of one difficulty were solved Easy on the first try.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromRow)]
+pub struct FirstAttemptRate {
+    /// The difficulty, or `None` for problems without one.
+    pub difficulty: Option<LeetCodeDifficulty>,
+    /// Problems whose first attempt is in the history log.
+    pub attempted: i64,
+    /// How many of those were first rated Easy.
+    pub easy: i64,
+}
+
+impl FirstAttemptRate {
+    /// The Easy fraction as a percentage, or `None` with nothing attempted.
+    pub fn percent(&self) -> Option<f64> {
+        (self.attempted > 0).then(|| self.easy as f64 / self.attempted as f64 * 100.0)
+    }
+}
+
+/// The priority level of `problems p` set with `track set-priority`, as the
+/// number `PriorityLevel::value` gives: 0 unless a level was set.
+const PRIORITY_LEVEL: &str =
+    "COALESCE((SELECT level FROM problem_priorities WHERE problem_id = p.id), 0)";
+
+/// Narrows the problems `next` picks from. The default allows every problem.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NextFilter<'a> {
+    /// Only problems with this tag, e.g. "graph".
+    pub tag: Option<&'a str>,
+    /// Only problems of this difficulty.
+    pub difficulty: Option<LeetCodeDifficulty>,
+    /// Only problems in this study-plan week.
+    pub week: Option<i64>,
+}
+
+impl<'a> NextFilter<'a> {
+    /// A filter on `tag` alone, or none if it's `None`.
+    pub fn tagged(tag: Option<&'a str>) -> Self {
+        NextFilter {
+            tag,
+            ..NextFilter::default()
+        }
+    }
+
+    /// Whether the filter lets every problem through.
+    pub fn allows_all(&self) -> bool {
+        self.tag.is_none() && self.difficulty.is_none() && self.week.is_none()
+    }
+
+    /// The filter's `WHERE` conditions over `problems p`, with the tag,
+    /// difficulty and week bound as parameters `?first` onwards.
+    fn conditions(first: usize) -> String {
+        format!(
+            r#"(?{tag} IS NULL OR p.id IN (
+                SELECT pt.problem_id FROM problem_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE t.name = ?{tag}
+            ))
+            AND (?{difficulty} IS NULL OR p.difficulty = ?{difficulty})
+            AND (?{week} IS NULL OR p.week = ?{week})"#,
+            tag = first,
+            difficulty = first + 1,
+            week = first + 2
+        )
+    }
+}
+
+/// How `next` picks among the unattempted problems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum NextStrategy {
+    /// The bank's order, with high-priority problems moved to the start of
+    /// their week and low-priority ones to its end.
+    #[default]
+    Order,
+    /// Easy, then Medium, then Hard, with high-priority problems first within
+    /// each. Problems without a difficulty come last.
+    DifficultyAsc,
+    /// Hard, then Medium, then Easy, with high-priority problems first within
+    /// each. Problems without a difficulty come last.
+    DifficultyDesc,
+    /// A random unattempted problem, whatever its priority level.
+    Random,
+}
+
+impl NextStrategy {
+    /// The `ORDER BY` terms for this strategy, over `problems p`.
+    fn order_by(&self) -> String {
+        // Difficulty is stored as text, so rank it explicitly. Unknown
+        // difficulties rank NULL, and sorting on `IS NULL` first puts them last.
+        const RANK: &str =
+            "CASE p.difficulty WHEN 'Easy' THEN 1 WHEN 'Medium' THEN 2 WHEN 'Hard' THEN 3 END";
+        // The bank's order stays the primary key. A high-priority problem moves
+        // up to where its week starts and a low-priority one down to where it
+        // ends; problems without a level, or without a week, stay put.
+        const POSITION: &str = r#"CASE
+            WHEN p.week IS NULL OR {level} = 0 THEN p."order"
+            WHEN {level} > 0 THEN MIN(p."order") OVER (PARTITION BY p.week)
+            ELSE MAX(p."order") OVER (PARTITION BY p.week)
+        END"#;
+        match self {
+            NextStrategy::Order => format!(
+                r#"{}, {PRIORITY_LEVEL} DESC, p."order" ASC"#,
+                POSITION.replace("{level}", PRIORITY_LEVEL)
+            ),
+            NextStrategy::DifficultyAsc => {
+                format!(r#"{RANK} IS NULL, {RANK} ASC, {PRIORITY_LEVEL} DESC, p."order" ASC"#)
+            }
+            NextStrategy::DifficultyDesc => {
+                format!(r#"{RANK} IS NULL, {RANK} DESC, {PRIORITY_LEVEL} DESC, p."order" ASC"#)
+            }
+            NextStrategy::Random => "RANDOM()".to_string(),
+        }
+    }
+}
+
+/// One deck and how far through it you are.
+#[derive(Debug, FromRow)]
+pub struct DeckSummary {
+    /// The deck's name.
+    pub deck: String,
+    /// How many problems the deck has.
+    pub total: i64,
+    /// How many of them have been attempted.
+    pub attempted: i64,
+}
+
+/// How far through one week of the study plan you are.
+#[derive(Debug, FromRow, serde::Serialize)]
+pub struct WeekCompletion {
+    /// The week, or `None` for problems without one.
+    pub week: Option<i64>,
+    /// How many of the week's problems have been attempted.
+    pub attempted: i64,
+    /// How many problems the week has.
+    pub total: i64,
+}
+
+impl WeekCompletion {
+    /// The share of the week's problems attempted, from 0 to 100.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.attempted as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+/// Attempted problems of one difficulty, counted by their latest rating.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, serde::Serialize)]
+pub struct DifficultyBreakdown {
+    /// The difficulty, or `None` for problems without one.
+    pub difficulty: Option<LeetCodeDifficulty>,
+    /// How many problems of this difficulty have been attempted.
+    pub attempted: i64,
+    /// How many attempts they took between them.
+    pub attempts: i64,
+    /// Problems last rated Easy.
+    pub easy: i64,
+    /// Problems last rated Hard.
+    pub hard: i64,
+    /// Problems last rated Messy.
+    pub messy: i64,
+    /// Problems last rated LongFail.
+    pub long_fail: i64,
+    /// Problems last rated ShortFail.
+    pub short_fail: i64,
+}
+
+impl DifficultyBreakdown {
+    /// The mean number of attempts per attempted problem.
+    pub fn average_attempts(&self) -> Option<f64> {
+        (self.attempted > 0).then(|| self.attempts as f64 / self.attempted as f64)
+    }
+}
+
+/// Problems with one tag, counted by their latest rating.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, serde::Serialize)]
+pub struct TagBreakdown {
+    /// The tag's name.
+    pub tag: String,
+    /// How many problems have the tag.
+    pub total: i64,
+    /// How many of them have been attempted.
+    pub attempted: i64,
+    /// How many attempts they took between them.
+    pub attempts: i64,
+    /// Problems last rated Easy.
+    pub easy: i64,
+    /// Problems last rated Hard.
+    pub hard: i64,
+    /// Problems last rated Messy.
+    pub messy: i64,
+    /// Problems last rated LongFail.
+    pub long_fail: i64,
+    /// Problems last rated ShortFail.
+    pub short_fail: i64,
+}
+
+impl TagBreakdown {
+    /// How badly the tag's attempted problems are going, from 0 (all solved
+    /// Easy or Hard) to 1 (all failed). Messy counts as half a failure.
+    /// `None` if none have been attempted.
+    pub fn struggle_rate(&self) -> Option<f64> {
+        let struggles = (self.long_fail + self.short_fail) as f64 + self.messy as f64 / 2.0;
+        (self.attempted > 0).then(|| struggles / self.attempted as f64)
+    }
+}
+
+/// The optional extras recorded alongside an attempt's rating.
+#[derive(Debug, Clone, Default)]
+pub struct AttemptDetails {
+    /// The language it was solved in, e.g. "rust".
+    pub language: Option<String>,
+    /// How long the attempt took, in minutes.
+    pub duration_minutes: Option<i64>,
+    /// Anything worth remembering about this attempt.
+    pub note: Option<String>,
+}
+
+/// One logged attempt from `attempt_history`.
+#[derive(Debug, FromRow, serde::Serialize, serde::Deserialize)]
+pub struct HistoryEntry {
+    /// Which attempt this was, starting at 1.
+    pub attempt_number: i64,
+    /// When it was attempted.
+    pub attempted_on: NaiveDate,
+    /// How it went.
+    pub rating: AttemptRating,
+    /// The language it was solved in, if recorded.
+    pub language: Option<String>,
+    /// How long it took, if recorded.
+    pub duration_minutes: Option<i64>,
+    /// The note logged with it, if any.
+    pub note: Option<String>,
+}
+
+/// A problem with a review date.
+#[derive(Debug, FromRow, serde::Serialize)]
+pub struct ScheduledReview {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The problem's name.
+    pub name: String,
+    /// The problem's difficulty, if the bank gave one.
+    pub difficulty: Option<LeetCodeDifficulty>,
+    /// When the problem is due for review.
+    pub next_attempt_date: NaiveDate,
+}
+
+impl ScheduledReview {
+    /// The number of whole days this review is past its `next_attempt_date`.
+    pub fn days_overdue(&self, today: NaiveDate) -> i64 {
+        (today - self.next_attempt_date).num_days()
+    }
+}
+
+/// The migrations bundled into this binary from `./migrations`.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// A migration recorded as applied in the database's `_sqlx_migrations` table.
+#[derive(Debug, FromRow)]
+pub struct AppliedMigration {
+    /// The migration's timestamp.
+    pub version: i64,
+    /// The migration's file name, minus the timestamp.
+    pub description: String,
+    /// Whether it finished applying.
+    pub success: bool,
+    /// The checksum of the migration as it was applied.
+    pub checksum: Vec<u8>,
+}
+
+/// Opens a connection pool for `db_url` and brings its schema up to date.
+///
+/// The database file is created if it doesn't exist. In-memory URLs such as
+/// `sqlite::memory:` get a single long-lived connection, since every new
+/// connection to an in-memory database would otherwise start out empty.
+///
+/// Every connection runs in WAL mode with `synchronous=NORMAL`, so a long
+/// `--build` doesn't block readers and bulk writes avoid an fsync per commit,
+/// and with `foreign_keys=ON` so the schema's foreign keys are enforced.
+pub async fn init_pool(db_url: &str) -> anyhow::Result<SqlitePool> {
+    let connect_options = db_url
+        .parse::<SqliteConnectOptions>()
+        .with_context(|| format!("Invalid database URL: {}", db_url))?
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .foreign_keys(true);
+
+    let pool_options = if db_url.contains(":memory:") {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+    } else {
+        SqlitePoolOptions::new().max_connections(5)
+    };
+
+    let pool = pool_options
+        .connect_with(connect_options)
+        .await
+        .with_context(|| format!("Failed to open database: {}", db_url))?;
+
+    if let Err(e) = MIGRATOR.run(&pool).await {
+        let message = match &e {
+            MigrateError::VersionMissing(version) => format!(
+                "Database {} has migration {} applied, which this build of track doesn't know about. \
+                 It was probably created by a newer version; upgrade track to use it.",
+                db_url, version
+            ),
+            MigrateError::VersionMismatch(version) => format!(
+                "Migration {} in database {} differs from the one built into track. \
+                 The migrations directory may have been edited after it was applied.",
+                version, db_url
+            ),
+            _ => "Failed to run database migrations.".to_string(),
+        };
+        return Err(anyhow::Error::new(e).context(message));
+    }
+
+    Ok(pool)
+}
+
+/// Adds a hint to errors caused by another connection holding the database
+/// lock (`SQLITE_BUSY` or `SQLITE_LOCKED`), which otherwise just read
+/// "database is locked". Other errors are returned unchanged.
+pub fn explain_lock_error(error: anyhow::Error) -> anyhow::Error {
+    let locked = error.chain().any(|cause| {
+        let sqlx_error = match cause.downcast_ref::<sqlx::Error>() {
+            Some(e) => e,
+            None => match cause.downcast_ref::<MigrateError>() {
+                Some(MigrateError::Execute(e)) => e,
+                _ => return false,
+            },
+        };
+        match sqlx_error {
+            sqlx::Error::Database(db_error) => {
+                // Extended result codes keep the primary code in the low byte.
+                let code = db_error.code().and_then(|code| code.parse::<i32>().ok());
+                matches!(code.map(|code| code & 0xff), Some(5 | 6))
+            }
+            _ => false,
+        }
+    });
+
+    if locked {
+        error.context("The database is locked; another track process may be running.")
+    } else {
+        error
+    }
 }
 
 /// Fetches the current progress for a single problem from the database.
 ///
 /// Returns `Ok(None)` if no progress has been logged for this problem yet.
-pub async fn fetch_progress(
-    pool: &SqlitePool,
+pub async fn fetch_progress<'e, E>(
+    executor: E,
     problem_id: i64,
-) -> anyhow::Result<Option<ProblemAttempt>> {
+) -> anyhow::Result<Option<ProblemAttempt>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     // THE FIX: Use the `query_as()` function instead of the `query_as!` macro.
     // This correctly leverages the `FromRow` trait on your `ProblemAttempt` struct
     // and the `Type` trait on your enums and NaiveDate.
     let progress =
         sqlx::query_as::<_, ProblemAttempt>("SELECT * FROM progress WHERE problem_id = ?")
             .bind(problem_id) // Use .bind() to pass arguments to a query_as function
-            .fetch_optional(pool)
+            .fetch_optional(executor)
             .await
             .with_context(|| format!("Failed to fetch progress for problem_id: {}", problem_id))?;
 
@@ -43,32 +458,42 @@ pub async fn fetch_progress(
 /// per problem, effectively overwriting any previous attempt history.
 ///
 /// # Arguments
-/// * `pool` - A reference to the `sqlx` connection pool.
+/// * `executor` - The pool, connection, or transaction to write through.
 /// * `problem_id` - The ID of the problem being attempted.
 /// * `rating` - The `AttemptRating` for this new attempt.
 /// * `attempt_date` - An optional date for the attempt. If `None`, today's date is used.
-pub async fn add_or_replace_progress(
-    pool: &SqlitePool,
+/// * `language` - The programming language the attempt was solved in, if recorded.
+pub async fn add_or_replace_progress<'e, E>(
+    executor: E,
     problem_id: i64,
     rating: AttemptRating,
     attempt_date: Option<NaiveDate>,
-) -> anyhow::Result<()> {
+    language: Option<String>,
+) -> anyhow::Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     // Use your existing logic to construct the new progress state.
-    let new_progress = ProblemAttempt::new_attempt(problem_id, rating, attempt_date);
+    let mut new_progress = ProblemAttempt::new_attempt(problem_id, rating, attempt_date);
+    new_progress.language = language;
 
     // Execute the query to insert or replace the row in the `progress` table.
     sqlx::query!(
         r#"
-        INSERT OR REPLACE INTO progress (problem_id, last_attempted, attempt_rating, next_attempt_date, number_of_attempts)
-        VALUES (?, ?, ?, ?, ?)
+        INSERT OR REPLACE INTO progress (problem_id, last_attempted, attempt_rating, next_attempt_date, number_of_attempts, language, mastered, repetitions, ease)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         new_progress.problem_id,
         new_progress.last_attempted,
         new_progress.attempt_rating,
         new_progress.next_attempt_date,
-        new_progress.number_of_attempts
+        new_progress.number_of_attempts,
+        new_progress.language,
+        new_progress.mastered,
+        new_progress.repetitions,
+        new_progress.ease
     )
-    .execute(pool)
+    .execute(executor)
     .await
     .with_context(|| format!("Failed to add/replace progress for problem_id: {}", problem_id))?;
 
@@ -83,66 +508,504 @@ pub async fn add_or_replace_progress(
 ///
 /// # Errors
 /// Returns an error if no progress has been logged for the problem yet.
-pub async fn update_progress(
-    pool: &SqlitePool,
+pub async fn update_progress<'a, A>(
+    db: A,
     problem_id: i64,
     latest_rating: AttemptRating,
     attempt_date: Option<NaiveDate>,
-) -> anyhow::Result<()> {
+    language: Option<String>,
+) -> anyhow::Result<()>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = db
+        .acquire()
+        .await
+        .context("Failed to acquire a database connection.")?;
+
     // 1. Fetch the current progress from the database.
-    let mut current_progress = fetch_progress(pool, problem_id)
+    let mut current_progress = fetch_progress(&mut *conn, problem_id)
         .await?
         .context("Cannot update progress for a problem that has no attempts yet. Use `add_or_replace_progress` for the first attempt.")?;
 
     // 2. Use your existing logic to update the struct in memory.
     current_progress.update_attempt(latest_rating, attempt_date);
+    current_progress.language = language;
 
     // 3. Write the updated struct back to the database.
     sqlx::query!(
         r#"
         UPDATE progress
-        SET last_attempted = ?, attempt_rating = ?, next_attempt_date = ?, number_of_attempts = ?
+        SET last_attempted = ?, attempt_rating = ?, next_attempt_date = ?, number_of_attempts = ?, language = ?, mastered = ?, repetitions = ?, ease = ?
         WHERE problem_id = ?
         "#,
         current_progress.last_attempted,
         current_progress.attempt_rating,
         current_progress.next_attempt_date,
         current_progress.number_of_attempts,
+        current_progress.language,
+        current_progress.mastered,
+        current_progress.repetitions,
+        current_progress.ease,
         current_progress.problem_id
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await
     .with_context(|| format!("Failed to update progress for problem_id: {}", problem_id))?;
 
     Ok(())
 }
 
-pub async fn fetch_next_unattempted_problem(pool: &SqlitePool) -> anyhow::Result<Option<Problem>> {
+/// Logs an attempt, creating the problem's progress on its first attempt and
+/// updating it on every later one.
+///
+/// Returns `true` if this was the problem's first logged attempt.
+pub async fn log_attempt<'a, A>(
+    db: A,
+    problem_id: i64,
+    rating: AttemptRating,
+    attempt_date: Option<NaiveDate>,
+    details: AttemptDetails,
+) -> anyhow::Result<bool>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut tx = db.begin().await.context("Failed to start a transaction.")?;
+
+    let first = fetch_progress(&mut *tx, problem_id).await?.is_none();
+    if first {
+        add_or_replace_progress(&mut *tx, problem_id, rating, attempt_date, details.language)
+            .await?;
+    } else {
+        update_progress(&mut *tx, problem_id, rating, attempt_date, details.language).await?;
+    }
+    if let Some(progress) = fetch_progress(&mut *tx, problem_id).await?
+        && let Some(due) = progress.next_attempt_date
+    {
+        let smoothed = smoothed_review_date(
+            &mut tx,
+            schedule::current(),
+            problem_id,
+            progress.last_attempted,
+            due,
+        )
+        .await?;
+        if smoothed != due {
+            sqlx::query("UPDATE progress SET next_attempt_date = ? WHERE problem_id = ?")
+                .bind(smoothed)
+                .bind(problem_id)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| {
+                    format!("Failed to move the review for problem_id: {}", problem_id)
+                })?;
+        }
+    }
+
+    // Copy the attempt just written into the history log.
+    sqlx::query(
+        r#"
+        INSERT INTO attempt_history
+            (problem_id, attempted_on, rating, language, attempt_number, duration_minutes, note)
+        SELECT problem_id, last_attempted, attempt_rating, language, number_of_attempts, ?2, ?3
+        FROM progress
+        WHERE problem_id = ?1
+        "#,
+    )
+    .bind(problem_id)
+    .bind(details.duration_minutes)
+    .bind(details.note)
+    .execute(&mut *tx)
+    .await
+    .with_context(|| format!("Failed to record history for problem_id: {}", problem_id))?;
+
+    tx.commit().await.context("Failed to commit the attempt.")?;
+
+    Ok(first)
+}
+
+/// Fetches every logged attempt at a problem, oldest first.
+pub async fn fetch_attempt_history(
+    pool: &SqlitePool,
+    problem_id: i64,
+) -> anyhow::Result<Vec<HistoryEntry>> {
+    let history = sqlx::query_as::<_, HistoryEntry>(
+        r#"
+        SELECT attempt_number, attempted_on, rating, language, duration_minutes, note
+        FROM attempt_history
+        WHERE problem_id = ?
+        ORDER BY attempted_on ASC, id ASC
+        "#,
+    )
+    .bind(problem_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("Failed to fetch history for problem_id: {}", problem_id))?;
+
+    Ok(history)
+}
+
+/// One logged attempt with the problem it was for, as exported.
+#[derive(Debug, FromRow)]
+pub struct AttemptRecord {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The problem's name.
+    pub name: String,
+    /// The problem's difficulty, if the bank gave one.
+    pub difficulty: Option<LeetCodeDifficulty>,
+    /// Which attempt this was, starting at 1.
+    pub attempt_number: i64,
+    /// When it was attempted.
+    pub attempted_on: NaiveDate,
+    /// How it went.
+    pub rating: AttemptRating,
+    /// The language it was solved in, if recorded.
+    pub language: Option<String>,
+    /// How long it took, if recorded.
+    pub duration_minutes: Option<i64>,
+    /// The note logged with it, if any.
+    pub note: Option<String>,
+}
+
+/// Fetches every logged attempt, oldest first. With `deck`, only attempts at
+/// that deck's problems are returned.
+pub async fn fetch_all_attempts(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<AttemptRecord>> {
+    let attempts = sqlx::query_as::<_, AttemptRecord>(
+        r#"
+        SELECT
+            h.problem_id, p.name, p.difficulty, h.attempt_number, h.attempted_on, h.rating,
+            h.language, h.duration_minutes, h.note
+        FROM
+            attempt_history h
+        JOIN
+            problems p ON p.id = h.problem_id
+        WHERE
+            p.id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+        ORDER BY
+            h.attempted_on ASC, h.id ASC
+        "#,
+    )
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch attempt history from the database.")?;
+
+    Ok(attempts)
+}
+
+/// Removes the most recently logged attempt, at `problem_id` or at any
+/// problem if it's `None`, and rewinds the problem's progress to the attempt
+/// before it. Progress is deleted if no earlier attempt is left.
+///
+/// Returns the problem id and the attempt that was removed, or `None` if
+/// there was nothing to remove.
+pub async fn delete_latest_attempt(
+    pool: &SqlitePool,
+    problem_id: Option<i64>,
+) -> anyhow::Result<Option<(i64, HistoryEntry)>> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to start a transaction.")?;
+
+    const LATEST: &str = r#"
+        SELECT id, problem_id, attempt_number, attempted_on, rating, language, duration_minutes, note
+        FROM attempt_history
+        WHERE ?1 IS NULL OR problem_id = ?1
+        ORDER BY id DESC
+        LIMIT 1
+    "#;
+    let Some((history_id, problem_id)) = sqlx::query_as::<_, (i64, i64)>(LATEST)
+        .bind(problem_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to find the latest attempt.")?
+    else {
+        return Ok(None);
+    };
+    let removed = sqlx::query_as::<_, HistoryEntry>(LATEST)
+        .bind(problem_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to read the latest attempt.")?;
+
+    sqlx::query("DELETE FROM attempt_history WHERE id = ?")
+        .bind(history_id)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to delete the latest attempt at problem {}",
+                problem_id
+            )
+        })?;
+
+    let previous = sqlx::query_as::<_, HistoryEntry>(LATEST)
+        .bind(problem_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to read the previous attempt.")?;
+    match previous {
+        Some(entry) => {
+            // The review state is rebuilt from the attempts that are left.
+            let ratings = sqlx::query_scalar::<_, AttemptRating>(
+                "SELECT rating FROM attempt_history WHERE problem_id = ? ORDER BY attempt_number, id",
+            )
+            .bind(problem_id)
+            .fetch_all(&mut *tx)
+            .await
+            .context("Failed to read the remaining attempts.")?;
+            let mut progress = ProblemAttempt {
+                problem_id,
+                last_attempted: entry.attempted_on,
+                attempt_rating: entry.rating,
+                next_attempt_date: None,
+                number_of_attempts: entry.attempt_number,
+                language: entry.language,
+                mastered: false,
+                repetitions: 0,
+                ease: schedule::DEFAULT_EASE,
+            };
+            progress.set_review_state(schedule::current().replay(ratings));
+            progress.reschedule();
+            sqlx::query(
+                r#"
+                UPDATE progress
+                SET last_attempted = ?, attempt_rating = ?, next_attempt_date = ?,
+                    number_of_attempts = ?, language = ?, mastered = ?,
+                    repetitions = ?, ease = ?
+                WHERE problem_id = ?
+                "#,
+            )
+            .bind(progress.last_attempted)
+            .bind(progress.attempt_rating)
+            .bind(progress.next_attempt_date)
+            .bind(progress.number_of_attempts)
+            .bind(&progress.language)
+            .bind(progress.mastered)
+            .bind(progress.repetitions)
+            .bind(progress.ease)
+            .bind(problem_id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| {
+                format!("Failed to restore progress for problem_id: {}", problem_id)
+            })?;
+        }
+        None => {
+            sqlx::query("DELETE FROM progress WHERE problem_id = ?")
+                .bind(problem_id)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| {
+                    format!("Failed to delete progress for problem_id: {}", problem_id)
+                })?;
+        }
+    }
+
+    tx.commit().await.context("Failed to commit the undo.")?;
+
+    Ok(Some((problem_id, removed)))
+}
+
+/// Fetches the first unattempted problem that passes `filter`, ordered by
+/// `strategy`, skipping suspended ones.
+///
+/// Pinned problems always come first, whatever the strategy.
+pub async fn fetch_next_unattempted_problem(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+    filter: NextFilter<'_>,
+    strategy: NextStrategy,
+) -> anyhow::Result<Option<Problem>> {
+    let problems = fetch_unattempted_problems(pool, deck, filter, strategy, 1).await?;
+    Ok(problems.into_iter().next())
+}
+
+/// A pinned problem, as `track pins` lists it.
+#[derive(Debug, FromRow, serde::Serialize)]
+pub struct PinnedProblem {
+    /// The problem.
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub problem: Problem,
+    /// Whether it has progress yet.
+    pub attempted: bool,
+}
+
+/// Fetches the pinned problems, attempted or not, in the order `next` offers
+/// the unattempted ones.
+pub async fn fetch_pinned_problems(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<PinnedProblem>> {
+    let problems = sqlx::query_as::<_, PinnedProblem>(&format!(
+        r#"
+        SELECT
+            p.id, p."order", p.name, p.difficulty, p.week, p.priority, p.deck, p.url,
+            pr.problem_id IS NOT NULL AS attempted
+        FROM
+            problems p
+        LEFT JOIN
+            progress pr ON p.id = pr.problem_id
+        WHERE
+            p.priority >= ?1
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?2 IS NULL OR deck = ?2)
+        ORDER BY
+            p.priority DESC, {}
+        "#,
+        NextStrategy::Order.order_by()
+    ))
+    .bind(Problem::PINNED_PRIORITY)
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch pinned problems.")?;
+
+    Ok(problems)
+}
+
+/// Fetches up to `limit` unattempted problems that pass `filter`, in the
+/// order `fetch_next_unattempted_problem` would suggest them.
+pub async fn fetch_unattempted_problems(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+    filter: NextFilter<'_>,
+    strategy: NextStrategy,
+    limit: u32,
+) -> anyhow::Result<Vec<Problem>> {
     // THE FIX: Use the `query_as()` function instead of the `query_as!` macro.
     // This correctly leverages the `FromRow` trait on your `Problem` struct.
-    let next_problem = sqlx::query_as::<_, Problem>(
+    let problems = sqlx::query_as::<_, Problem>(&format!(
         r#"
         SELECT
-            p.id, p."order", p.name, p.difficulty, p.week
+            p.id, p."order", p.name, p.difficulty, p.week, p.priority, p.deck, p.url
         FROM
             problems p
         LEFT JOIN
             progress pr ON p.id = pr.problem_id
         WHERE
             pr.problem_id IS NULL
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+            AND {}
+            AND p.id NOT IN (SELECT problem_id FROM suspended_problems)
+        ORDER BY
+            p.priority DESC, {}
+        LIMIT ?2
+        "#,
+        NextFilter::conditions(3),
+        strategy.order_by()
+    ))
+    .bind(deck)
+    .bind(limit)
+    .bind(filter.tag)
+    .bind(filter.difficulty)
+    .bind(filter.week)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch unattempted problems.")?;
+
+    Ok(problems)
+}
+
+/// Fetches the problem to work on next: a review due on or before `today` if
+/// there is one, otherwise the next unattempted problem picked by `strategy`.
+/// Only problems that pass `filter` count, and suspended problems and
+/// leeches are skipped.
+///
+/// Among due reviews, ones whose latest attempt failed come first, then the
+/// most overdue.
+pub async fn fetch_next_problem(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    deck: Option<&str>,
+    filter: NextFilter<'_>,
+    strategy: NextStrategy,
+) -> anyhow::Result<Option<Problem>> {
+    let due = sqlx::query_as::<_, Problem>(&format!(
+        r#"
+        SELECT
+            p.id, p."order", p.name, p.difficulty, p.week, p.priority, p.deck, p.url
+        FROM
+            problems p
+        JOIN
+            progress pr ON p.id = pr.problem_id
+        WHERE
+            pr.next_attempt_date IS NOT NULL
+            AND pr.next_attempt_date <= ?1
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?2 IS NULL OR deck = ?2)
+            AND {conditions}
+            AND p.id NOT IN (SELECT problem_id FROM suspended_problems)
         ORDER BY
-            p."order" ASC
+            pr.attempt_rating IN ('ShortFail', 'LongFail') DESC,
+            pr.next_attempt_date ASC, p.priority DESC, {PRIORITY_LEVEL} DESC, p."order" ASC
         LIMIT 1
         "#,
-    )
+        conditions = NextFilter::conditions(3),
+    ))
+    .bind(today)
+    .bind(deck)
+    .bind(filter.tag)
+    .bind(filter.difficulty)
+    .bind(filter.week)
     .fetch_optional(pool)
     .await
-    .context("Failed to fetch the next unattempted problem.")?;
+    .context("Failed to fetch the next due problem.")?;
+
+    match due {
+        Some(problem) => Ok(Some(problem)),
+        None => fetch_next_unattempted_problem(pool, deck, filter, strategy).await,
+    }
+}
+
+/// Fetches the next unattempted problem of every week, in week order, with
+/// pinned problems first within a week.
+pub async fn fetch_next_unattempted_by_week(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<Problem>> {
+    let problems = sqlx::query_as::<_, Problem>(&format!(
+        r#"
+        SELECT id, "order", name, difficulty, week, priority, deck, url
+        FROM (
+            SELECT
+                p.*,
+                ROW_NUMBER() OVER (
+                    PARTITION BY p.week ORDER BY p.priority DESC, {PRIORITY_LEVEL} DESC, p."order" ASC
+                ) AS rank_in_week
+            FROM
+                problems p
+            LEFT JOIN
+                progress pr ON p.id = pr.problem_id
+            WHERE
+                pr.problem_id IS NULL
+                AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+        )
+        WHERE rank_in_week = 1
+        ORDER BY week ASC
+        "#,
+    ))
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch the next problem of each week.")?;
 
-    Ok(next_problem)
+    Ok(problems)
 }
 
-pub async fn fetch_all_progress(pool: &SqlitePool) -> anyhow::Result<Vec<ProgressView>> {
+/// Fetches every attempted problem, most recently attempted first.
+///
+/// With `since`, only problems last attempted on or after that date are
+/// returned. With `deck`, only problems in that deck are returned.
+pub async fn fetch_all_progress(
+    pool: &SqlitePool,
+    since: Option<NaiveDate>,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<ProgressView>> {
     let progress_list = sqlx::query_as::<_, ProgressView>(
         r#"
         SELECT
@@ -151,15 +1014,40 @@ pub async fn fetch_all_progress(pool: &SqlitePool) -> anyhow::Result<Vec<Progres
             p.difficulty,
             pr.last_attempted,
             pr.attempt_rating,
-            pr.number_of_attempts
+            pr.number_of_attempts,
+            pr.mastered,
+            pr.next_attempt_date,
+            (
+                SELECT h.duration_minutes FROM attempt_history h
+                WHERE h.problem_id = pr.problem_id
+                ORDER BY h.id DESC
+                LIMIT 1
+            ) AS duration_minutes,
+            COALESCE(
+                n.note,
+                (
+                    SELECT h.note FROM attempt_history h
+                    WHERE h.problem_id = pr.problem_id AND h.note IS NOT NULL
+                    ORDER BY h.id DESC
+                    LIMIT 1
+                )
+            ) AS note,
+            p.url
         FROM
             progress pr
         JOIN
             problems p ON pr.problem_id = p.id
+        LEFT JOIN
+            problem_notes n ON n.problem_id = pr.problem_id
+        WHERE
+            (?1 IS NULL OR pr.last_attempted >= ?1)
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?2 IS NULL OR deck = ?2)
         ORDER BY
             pr.last_attempted DESC
         "#,
     )
+    .bind(since)
+    .bind(deck)
     .fetch_all(pool)
     .await
     .context("Failed to fetch progress list from database.")?;
@@ -167,17 +1055,3999 @@ pub async fn fetch_all_progress(pool: &SqlitePool) -> anyhow::Result<Vec<Progres
     Ok(progress_list)
 }
 
-pub async fn fetch_all_problems(pool: &SqlitePool) -> anyhow::Result<Vec<Problem>> {
-    let all_problems = sqlx::query_as::<_, Problem>(
+/// Fetches a single problem definition by its LeetCode id.
+pub async fn fetch_problem<'e, E>(executor: E, problem_id: i64) -> anyhow::Result<Option<Problem>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let problem = sqlx::query_as::<_, Problem>(
+        r#"
+        SELECT id, "order", name, difficulty, week, priority, deck, url
+        FROM problems
+        WHERE id = ?
+        "#,
+    )
+    .bind(problem_id)
+    .fetch_optional(executor)
+    .await
+    .with_context(|| format!("Failed to fetch problem with id: {}", problem_id))?;
+
+    Ok(problem)
+}
+
+/// The id of the problem stored as `name`, if any. Names are unique.
+pub async fn fetch_problem_id_by_name<'e, E>(executor: E, name: &str) -> anyhow::Result<Option<i64>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_scalar("SELECT id FROM problems WHERE name = ?")
+        .bind(name)
+        .fetch_optional(executor)
+        .await
+        .with_context(|| format!("Failed to look up problem '{}'.", name))
+}
+
+/// Fetches every problem, in week and bank order. With `deck`, only that
+/// deck's problems are returned.
+pub async fn fetch_all_problems(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<Problem>> {
+    // SQLite treats a negative LIMIT as "no limit".
+    fetch_problems_page(pool, deck, None, -1, 0).await
+}
+
+/// Fetches one page of problems in the same week/order as `fetch_all_problems`.
+/// With `tag`, only problems tagged with it are returned.
+pub async fn fetch_problems_page(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+    tag: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> anyhow::Result<Vec<Problem>> {
+    let problems = sqlx::query_as::<_, Problem>(
         r#"
-        SELECT id, "order", name, difficulty, week
+        SELECT id, "order", name, difficulty, week, priority, deck, url
         FROM problems
+        WHERE
+            id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+            AND (?4 IS NULL OR id IN (
+                SELECT pt.problem_id FROM problem_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE t.name = ?4
+            ))
         ORDER BY week ASC, "order" ASC
+        LIMIT ?2 OFFSET ?3
         "#,
     )
+    .bind(deck)
+    .bind(limit)
+    .bind(offset)
+    .bind(tag)
     .fetch_all(pool)
     .await
     .context("Failed to fetch all problems from the database.")?;
 
-    Ok(all_problems)
+    Ok(problems)
+}
+
+/// Counts the problems, or with `deck` and `tag` only the ones in that deck
+/// and with that tag.
+pub async fn count_problems(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+    tag: Option<&str>,
+) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM problems
+        WHERE
+            id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+            AND (?2 IS NULL OR id IN (
+                SELECT pt.problem_id FROM problem_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE t.name = ?2
+            ))
+        "#,
+    )
+    .bind(deck)
+    .bind(tag)
+    .fetch_one(pool)
+    .await
+    .context("Failed to count problems in the database.")?;
+
+    Ok(count)
+}
+
+/// Fetches every problem whose `next_attempt_date` is strictly before `today`.
+///
+/// Results are sorted most-overdue first, so the oldest missed review comes
+/// out at the top of the list.
+pub async fn fetch_overdue_problems(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<ScheduledReview>> {
+    fetch_reviews_before(pool, today, deck)
+        .await
+        .context("Failed to fetch overdue problems from the database.")
+}
+
+/// Fetches every problem due for review: those whose `next_attempt_date` is
+/// `today` or earlier. Sorted most-overdue first, like `fetch_overdue_problems`.
+pub async fn fetch_due_problems(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<ScheduledReview>> {
+    fetch_reviews_before(pool, today + Duration::days(1), deck)
+        .await
+        .context("Failed to fetch due problems from the database.")
+}
+
+/// Fetches every review due within `days` days of `today`, overdue ones
+/// included, so `days = 0` gives the same list as `fetch_due_problems`.
+pub async fn fetch_reviews_due_within(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    days: u32,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<ScheduledReview>> {
+    fetch_reviews_before(pool, today + Duration::days(i64::from(days) + 1), deck)
+        .await
+        .context("Failed to fetch upcoming reviews from the database.")
+}
+
+/// Scheduled reviews strictly before `cutoff`, most overdue first.
+async fn fetch_reviews_before(
+    pool: &SqlitePool,
+    cutoff: NaiveDate,
+    deck: Option<&str>,
+) -> Result<Vec<ScheduledReview>, sqlx::Error> {
+    sqlx::query_as::<_, ScheduledReview>(
+        r#"
+        SELECT
+            p.id as problem_id,
+            p.name,
+            p.difficulty,
+            pr.next_attempt_date
+        FROM
+            progress pr
+        JOIN
+            problems p ON pr.problem_id = p.id
+        WHERE
+            pr.next_attempt_date IS NOT NULL
+            AND pr.next_attempt_date < ?1
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?2 IS NULL OR deck = ?2)
+        ORDER BY
+            pr.next_attempt_date ASC, p."order" ASC
+        "#,
+    )
+    .bind(cutoff)
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+}
+
+/// Counts the distinct problems attempted within the ISO week containing
+/// `day` (Monday through Sunday).
+pub async fn count_problems_attempted_in_week(
+    pool: &SqlitePool,
+    day: NaiveDate,
+) -> anyhow::Result<i64> {
+    let week = day.iso_week();
+    let monday = NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon)
+        .context("Failed to compute the start of the ISO week.")?;
+    let sunday = monday + Duration::days(6);
+
+    let count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(DISTINCT problem_id)
+        FROM attempt_history
+        WHERE attempted_on >= ? AND attempted_on <= ?
+        "#,
+    )
+    .bind(monday)
+    .bind(sunday)
+    .fetch_one(pool)
+    .await
+    .context("Failed to count this week's attempts.")?;
+
+    Ok(count)
+}
+
+/// Counts the problems whose first logged attempt falls between `from` and
+/// `to`, inclusive.
+pub async fn count_problems_started_between(
+    pool: &SqlitePool,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> anyhow::Result<i64> {
+    let count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM (
+            SELECT MIN(attempted_on) AS first_attempted_on
+            FROM attempt_history
+            GROUP BY problem_id
+        )
+        WHERE first_attempted_on >= ? AND first_attempted_on <= ?
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await
+    .context("Failed to count newly started problems.")?;
+
+    Ok(count)
+}
+
+/// Counts attempted problems by the language of their latest attempt.
+///
+/// Attempts logged without a language are counted under "unknown". With
+/// `since`, only problems last attempted on or after that date are counted.
+pub async fn fetch_language_counts(
+    pool: &SqlitePool,
+    since: Option<NaiveDate>,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<(String, i64)>> {
+    let counts = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT COALESCE(pr.language, 'unknown') as lang, COUNT(*) as count
+        FROM progress pr
+        JOIN problems p ON pr.problem_id = p.id
+        WHERE (?1 IS NULL OR pr.last_attempted >= ?1)
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?2 IS NULL OR deck = ?2)
+        GROUP BY lang
+        ORDER BY count DESC, lang ASC
+        "#,
+    )
+    .bind(since)
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch language breakdown from the database.")?;
+
+    Ok(counts)
+}
+
+/// Fetches the date of every logged attempt, for computing streaks.
+pub async fn fetch_attempt_dates(pool: &SqlitePool) -> anyhow::Result<Vec<NaiveDate>> {
+    let dates = sqlx::query_scalar::<_, NaiveDate>("SELECT attempted_on FROM attempt_history")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch attempt dates from the database.")?;
+
+    Ok(dates)
+}
+
+/// Counts attempted and total problems for each week of the bank.
+///
+/// Problems without a week are grouped into a single row with `week: None`,
+/// which sorts after every numbered week.
+pub async fn fetch_week_completion(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<WeekCompletion>> {
+    let weeks = sqlx::query_as::<_, WeekCompletion>(
+        r#"
+        SELECT
+            p.week,
+            COUNT(pr.problem_id) as attempted,
+            COUNT(*) as total
+        FROM
+            problems p
+        LEFT JOIN
+            progress pr ON p.id = pr.problem_id
+        WHERE
+            p.id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+        GROUP BY
+            p.week
+        ORDER BY
+            p.week IS NULL, p.week ASC
+        "#,
+    )
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch per-week completion from the database.")?;
+
+    Ok(weeks)
+}
+
+/// Counts attempted problems per difficulty and latest rating, easiest
+/// difficulty first with unrated problems last.
+pub async fn fetch_difficulty_breakdown(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<DifficultyBreakdown>> {
+    let breakdown = sqlx::query_as::<_, DifficultyBreakdown>(
+        r#"
+        SELECT
+            p.difficulty,
+            COUNT(*) AS attempted,
+            SUM(pr.number_of_attempts) AS attempts,
+            SUM(pr.attempt_rating = 'Easy') AS easy,
+            SUM(pr.attempt_rating = 'Hard') AS hard,
+            SUM(pr.attempt_rating = 'Messy') AS messy,
+            SUM(pr.attempt_rating = 'LongFail') AS long_fail,
+            SUM(pr.attempt_rating = 'ShortFail') AS short_fail
+        FROM
+            progress pr
+        JOIN
+            problems p ON pr.problem_id = p.id
+        WHERE
+            p.id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+        GROUP BY
+            p.difficulty
+        ORDER BY
+            CASE p.difficulty WHEN 'Easy' THEN 0 WHEN 'Medium' THEN 1 WHEN 'Hard' THEN 2 ELSE 3 END
+        "#,
+    )
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch the per-difficulty breakdown from the database.")?;
+
+    Ok(breakdown)
+}
+
+/// Counts problems per tag and latest rating, tags in alphabetical order.
+/// Unattempted problems count towards `total` only.
+pub async fn fetch_tag_breakdown(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<TagBreakdown>> {
+    let breakdown = sqlx::query_as::<_, TagBreakdown>(
+        r#"
+        SELECT
+            t.name AS tag,
+            COUNT(*) AS total,
+            COUNT(pr.problem_id) AS attempted,
+            COALESCE(SUM(pr.number_of_attempts), 0) AS attempts,
+            COALESCE(SUM(pr.attempt_rating = 'Easy'), 0) AS easy,
+            COALESCE(SUM(pr.attempt_rating = 'Hard'), 0) AS hard,
+            COALESCE(SUM(pr.attempt_rating = 'Messy'), 0) AS messy,
+            COALESCE(SUM(pr.attempt_rating = 'LongFail'), 0) AS long_fail,
+            COALESCE(SUM(pr.attempt_rating = 'ShortFail'), 0) AS short_fail
+        FROM
+            tags t
+        JOIN
+            problem_tags pt ON pt.tag_id = t.id
+        LEFT JOIN
+            progress pr ON pr.problem_id = pt.problem_id
+        WHERE
+            pt.problem_id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+        GROUP BY
+            t.id
+        ORDER BY
+            t.name ASC
+        "#,
+    )
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch the per-tag breakdown from the database.")?;
+
+    Ok(breakdown)
+}
+
+/// Picks a problem from the topic you're doing worst at: the attempted tag
+/// with the highest `struggle_rate`, preferring tags with more attempts on a
+/// tie. Within the tag, due reviews come before unattempted problems, as in
+/// `fetch_next_problem`. If that tag has nothing left, the next weakest is
+/// tried.
+///
+/// Returns `None` if no tagged problem has been attempted yet, or every
+/// weak tag is done.
+pub async fn fetch_weakest_tag_problem(
+    pool: &SqlitePool,
+    today: NaiveDate,
+    deck: Option<&str>,
+    strategy: NextStrategy,
+) -> anyhow::Result<Option<(TagBreakdown, Problem)>> {
+    let mut tags: Vec<(f64, TagBreakdown)> = fetch_tag_breakdown(pool, deck)
+        .await?
+        .into_iter()
+        .filter_map(|tag| tag.struggle_rate().map(|rate| (rate, tag)))
+        .collect();
+    tags.sort_by(|(a_rate, a), (b_rate, b)| {
+        b_rate
+            .total_cmp(a_rate)
+            .then(b.attempted.cmp(&a.attempted))
+            .then(a.tag.cmp(&b.tag))
+    });
+
+    for (_, tag) in tags {
+        if let Some(problem) = fetch_next_problem(
+            pool,
+            today,
+            deck,
+            NextFilter::tagged(Some(&tag.tag)),
+            strategy,
+        )
+        .await?
+        {
+            return Ok(Some((tag, problem)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Fetches a problem's tags in alphabetical order.
+pub async fn fetch_problem_tags(pool: &SqlitePool, problem_id: i64) -> anyhow::Result<Vec<String>> {
+    let tags = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT t.name
+        FROM problem_tags pt
+        JOIN tags t ON t.id = pt.tag_id
+        WHERE pt.problem_id = ?
+        ORDER BY t.name ASC
+        "#,
+    )
+    .bind(problem_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("Failed to fetch tags for problem_id: {}", problem_id))?;
+
+    Ok(tags)
+}
+
+/// Every deck a problem is in, alphabetically.
+pub async fn fetch_problem_decks(
+    pool: &SqlitePool,
+    problem_id: i64,
+) -> anyhow::Result<Vec<String>> {
+    let decks = sqlx::query_scalar::<_, String>(
+        "SELECT deck FROM deck_problems WHERE problem_id = ? ORDER BY deck ASC",
+    )
+    .bind(problem_id)
+    .fetch_all(pool)
+    .await
+    .with_context(|| format!("Failed to fetch decks for problem_id: {}", problem_id))?;
+
+    Ok(decks)
+}
+
+/// Replaces a problem's tags. Tags are trimmed and lowercased, so "DP" and
+/// "dp " are the same tag; blank ones are skipped.
+pub async fn set_problem_tags(
+    conn: &mut SqliteConnection,
+    problem_id: i64,
+    tags: &[String],
+) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM problem_tags WHERE problem_id = ?")
+        .bind(problem_id)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to clear tags for problem_id: {}", problem_id))?;
+
+    for tag in tags
+        .iter()
+        .map(|t| normalize_tag(t))
+        .filter(|t| !t.is_empty())
+    {
+        sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?)")
+            .bind(&tag)
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("Failed to save tag '{}'", tag))?;
+        sqlx::query(
+            "INSERT OR IGNORE INTO problem_tags (problem_id, tag_id) \
+             SELECT ?1, id FROM tags WHERE name = ?2",
+        )
+        .bind(problem_id)
+        .bind(&tag)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to tag problem_id {} with '{}'", problem_id, tag))?;
+    }
+
+    Ok(())
+}
+
+/// The form tags are stored in: trimmed and lowercased.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Fetches every problem that has a scheduled review, soonest first.
+pub async fn fetch_scheduled_reviews(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<ScheduledReview>> {
+    let reviews = sqlx::query_as::<_, ScheduledReview>(
+        r#"
+        SELECT
+            p.id as problem_id,
+            p.name,
+            p.difficulty,
+            pr.next_attempt_date
+        FROM
+            progress pr
+        JOIN
+            problems p ON pr.problem_id = p.id
+        WHERE
+            pr.next_attempt_date IS NOT NULL
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+        ORDER BY
+            pr.next_attempt_date ASC, p."order" ASC
+        "#,
+    )
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch scheduled reviews from the database.")?;
+
+    Ok(reviews)
+}
+
+/// Sets a problem's manual priority. `next` suggests higher priorities first.
+///
+/// # Errors
+/// Returns an error if no problem with `problem_id` exists.
+pub async fn set_problem_priority(
+    pool: &SqlitePool,
+    problem_id: i64,
+    priority: i64,
+) -> anyhow::Result<()> {
+    let result = sqlx::query("UPDATE problems SET priority = ? WHERE id = ?")
+        .bind(priority)
+        .bind(problem_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to set priority for problem_id: {}", problem_id))?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!("No problem with id {} exists in the database.", problem_id);
+    }
+
+    Ok(())
+}
+
+/// Sets a problem's priority level. A normal level removes its row, so only
+/// high and low problems are stored.
+///
+/// # Errors
+/// Returns an error if no problem with `problem_id` exists.
+pub async fn set_priority_level(
+    pool: &SqlitePool,
+    problem_id: i64,
+    level: PriorityLevel,
+) -> anyhow::Result<()> {
+    if fetch_problem(pool, problem_id).await?.is_none() {
+        anyhow::bail!("No problem with id {} exists in the database.", problem_id);
+    }
+    let query = match level {
+        PriorityLevel::Normal => {
+            sqlx::query("DELETE FROM problem_priorities WHERE problem_id = ?1").bind(problem_id)
+        }
+        _ => sqlx::query(
+            r#"
+            INSERT INTO problem_priorities (problem_id, level) VALUES (?1, ?2)
+            ON CONFLICT(problem_id) DO UPDATE SET level = ?2
+            "#,
+        )
+        .bind(problem_id)
+        .bind(level.value()),
+    };
+    query.execute(pool).await.with_context(|| {
+        format!(
+            "Failed to set the priority level of problem_id: {}",
+            problem_id
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Fetches a problem's priority level, normal unless one was set.
+pub async fn fetch_priority_level(
+    pool: &SqlitePool,
+    problem_id: i64,
+) -> anyhow::Result<PriorityLevel> {
+    let level: Option<i64> =
+        sqlx::query_scalar("SELECT level FROM problem_priorities WHERE problem_id = ?")
+            .bind(problem_id)
+            .fetch_optional(pool)
+            .await
+            .context("Failed to fetch the priority level from the database.")?;
+
+    Ok(level.map_or(PriorityLevel::Normal, PriorityLevel::from_value))
+}
+
+/// Moves a problem to another study-plan week.
+///
+/// # Errors
+/// Returns an error if no problem with `problem_id` exists.
+pub async fn set_problem_week(pool: &SqlitePool, problem_id: i64, week: i64) -> anyhow::Result<()> {
+    let result = sqlx::query("UPDATE problems SET week = ? WHERE id = ?")
+        .bind(week)
+        .bind(problem_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to set the week for problem_id: {}", problem_id))?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!("No problem with id {} exists in the database.", problem_id);
+    }
+
+    Ok(())
+}
+
+/// A problem failed (ShortFail or LongFail) four or more times.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow, serde::Serialize)]
+pub struct Leech {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The problem's name.
+    pub name: String,
+    /// How many attempts at it failed.
+    pub failures: i64,
+    /// Whether `track next` skips it, which it does until it's unsuspended.
+    pub suspended: bool,
+}
+
+/// Fetches every leech, most failed first. With `deck`, only that deck's.
+pub async fn fetch_leeches(pool: &SqlitePool, deck: Option<&str>) -> anyhow::Result<Vec<Leech>> {
+    let leeches = sqlx::query_as::<_, Leech>(
+        r#"
+        SELECT
+            l.problem_id, p.name, l.failures,
+            l.problem_id IN (SELECT problem_id FROM suspended_problems) AS suspended
+        FROM leeches l
+        JOIN problems p ON p.id = l.problem_id
+        WHERE p.id IN (SELECT problem_id FROM deck_problems WHERE ?1 IS NULL OR deck = ?1)
+        ORDER BY l.failures DESC, p."order" ASC
+        "#,
+    )
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch leeches from the database.")?;
+
+    Ok(leeches)
+}
+
+/// Gathers everything `track stats` reports as of `today`. With `deck`, only
+/// that deck's problems count, though the streaks still cover every attempt.
+pub async fn fetch_stats_report(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+    today: NaiveDate,
+) -> anyhow::Result<StatsReport> {
+    let progress = fetch_all_progress(pool, None, deck).await?;
+    let dates = fetch_attempt_dates(pool).await?;
+    let summary = compute_stats(&progress, &dates, today);
+    Ok(StatsReport {
+        average_attempts: summary.average_attempts(),
+        summary,
+        weeks: fetch_week_completion(pool, deck).await?,
+        difficulties: fetch_difficulty_breakdown(pool, deck).await?,
+        tags: fetch_tag_breakdown(pool, deck).await?,
+        leeches: fetch_leeches(pool, deck).await?,
+    })
+}
+
+/// Whether `track next` skips the problem, because it was suspended or is a
+/// leech nobody unsuspended.
+pub async fn is_suspended(pool: &SqlitePool, problem_id: i64) -> anyhow::Result<bool> {
+    let suspended: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM suspended_problems WHERE problem_id = ?)")
+            .bind(problem_id)
+            .fetch_one(pool)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to check whether problem_id {} is suspended",
+                    problem_id
+                )
+            })?;
+
+    Ok(suspended)
+}
+
+/// Suspends a problem, so `track next` skips it, or unsuspends it. Either
+/// choice overrides leech detection.
+pub async fn set_suspended(
+    pool: &SqlitePool,
+    problem_id: i64,
+    suspended: bool,
+    today: NaiveDate,
+) -> anyhow::Result<()> {
+    if fetch_problem(pool, problem_id).await?.is_none() {
+        anyhow::bail!("No problem with id {} exists in the database.", problem_id);
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO problem_suspensions (problem_id, suspended, updated_on) VALUES (?1, ?2, ?3)
+        ON CONFLICT(problem_id) DO UPDATE SET suspended = ?2, updated_on = ?3
+        "#,
+    )
+    .bind(problem_id)
+    .bind(suspended)
+    .bind(today)
+    .execute(pool)
+    .await
+    .with_context(|| format!("Failed to suspend problem_id: {}", problem_id))?;
+
+    Ok(())
+}
+
+/// A timed practice session, opened by `track start`.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct Session {
+    /// The session's row ID.
+    pub id: i64,
+    /// The LeetCode ID of the problem being practiced.
+    pub problem_id: i64,
+    /// When the session started, in local time.
+    pub started_at: NaiveDateTime,
+    /// When it stopped, or `None` while it's running.
+    pub stopped_at: Option<NaiveDateTime>,
+    /// The time spent working, if breaks were taken out of it. `None` means
+    /// the whole session was work.
+    pub focused_seconds: Option<i64>,
+}
+
+impl Session {
+    /// The minutes worked, rounded to the nearest and at least one, as
+    /// logged with the attempt. Without `focused_seconds`, that's the time
+    /// from the start to `until`.
+    pub fn minutes(&self, until: NaiveDateTime) -> i64 {
+        let seconds = self
+            .focused_seconds
+            .unwrap_or_else(|| (until - self.started_at).num_seconds());
+        ((seconds + 30) / 60).max(1)
+    }
+}
+
+/// Fetches the running session, if there is one.
+pub async fn fetch_open_session(pool: &SqlitePool) -> anyhow::Result<Option<Session>> {
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        SELECT id, problem_id, started_at, stopped_at, focused_seconds
+        FROM sessions
+        WHERE stopped_at IS NULL
+        "#,
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch the running session.")?;
+
+    Ok(session)
+}
+
+/// Starts timing a problem at `now`.
+///
+/// # Errors
+/// Returns an error if the problem doesn't exist or a session is already
+/// running, since only one can be.
+pub async fn start_session(
+    pool: &SqlitePool,
+    problem_id: i64,
+    now: NaiveDateTime,
+) -> anyhow::Result<Session> {
+    if fetch_problem(pool, problem_id).await?.is_none() {
+        anyhow::bail!("No problem with id {} exists in the database.", problem_id);
+    }
+    if let Some(open) = fetch_open_session(pool).await? {
+        anyhow::bail!(
+            "Already timing problem {} (started {}). Run `track stop` first.",
+            open.problem_id,
+            open.started_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    let id = sqlx::query("INSERT INTO sessions (problem_id, started_at) VALUES (?, ?)")
+        .bind(problem_id)
+        .bind(now)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to start a session for problem_id: {}", problem_id))?
+        .last_insert_rowid();
+
+    Ok(Session {
+        id,
+        problem_id,
+        started_at: now,
+        stopped_at: None,
+        focused_seconds: None,
+    })
+}
+
+/// Stops the running session at `now` and returns it, or `None` if no
+/// session was running. `focused_seconds` is the time worked, when breaks
+/// shouldn't count.
+pub async fn stop_session(
+    conn: &mut SqliteConnection,
+    now: NaiveDateTime,
+    focused_seconds: Option<i64>,
+) -> anyhow::Result<Option<Session>> {
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        UPDATE sessions SET stopped_at = ?, focused_seconds = ?
+        WHERE stopped_at IS NULL
+        RETURNING id, problem_id, started_at, stopped_at, focused_seconds
+        "#,
+    )
+    .bind(now)
+    .bind(focused_seconds)
+    .fetch_optional(&mut *conn)
+    .await
+    .context("Failed to stop the running session.")?;
+
+    Ok(session)
+}
+
+/// Runs SQLite's `PRAGMA integrity_check`.
+///
+/// Returns the messages it reports; a healthy database yields exactly `["ok"]`.
+pub async fn check_integrity(pool: &SqlitePool) -> anyhow::Result<Vec<String>> {
+    let messages = sqlx::query_scalar::<_, String>("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await
+        .context("Failed to run the integrity check.")?;
+
+    Ok(messages)
+}
+
+/// Lists the migrations applied to the database, oldest first.
+pub async fn fetch_applied_migrations(pool: &SqlitePool) -> anyhow::Result<Vec<AppliedMigration>> {
+    let migrations = sqlx::query_as::<_, AppliedMigration>(
+        "SELECT version, description, success, checksum FROM _sqlx_migrations ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to read applied migrations.")?;
+
+    Ok(migrations)
+}
+
+/// Fetches the ids of progress rows whose problem no longer exists.
+pub async fn fetch_orphan_progress(pool: &SqlitePool) -> anyhow::Result<Vec<i64>> {
+    let orphans = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT pr.problem_id
+        FROM progress pr
+        LEFT JOIN problems p ON pr.problem_id = p.id
+        WHERE p.id IS NULL
+        ORDER BY pr.problem_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to look for orphaned progress rows.")?;
+
+    Ok(orphans)
+}
+
+/// Deletes progress rows whose problem no longer exists, returning how many
+/// were removed.
+pub async fn delete_orphan_progress(pool: &SqlitePool) -> anyhow::Result<u64> {
+    let result =
+        sqlx::query("DELETE FROM progress WHERE problem_id NOT IN (SELECT id FROM problems)")
+            .execute(pool)
+            .await
+            .context("Failed to delete orphaned progress rows.")?;
+
+    Ok(result.rows_affected())
+}
+
+/// Lists every deck with its problem count and how many have been attempted.
+pub async fn fetch_deck_summaries(pool: &SqlitePool) -> anyhow::Result<Vec<DeckSummary>> {
+    let decks = sqlx::query_as::<_, DeckSummary>(
+        r#"
+        SELECT
+            dp.deck,
+            COUNT(*) as total,
+            COUNT(pr.problem_id) as attempted
+        FROM
+            deck_problems dp
+        LEFT JOIN
+            progress pr ON dp.problem_id = pr.problem_id
+        GROUP BY
+            dp.deck
+        ORDER BY
+            dp.deck ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch decks from the database.")?;
+
+    Ok(decks)
+}
+
+/// Where `problem_id`'s review, due on `due`, should go to keep each day
+/// under `schedule.daily_review_cap`, counting the other problems' reviews
+/// already scheduled around it.
+async fn smoothed_review_date(
+    conn: &mut SqliteConnection,
+    schedule: &Schedule,
+    problem_id: i64,
+    attempted: NaiveDate,
+    due: NaiveDate,
+) -> anyhow::Result<NaiveDate> {
+    if schedule.daily_review_cap.is_none() {
+        return Ok(due);
+    }
+    let (earliest, latest) = schedule.smoothing_window(attempted, due);
+    let load: HashMap<NaiveDate, u32> = sqlx::query_as(
+        r#"
+        SELECT next_attempt_date, COUNT(*)
+        FROM progress
+        WHERE next_attempt_date >= ?1 AND next_attempt_date <= ?2 AND problem_id != ?3
+            AND problem_id IN (SELECT problem_id FROM deck_problems)
+        GROUP BY next_attempt_date
+        "#,
+    )
+    .bind(earliest)
+    .bind(latest)
+    .bind(problem_id)
+    .fetch_all(&mut *conn)
+    .await
+    .context("Failed to count the reviews scheduled around a new one.")?
+    .into_iter()
+    .collect();
+
+    Ok(schedule.smooth(attempted, due, &load))
+}
+
+/// Recomputes every problem's `next_attempt_date` with the current scheduling
+/// rules, in a single transaction.
+///
+/// Returns how many dates changed.
+pub async fn reschedule_all_progress(pool: &SqlitePool) -> anyhow::Result<u64> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to start a transaction.")?;
+
+    let rows = sqlx::query_as::<_, ProblemAttempt>("SELECT * FROM progress")
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to fetch progress from the database.")?;
+
+    let mut changed = 0;
+    for mut row in rows {
+        let previous = row.next_attempt_date;
+        row.reschedule();
+        if let Some(due) = row.next_attempt_date {
+            let smoothed = smoothed_review_date(
+                &mut tx,
+                schedule::current(),
+                row.problem_id,
+                row.last_attempted,
+                due,
+            )
+            .await?;
+            row.next_attempt_date = Some(smoothed);
+        }
+        if row.next_attempt_date == previous {
+            continue;
+        }
+
+        sqlx::query("UPDATE progress SET next_attempt_date = ? WHERE problem_id = ?")
+            .bind(row.next_attempt_date)
+            .bind(row.problem_id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to reschedule problem_id: {}", row.problem_id))?;
+        changed += 1;
+    }
+
+    tx.commit()
+        .await
+        .context("Failed to commit the new review dates.")?;
+
+    Ok(changed)
+}
+
+/// Computes the first-attempt Easy rate for each difficulty.
+///
+/// A problem counts once its first attempt is in the history log. Problems
+/// first attempted before the log existed only have their latest attempt
+/// recorded, so they're left out rather than counted by a later rating.
+pub async fn fetch_first_attempt_rates(
+    pool: &SqlitePool,
+    since: Option<NaiveDate>,
+    deck: Option<&str>,
+) -> anyhow::Result<Vec<FirstAttemptRate>> {
+    let rates = sqlx::query_as::<_, FirstAttemptRate>(
+        r#"
+        SELECT
+            p.difficulty,
+            COUNT(*) as attempted,
+            SUM(h.rating = 'Easy') as easy
+        FROM
+            attempt_history h
+        JOIN
+            problems p ON h.problem_id = p.id
+        WHERE
+            -- A merge can bring in a second "first" attempt; keep the earliest.
+            h.id IN (
+                SELECT MIN(id) FROM attempt_history WHERE attempt_number = 1 GROUP BY problem_id
+            )
+            AND (?1 IS NULL OR h.attempted_on >= ?1)
+            AND p.id IN (SELECT problem_id FROM deck_problems WHERE ?2 IS NULL OR deck = ?2)
+        GROUP BY
+            p.difficulty
+        ORDER BY
+            CASE p.difficulty WHEN 'Easy' THEN 0 WHEN 'Medium' THEN 1 WHEN 'Hard' THEN 2 ELSE 3 END
+        "#,
+    )
+    .bind(since)
+    .bind(deck)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch first-attempt rates from the database.")?;
+
+    Ok(rates)
+}
+
+/// Merges another track database into this one, in a single transaction.
+///
+/// Problems and progress that only exist in the other database are copied
+/// over. A problem stored under another id there but with a name stored here
+/// is treated as the same problem, and its data is filed under the id here. Where both have progress for a problem, the row with more attempts
+/// wins, with ties going to the later `last_attempted`. Attempt history is
+/// combined, skipping rows already present. The other database is migrated to
+/// the current schema first, but otherwise left untouched.
+pub async fn merge_database(pool: &SqlitePool, other_path: &Path) -> anyhow::Result<MergeSummary> {
+    anyhow::ensure!(
+        other_path.exists(),
+        "Database to merge does not exist: {}",
+        other_path.display()
+    );
+    // Bring the other file up to date so both schemas match.
+    init_pool(&format!("sqlite:{}", other_path.display()))
+        .await?
+        .close()
+        .await;
+
+    // ATTACH is per connection and can't run inside a transaction, so pin
+    // one connection for the whole merge.
+    let mut conn = pool
+        .acquire()
+        .await
+        .context("Failed to acquire a database connection.")?;
+    sqlx::query("ATTACH DATABASE ? AS other")
+        .bind(other_path.to_string_lossy().into_owned())
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to attach {}", other_path.display()))?;
+
+    let result = merge_attached(&mut conn).await;
+
+    sqlx::query("DETACH DATABASE other")
+        .execute(&mut *conn)
+        .await
+        .context("Failed to detach the merged database.")?;
+
+    result
+}
+
+/// Does the work of `merge_database` once the other database is attached.
+async fn merge_attached(conn: &mut SqliteConnection) -> anyhow::Result<MergeSummary> {
+    let mut tx = conn
+        .begin()
+        .await
+        .context("Failed to start a transaction.")?;
+
+    // Maps each of the other database's problem ids to the one used here: the
+    // same id if it's stored here, else the id its name is stored under (the
+    // names are unique, and the ids may come from an older id space), else its
+    // own id, for a problem that's about to be copied over.
+    sqlx::query(
+        "CREATE TEMP TABLE merge_ids (other_id INTEGER PRIMARY KEY, main_id INTEGER NOT NULL)",
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create the merge's id map.")?;
+    sqlx::query(
+        r#"
+        INSERT INTO temp.merge_ids (other_id, main_id)
+        SELECT o.id, COALESCE(
+            (SELECT id FROM main.problems WHERE id = o.id),
+            (SELECT id FROM main.problems WHERE name = o.name),
+            o.id
+        )
+        FROM other.problems o
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to match up problem ids.")?;
+    let problems_matched_by_name = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM temp.merge_ids WHERE main_id != other_id",
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to count the problems matched by name.")?
+        as u64;
+
+    let problems_added = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO main.problems (id, "order", name, difficulty, week, priority, deck, url)
+        SELECT id, "order", name, difficulty, week, priority, deck, url
+        FROM other.problems
+        WHERE
+            id NOT IN (SELECT id FROM main.problems)
+            AND id IN (SELECT other_id FROM temp.merge_ids WHERE main_id = other_id)
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy problems.")?
+    .rows_affected();
+
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO main.deck_problems (deck, problem_id)
+        SELECT o.deck, m.main_id
+        FROM other.deck_problems o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy deck memberships.")?;
+    sqlx::query(
+        "UPDATE main.problems SET archived_on = NULL WHERE archived_on IS NOT NULL AND id IN (SELECT problem_id FROM main.deck_problems)",
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to restore problems that rejoined a deck.")?;
+
+    // Tag ids differ between databases, so tags are matched up by name.
+    sqlx::query("INSERT OR IGNORE INTO main.tags (name) SELECT name FROM other.tags")
+        .execute(&mut *tx)
+        .await
+        .context("Failed to copy tags.")?;
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO main.problem_tags (problem_id, tag_id)
+        SELECT m.main_id, mt.id
+        FROM other.problem_tags pt
+        JOIN temp.merge_ids m ON m.other_id = pt.problem_id
+        JOIN other.tags ot ON ot.id = pt.tag_id
+        JOIN main.tags mt ON mt.name = ot.name
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy problem tags.")?;
+
+    // Metadata is a cache of LeetCode's, so whichever copy is already here
+    // is as good as the other one.
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO main.problem_meta (problem_id, title, acceptance_rate, fetched_on)
+        SELECT m.main_id, o.title, o.acceptance_rate, o.fetched_on
+        FROM other.problem_meta o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy problem metadata.")?;
+
+    // As with notes, a suspension already set here wins.
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO main.problem_suspensions (problem_id, suspended, updated_on)
+        SELECT m.main_id, o.suspended, o.updated_on
+        FROM other.problem_suspensions o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy problem suspensions.")?;
+
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO main.problem_priorities (problem_id, level)
+        SELECT m.main_id, o.level
+        FROM other.problem_priorities o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy problem priority levels.")?;
+
+    let progress_updated = sqlx::query(
+        r#"
+        UPDATE main.progress
+        SET
+            last_attempted = o.last_attempted,
+            attempt_rating = o.attempt_rating,
+            next_attempt_date = o.next_attempt_date,
+            number_of_attempts = o.number_of_attempts,
+            language = o.language,
+            mastered = o.mastered,
+            repetitions = o.repetitions,
+            ease = o.ease
+        FROM other.progress o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        WHERE
+            main.progress.problem_id = m.main_id
+            AND (
+                o.number_of_attempts > main.progress.number_of_attempts
+                OR (
+                    o.number_of_attempts = main.progress.number_of_attempts
+                    AND o.last_attempted > main.progress.last_attempted
+                )
+            )
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update overlapping progress.")?
+    .rows_affected();
+
+    let progress_added = sqlx::query(
+        r#"
+        INSERT INTO main.progress
+            (problem_id, last_attempted, attempt_rating, next_attempt_date, number_of_attempts, language, mastered, repetitions, ease)
+        SELECT
+            m.main_id, o.last_attempted, o.attempt_rating, o.next_attempt_date, o.number_of_attempts,
+            o.language, o.mastered, o.repetitions, o.ease
+        FROM other.progress o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        WHERE m.main_id NOT IN (SELECT problem_id FROM main.progress)
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy progress.")?
+    .rows_affected();
+
+    let history_added = sqlx::query(
+        r#"
+        INSERT INTO main.attempt_history
+            (problem_id, attempted_on, rating, language, attempt_number, duration_minutes, note)
+        SELECT
+            m.main_id, o.attempted_on, o.rating, o.language, o.attempt_number,
+            o.duration_minutes, o.note
+        FROM other.attempt_history o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        WHERE
+            NOT EXISTS (
+                SELECT 1 FROM main.attempt_history h
+                WHERE h.problem_id = m.main_id
+                    AND h.attempted_on = o.attempted_on
+                    AND h.rating = o.rating
+                    AND h.attempt_number = o.attempt_number
+            )
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy attempt history.")?
+    .rows_affected();
+
+    // A problem note already in this database wins over the other one's.
+    let notes_added = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO main.problem_notes (problem_id, note, updated_on)
+        SELECT m.main_id, o.note, o.updated_on
+        FROM other.problem_notes o
+        JOIN temp.merge_ids m ON m.other_id = o.problem_id
+        "#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to copy problem notes.")?
+    .rows_affected();
+
+    sqlx::query("DROP TABLE temp.merge_ids")
+        .execute(&mut *tx)
+        .await
+        .context("Failed to drop the merge's id map.")?;
+    tx.commit().await.context("Failed to commit the merge.")?;
+
+    Ok(MergeSummary {
+        problems_added,
+        problems_matched_by_name,
+        progress_added,
+        progress_updated,
+        history_added,
+        notes_added,
+    })
+}
+
+/// Takes a snapshot of the whole database for `track export --all`.
+pub async fn fetch_snapshot(pool: &SqlitePool, today: NaiveDate) -> anyhow::Result<Snapshot> {
+    let mut decks: HashMap<i64, Vec<String>> = HashMap::new();
+    for (problem_id, deck) in sqlx::query_as::<_, (i64, String)>(
+        "SELECT problem_id, deck FROM deck_problems ORDER BY deck",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch deck memberships.")?
+    {
+        decks.entry(problem_id).or_default().push(deck);
+    }
+
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for (problem_id, tag) in sqlx::query_as::<_, (i64, String)>(
+        "SELECT pt.problem_id, t.name FROM problem_tags pt JOIN tags t ON t.id = pt.tag_id ORDER BY t.name",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch problem tags.")?
+    {
+        tags.entry(problem_id).or_default().push(tag);
+    }
+
+    let problems = fetch_all_problems(pool, None)
+        .await?
+        .into_iter()
+        .map(|problem| SnapshotProblem {
+            decks: decks.remove(&problem.id).unwrap_or_default(),
+            tags: tags.remove(&problem.id).unwrap_or_default(),
+            problem,
+        })
+        .collect();
+
+    let progress = sqlx::query_as::<_, ProblemAttempt>(
+        r#"
+        SELECT problem_id, last_attempted, attempt_rating, next_attempt_date, number_of_attempts, language, mastered, repetitions, ease
+        FROM progress
+        ORDER BY problem_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch progress.")?;
+
+    let history = sqlx::query_as::<_, SnapshotAttempt>(
+        r#"
+        SELECT problem_id, attempt_number, attempted_on, rating, language, duration_minutes, note
+        FROM attempt_history
+        ORDER BY problem_id, attempt_number, id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch attempt history.")?;
+
+    let notes = sqlx::query_as::<_, SnapshotNote>(
+        "SELECT problem_id, note, updated_on FROM problem_notes ORDER BY problem_id",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch problem notes.")?;
+
+    Ok(Snapshot {
+        version: SNAPSHOT_VERSION,
+        exported_on: today,
+        problems,
+        progress,
+        history,
+        notes,
+    })
+}
+
+/// Merges a snapshot from `track export --all` into the database.
+///
+/// The snapshot is loaded into a scratch database in the system temp
+/// directory, which is then merged with `merge_database`, so an import
+/// follows exactly the same rules as `track merge`. Importing into an empty
+/// database rebuilds it from the snapshot.
+pub async fn import_snapshot(
+    pool: &SqlitePool,
+    snapshot: &Snapshot,
+) -> anyhow::Result<MergeSummary> {
+    // Imports running side by side (as tests do) each need their own file.
+    static IMPORTS: AtomicUsize = AtomicUsize::new(0);
+    let scratch = std::env::temp_dir().join(format!(
+        "track-import-{}-{}.db",
+        std::process::id(),
+        IMPORTS.fetch_add(1, Ordering::Relaxed)
+    ));
+    remove_database_files(&scratch);
+
+    let result = async {
+        let scratch_pool = init_pool(&format!("sqlite:{}", scratch.display())).await?;
+        let loaded = load_snapshot(&scratch_pool, snapshot).await;
+        scratch_pool.close().await;
+        loaded?;
+        merge_database(pool, &scratch).await
+    }
+    .await;
+
+    remove_database_files(&scratch);
+    result
+}
+
+/// Writes every row of `snapshot` into an empty database.
+async fn load_snapshot(pool: &SqlitePool, snapshot: &Snapshot) -> anyhow::Result<()> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to start a transaction.")?;
+
+    for SnapshotProblem {
+        problem,
+        decks,
+        tags,
+    } in &snapshot.problems
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO problems (id, "order", name, difficulty, week, priority, deck, url)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(problem.id)
+        .bind(problem.order)
+        .bind(&problem.name)
+        .bind(problem.difficulty)
+        .bind(problem.week)
+        .bind(problem.priority)
+        .bind(&problem.deck)
+        .bind(&problem.url)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to load problem: {}", problem.name))?;
+
+        for deck in decks.iter().chain([&problem.deck]) {
+            sqlx::query("INSERT OR IGNORE INTO deck_problems (deck, problem_id) VALUES (?, ?)")
+                .bind(deck)
+                .bind(problem.id)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| {
+                    format!("Failed to add problem {} to deck '{}'", problem.id, deck)
+                })?;
+        }
+        set_problem_tags(&mut tx, problem.id, tags).await?;
+    }
+
+    for progress in &snapshot.progress {
+        sqlx::query(
+            r#"
+            INSERT INTO progress
+                (problem_id, last_attempted, attempt_rating, next_attempt_date, number_of_attempts, language, mastered, repetitions, ease)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(progress.problem_id)
+        .bind(progress.last_attempted)
+        .bind(progress.attempt_rating)
+        .bind(progress.next_attempt_date)
+        .bind(progress.number_of_attempts)
+        .bind(&progress.language)
+        .bind(progress.mastered)
+        .bind(progress.repetitions)
+        .bind(progress.ease)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to load progress for problem_id: {}", progress.problem_id))?;
+    }
+
+    for SnapshotAttempt {
+        problem_id,
+        attempt,
+    } in &snapshot.history
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO attempt_history
+                (problem_id, attempted_on, rating, language, attempt_number, duration_minutes, note)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(problem_id)
+        .bind(attempt.attempted_on)
+        .bind(attempt.rating)
+        .bind(&attempt.language)
+        .bind(attempt.attempt_number)
+        .bind(attempt.duration_minutes)
+        .bind(&attempt.note)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to load an attempt for problem_id: {}", problem_id))?;
+    }
+
+    for note in &snapshot.notes {
+        sqlx::query("INSERT INTO problem_notes (problem_id, note, updated_on) VALUES (?, ?, ?)")
+            .bind(note.problem_id)
+            .bind(&note.note)
+            .bind(note.updated_on)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to load the note for problem_id: {}",
+                    note.problem_id
+                )
+            })?;
+    }
+
+    tx.commit().await.context("Failed to commit the snapshot.")
+}
+
+/// Deletes a database file and its WAL side files, ignoring any that don't
+/// exist.
+fn remove_database_files(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = path.as_os_str().to_owned();
+        file.push(suffix);
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+/// Marks a problem mastered so it's never suggested for review again.
+///
+/// # Errors
+/// Returns an error if the problem has no progress yet.
+pub async fn master_problem(pool: &SqlitePool, problem_id: i64) -> anyhow::Result<()> {
+    let mut progress = fetch_progress(pool, problem_id).await?.with_context(|| {
+        format!(
+            "Problem {} hasn't been attempted yet, so it can't be marked mastered.",
+            problem_id
+        )
+    })?;
+    progress.master();
+
+    sqlx::query("UPDATE progress SET mastered = ?, next_attempt_date = ? WHERE problem_id = ?")
+        .bind(progress.mastered)
+        .bind(progress.next_attempt_date)
+        .bind(problem_id)
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to mark problem_id {} mastered", problem_id))?;
+
+    Ok(())
+}
+
+/// Archives problems: they leave every deck and get `archived_on` set to
+/// `today`, but keep their progress, history, notes, tags and sessions. Runs
+/// on `conn` so callers can make it part of a larger transaction. Returns how
+/// many problems were newly archived.
+pub async fn archive_problems(
+    conn: &mut SqliteConnection,
+    problem_ids: &[i64],
+    today: NaiveDate,
+) -> anyhow::Result<u64> {
+    let mut archived = 0;
+    for &problem_id in problem_ids {
+        sqlx::query("DELETE FROM deck_problems WHERE problem_id = ?")
+            .bind(problem_id)
+            .execute(&mut *conn)
+            .await
+            .with_context(|| {
+                format!("Failed to take problem_id {} out of its decks", problem_id)
+            })?;
+        archived += sqlx::query(
+            "UPDATE problems SET archived_on = ?2 WHERE id = ?1 AND archived_on IS NULL",
+        )
+        .bind(problem_id)
+        .bind(today)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to archive problem_id: {}", problem_id))?
+        .rows_affected();
+    }
+
+    Ok(archived)
+}
+
+/// When the problem was archived, if it is. Archived problems are in no deck,
+/// so they're left out of every listing.
+pub async fn fetch_archived_on(
+    pool: &SqlitePool,
+    problem_id: i64,
+) -> anyhow::Result<Option<NaiveDate>> {
+    let archived_on = sqlx::query_scalar("SELECT archived_on FROM problems WHERE id = ?")
+        .bind(problem_id)
+        .fetch_optional(pool)
+        .await
+        .with_context(|| format!("Failed to fetch problem with id: {}", problem_id))?;
+
+    Ok(archived_on.flatten())
+}
+
+/// Takes `problem_ids` out of `deck`, in one transaction. Problems left in no
+/// deck at all are archived with `archive_problems`; ones another bank still
+/// uses stay as they are. Returns how many were archived.
+pub async fn remove_from_deck(
+    pool: &SqlitePool,
+    deck: &str,
+    problem_ids: &[i64],
+    today: NaiveDate,
+) -> anyhow::Result<u64> {
+    let mut tx = pool
+        .begin()
+        .await
+        .context("Failed to start a transaction.")?;
+
+    let mut orphaned = Vec::new();
+    for &problem_id in problem_ids {
+        sqlx::query("DELETE FROM deck_problems WHERE deck = ?1 AND problem_id = ?2")
+            .bind(deck)
+            .bind(problem_id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to remove problem_id {} from deck '{}'",
+                    problem_id, deck
+                )
+            })?;
+
+        let remaining: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM deck_problems WHERE problem_id = ?")
+                .bind(problem_id)
+                .fetch_one(&mut *tx)
+                .await
+                .context("Failed to count the problem's decks.")?;
+        if remaining == 0 {
+            orphaned.push(problem_id);
+        }
+    }
+
+    let archived = archive_problems(&mut tx, &orphaned, today).await?;
+    tx.commit().await.context("Failed to commit the removal.")?;
+
+    Ok(archived)
+}
+
+/// Fetches the note set on a problem with `track note`, if any.
+pub async fn fetch_problem_note(
+    pool: &SqlitePool,
+    problem_id: i64,
+) -> anyhow::Result<Option<String>> {
+    let note =
+        sqlx::query_scalar::<_, String>("SELECT note FROM problem_notes WHERE problem_id = ?")
+            .bind(problem_id)
+            .fetch_optional(pool)
+            .await
+            .with_context(|| format!("Failed to fetch the note for problem_id: {}", problem_id))?;
+
+    Ok(note)
+}
+
+/// A problem as named on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemRef {
+    /// Its LeetCode ID.
+    Id(i64),
+    /// Its name or a unique prefix of it, its URL slug (`merge-intervals`) or
+    /// its full URL.
+    Name(String),
+}
+
+impl FromStr for ProblemRef {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().parse::<i64>() {
+            Ok(id) => ProblemRef::Id(id),
+            Err(_) => ProblemRef::Name(s.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for ProblemRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemRef::Id(id) => write!(f, "{}", id),
+            ProblemRef::Name(name) => write!(f, "'{}'", name),
+        }
+    }
+}
+
+/// Turns a `ProblemRef` into a problem id. IDs are returned as given; names
+/// are matched by URL slug, then by exact name, then by name prefix, all
+/// ignoring case and punctuation.
+///
+/// # Errors
+/// Returns an error if no problem matches, or if several match equally well.
+pub async fn resolve_problem(pool: &SqlitePool, problem: &ProblemRef) -> anyhow::Result<i64> {
+    let text = match problem {
+        ProblemRef::Id(id) => return Ok(*id),
+        ProblemRef::Name(text) => text,
+    };
+
+    let problems = fetch_all_problems(pool, None).await?;
+    let slug = url_slug(text).unwrap_or(text).trim().to_lowercase();
+    let wanted = normalize(text);
+    let tiers: [&dyn Fn(&Problem) -> bool; 3] = [
+        &|p| url_slug(&p.link()) == Some(slug.as_str()),
+        &|p| normalize(&p.name) == wanted,
+        &|p| !wanted.is_empty() && normalize(&p.name).starts_with(&wanted),
+    ];
+
+    for matches_tier in tiers {
+        let found: Vec<&Problem> = problems.iter().filter(|p| matches_tier(p)).collect();
+        match found.as_slice() {
+            [] => continue,
+            [only] => return Ok(only.id),
+            several => {
+                let listed: Vec<String> = several
+                    .iter()
+                    .map(|p| format!("  #{} {}", p.id, p.name))
+                    .collect();
+                anyhow::bail!(
+                    "'{}' matches {} problems. Use one of their IDs instead:\n{}",
+                    text,
+                    several.len(),
+                    listed.join("\n")
+                );
+            }
+        }
+    }
+
+    anyhow::bail!(
+        "No problem matches '{}'. Try `track search {}` to look it up.",
+        text,
+        text
+    )
+}
+
+/// Maps each problem's LeetCode URL slug to its ID, for matching
+/// submissions pulled by `track sync`.
+pub async fn fetch_problem_ids_by_slug(
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<HashMap<String, i64>> {
+    Ok(fetch_all_problems(pool, deck)
+        .await?
+        .iter()
+        .filter_map(|problem| Some((url_slug(&problem.link())?.to_string(), problem.id)))
+        .collect())
+}
+
+/// The slug in a LeetCode problem URL, e.g. `two-sum` in
+/// `https://leetcode.com/problems/two-sum/description/`.
+pub fn url_slug(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("/problems/")?;
+    rest.split('/').next().filter(|slug| !slug.is_empty())
+}
+
+/// Fetches every note as `(problem_id, note)`: the ones set with `track note`
+/// and the ones logged with attempts.
+pub async fn fetch_all_notes(pool: &SqlitePool) -> anyhow::Result<Vec<(i64, String)>> {
+    let notes = sqlx::query_as::<_, (i64, String)>(
+        r#"
+        SELECT problem_id, note FROM problem_notes
+        UNION ALL
+        SELECT problem_id, note FROM attempt_history WHERE note IS NOT NULL
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch notes from the database.")?;
+
+    Ok(notes)
+}
+
+/// Metadata about a problem fetched from LeetCode.
+#[derive(Debug, FromRow)]
+pub struct StoredMeta {
+    /// The problem's title on LeetCode.
+    pub title: String,
+    /// The percentage of submissions that are accepted, if LeetCode said.
+    pub acceptance_rate: Option<f64>,
+    /// When the metadata was fetched.
+    pub fetched_on: NaiveDate,
+}
+
+/// Stores metadata fetched from LeetCode: the difficulty replaces the
+/// problem's, the topic tags are added to its tags, and the rest is kept in
+/// `problem_meta`.
+pub async fn save_problem_meta(
+    conn: &mut SqliteConnection,
+    problem_id: i64,
+    meta: &ProblemMeta,
+    today: NaiveDate,
+) -> anyhow::Result<()> {
+    sqlx::query("UPDATE problems SET difficulty = ? WHERE id = ?")
+        .bind(meta.difficulty)
+        .bind(problem_id)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to update difficulty for problem_id: {}", problem_id))?;
+
+    let mut tags = sqlx::query_scalar::<_, String>(
+        "SELECT t.name FROM problem_tags pt JOIN tags t ON t.id = pt.tag_id WHERE pt.problem_id = ?",
+    )
+    .bind(problem_id)
+    .fetch_all(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to fetch tags for problem_id: {}", problem_id))?;
+    tags.extend(meta.topic_tags.iter().cloned());
+    set_problem_tags(conn, problem_id, &tags).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO problem_meta (problem_id, title, acceptance_rate, fetched_on)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(problem_id) DO UPDATE SET
+            title = excluded.title,
+            acceptance_rate = excluded.acceptance_rate,
+            fetched_on = excluded.fetched_on
+        "#,
+    )
+    .bind(problem_id)
+    .bind(&meta.title)
+    .bind(meta.ac_rate)
+    .bind(today)
+    .execute(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to save metadata for problem_id: {}", problem_id))?;
+
+    Ok(())
+}
+
+/// Files a daily challenge under `deck`, adding the problem at the end of
+/// the deck if it isn't stored yet, and saves its metadata. Returns whether
+/// the problem was new.
+pub async fn add_daily_problem(
+    pool: &SqlitePool,
+    daily: &DailyChallenge,
+    deck: &str,
+    today: NaiveDate,
+) -> anyhow::Result<bool> {
+    add_problem_from_meta(pool, &daily.meta, &daily.url(), deck, today)
+        .await
+        .context("Failed to commit the daily challenge.")
+}
+
+/// Files the problem at `url` under `deck`, adding it at the end of the deck
+/// if it isn't stored yet, and saves its metadata. Returns whether the
+/// problem was new.
+pub async fn add_problem_from_meta(
+    pool: &SqlitePool,
+    meta: &ProblemMeta,
+    url: &str,
+    deck: &str,
+    today: NaiveDate,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+    let is_new = fetch_problem(&mut *tx, meta.question_id).await?.is_none();
+    let order = next_deck_order(&mut *tx, deck).await?;
+
+    Problem {
+        id: meta.question_id,
+        order,
+        name: meta.title.clone(),
+        difficulty: Some(meta.difficulty),
+        week: None,
+        priority: 0,
+        deck: deck.to_string(),
+        url: Some(url.to_string()),
+    }
+    .insert(&mut *tx)
+    .await?;
+    save_problem_meta(&mut tx, meta.question_id, meta, today).await?;
+    tx.commit()
+        .await
+        .with_context(|| format!("Failed to add problem: {}", meta.title))?;
+
+    Ok(is_new)
+}
+
+/// The `order` a problem added at the end of `deck` gets.
+pub async fn next_deck_order<'e, E>(executor: E, deck: &str) -> anyhow::Result<i64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COALESCE(MAX(p."order"), 0) + 1
+        FROM problems p
+        JOIN deck_problems dp ON dp.problem_id = p.id
+        WHERE dp.deck = ?
+        "#,
+    )
+    .bind(deck)
+    .fetch_one(executor)
+    .await
+    .with_context(|| format!("Failed to find the end of deck '{}'", deck))
+}
+
+/// Fetches the metadata stored for a problem, if it's been fetched.
+pub async fn fetch_problem_meta(
+    pool: &SqlitePool,
+    problem_id: i64,
+) -> anyhow::Result<Option<StoredMeta>> {
+    sqlx::query_as::<_, StoredMeta>(
+        "SELECT title, acceptance_rate, fetched_on FROM problem_meta WHERE problem_id = ?",
+    )
+    .bind(problem_id)
+    .fetch_optional(pool)
+    .await
+    .with_context(|| format!("Failed to fetch metadata for problem_id: {}", problem_id))
+}
+
+/// The IDs of every problem with stored metadata.
+pub async fn fetch_problem_meta_ids(pool: &SqlitePool) -> anyhow::Result<HashSet<i64>> {
+    let ids = sqlx::query_scalar::<_, i64>("SELECT problem_id FROM problem_meta")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch problem metadata ids.")?;
+
+    Ok(ids.into_iter().collect())
+}
+
+/// Fetches every note set with `track note`, by problem ID.
+pub async fn fetch_problem_notes(pool: &SqlitePool) -> anyhow::Result<HashMap<i64, String>> {
+    let notes = sqlx::query_as::<_, (i64, String)>("SELECT problem_id, note FROM problem_notes")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch problem notes from the database.")?;
+
+    Ok(notes.into_iter().collect())
+}
+
+/// Replaces a problem's note. A `None` or blank note removes it.
+///
+/// # Errors
+/// Returns an error if the problem doesn't exist.
+pub async fn set_problem_note(
+    pool: &SqlitePool,
+    problem_id: i64,
+    note: Option<&str>,
+    today: NaiveDate,
+) -> anyhow::Result<()> {
+    if fetch_problem(pool, problem_id).await?.is_none() {
+        anyhow::bail!("No problem with id {} exists in the database.", problem_id);
+    }
+
+    let result = match note.map(str::trim).filter(|note| !note.is_empty()) {
+        Some(note) => {
+            sqlx::query(
+                r#"
+            INSERT INTO problem_notes (problem_id, note, updated_on) VALUES (?1, ?2, ?3)
+            ON CONFLICT(problem_id) DO UPDATE SET note = ?2, updated_on = ?3
+            "#,
+            )
+            .bind(problem_id)
+            .bind(note)
+            .bind(today)
+            .execute(pool)
+            .await
+        }
+        None => {
+            sqlx::query("DELETE FROM problem_notes WHERE problem_id = ?")
+                .bind(problem_id)
+                .execute(pool)
+                .await
+        }
+    };
+    result.with_context(|| format!("Failed to save the note for problem_id: {}", problem_id))?;
+
+    Ok(())
+}
+
+/// Fetches every cached slug-to-id lookup from earlier builds.
+pub async fn fetch_resolved_ids(pool: &SqlitePool) -> anyhow::Result<HashMap<String, i64>> {
+    let rows = sqlx::query_as::<_, (String, i64)>("SELECT slug, problem_id FROM resolved_ids")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch cached problem ids.")?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Remembers that `slug` resolved to `problem_id`, replacing any earlier entry.
+pub async fn cache_resolved_id<'e, E>(
+    executor: E,
+    slug: &str,
+    problem_id: i64,
+) -> anyhow::Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query("INSERT OR REPLACE INTO resolved_ids (slug, problem_id) VALUES (?, ?)")
+        .bind(slug)
+        .bind(problem_id)
+        .execute(executor)
+        .await
+        .with_context(|| format!("Failed to cache the id for '{}'", slug))?;
+
+    Ok(())
+}
+
+/// Tables that refer to a problem by its id in a `problem_id` column.
+const PROBLEM_ID_TABLES: [&str; 11] = [
+    "progress",
+    "attempt_history",
+    "problem_notes",
+    "deck_problems",
+    "problem_tags",
+    "problem_meta",
+    "problem_suspensions",
+    "problem_priorities",
+    "sessions",
+    "archived_progress",
+    "resolved_ids",
+];
+
+/// Moves the problem stored as `from`, with its progress, history, notes and
+/// everything else kept about it, to the id `to`, which must be free. Used
+/// when a build finds a problem stored under an id that isn't the one
+/// LeetCode shows for it. Runs on `conn` so callers can make it part of a
+/// larger transaction, which it must be: foreign keys are only checked once
+/// that commits.
+pub async fn rekey_problem(conn: &mut SqliteConnection, from: i64, to: i64) -> anyhow::Result<()> {
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .context("Failed to defer foreign key checks.")?;
+    sqlx::query("UPDATE problems SET id = ?2 WHERE id = ?1")
+        .bind(from)
+        .bind(to)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to move problem #{} to #{}", from, to))?;
+    for table in PROBLEM_ID_TABLES {
+        sqlx::query(&format!(
+            "UPDATE {} SET problem_id = ?2 WHERE problem_id = ?1",
+            table
+        ))
+        .bind(from)
+        .bind(to)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to move problem #{}'s {} to #{}", from, table, to))?;
+    }
+
+    Ok(())
+}
+
+/// Sets a problem's next review date directly, bypassing the scheduler.
+/// `None` leaves it unscheduled.
+///
+/// # Errors
+/// Returns an error if the problem has no progress yet.
+pub async fn set_next_attempt_date(
+    pool: &SqlitePool,
+    problem_id: i64,
+    next_attempt_date: Option<NaiveDate>,
+) -> anyhow::Result<()> {
+    let result = sqlx::query("UPDATE progress SET next_attempt_date = ? WHERE problem_id = ?")
+        .bind(next_attempt_date)
+        .bind(problem_id)
+        .execute(pool)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to set the review date for problem_id: {}",
+                problem_id
+            )
+        })?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!(
+            "Problem {} has no progress yet. Log an attempt before scheduling its review.",
+            problem_id
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem_attempts::preview_next_attempt_date;
+    use crate::problems::UpsertOutcome;
+    use crate::schedule::ReviewState;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    async fn seeded_pool() -> SqlitePool {
+        let pool = init_pool("sqlite::memory:").await.unwrap();
+        seed(&pool).await;
+        pool
+    }
+
+    async fn seed(pool: &SqlitePool) {
+        for (id, order, name, difficulty, deck) in [
+            (1, 1, "Two Sum", LeetCodeDifficulty::Easy, "grind-75"),
+            (
+                20,
+                2,
+                "Valid Parentheses",
+                LeetCodeDifficulty::Easy,
+                "grind-75",
+            ),
+            (146, 1, "LRU Cache", LeetCodeDifficulty::Medium, "company"),
+        ] {
+            Problem {
+                id,
+                order,
+                name: name.to_string(),
+                difficulty: Some(difficulty),
+                week: Some(1),
+                priority: 0,
+                deck: deck.to_string(),
+                url: None,
+            }
+            .insert(pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn counts_problems_by_the_day_they_were_first_attempted() {
+        let pool = seeded_pool().await;
+        for (id, day) in [(1, "2024-02-28"), (1, "2024-03-04"), (20, "2024-03-05")] {
+            log_attempt(
+                &pool,
+                id,
+                AttemptRating::Hard,
+                Some(date(day)),
+                AttemptDetails::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // Two Sum was reviewed this week, but started the week before.
+        let started = count_problems_started_between(&pool, date("2024-03-04"), date("2024-03-10"))
+            .await
+            .unwrap();
+        assert_eq!(started, 1);
+
+        let new =
+            fetch_unattempted_problems(&pool, None, NextFilter::default(), NextStrategy::Order, 5)
+                .await
+                .unwrap();
+        assert_eq!(new.iter().map(|p| p.id).collect::<Vec<_>>(), [146]);
+    }
+
+    #[tokio::test]
+    async fn fetch_progress_is_none_before_any_attempt() {
+        let pool = seeded_pool().await;
+        assert!(fetch_progress(&pool, 1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn logged_attempts_are_fetched_and_updated() {
+        let pool = seeded_pool().await;
+        add_or_replace_progress(
+            &pool,
+            1,
+            AttemptRating::Messy,
+            Some(date("2024-03-01")),
+            None,
+        )
+        .await
+        .unwrap();
+        update_progress(
+            &pool,
+            1,
+            AttemptRating::Easy,
+            Some(date("2024-03-05")),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let progress = fetch_progress(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(progress.attempt_rating, AttemptRating::Easy);
+        assert_eq!(progress.last_attempted, date("2024-03-05"));
+        assert_eq!(progress.number_of_attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn update_progress_requires_a_first_attempt() {
+        let pool = seeded_pool().await;
+        assert!(
+            update_progress(&pool, 1, AttemptRating::Easy, None, None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_all_progress_lists_attempted_problems_newest_first() {
+        let pool = seeded_pool().await;
+        add_or_replace_progress(
+            &pool,
+            1,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            None,
+        )
+        .await
+        .unwrap();
+        add_or_replace_progress(
+            &pool,
+            20,
+            AttemptRating::Easy,
+            Some(date("2024-03-02")),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let progress = fetch_all_progress(&pool, None, None).await.unwrap();
+        let ids: Vec<i64> = progress.iter().map(|item| item.problem_id).collect();
+        assert_eq!(ids, vec![20, 1]);
+        assert_eq!(progress[0].name, "Valid Parentheses");
+
+        let recent = fetch_all_progress(&pool, Some(date("2024-03-02")), None)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].problem_id, 20);
+    }
+
+    #[tokio::test]
+    async fn next_unattempted_problem_follows_bank_order() {
+        let pool = seeded_pool().await;
+        let next = fetch_next_unattempted_problem(
+            &pool,
+            Some("grind-75"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 1);
+
+        add_or_replace_progress(&pool, 1, AttemptRating::Easy, None, None)
+            .await
+            .unwrap();
+        let next = fetch_next_unattempted_problem(
+            &pool,
+            Some("grind-75"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 20);
+
+        add_or_replace_progress(&pool, 20, AttemptRating::Easy, None, None)
+            .await
+            .unwrap();
+        assert!(
+            fetch_next_unattempted_problem(
+                &pool,
+                Some("grind-75"),
+                NextFilter::default(),
+                NextStrategy::Order
+            )
+            .await
+            .unwrap()
+            .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn pinned_problems_jump_the_queue() {
+        let pool = seeded_pool().await;
+        set_problem_priority(&pool, 20, Problem::PINNED_PRIORITY)
+            .await
+            .unwrap();
+        let next = fetch_next_unattempted_problem(
+            &pool,
+            Some("grind-75"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 20);
+        assert!(next.is_pinned());
+
+        set_problem_priority(&pool, 20, 0).await.unwrap();
+        let next = fetch_next_unattempted_problem(
+            &pool,
+            Some("grind-75"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 1);
+    }
+
+    #[tokio::test]
+    async fn lists_pinned_problems_attempted_or_not() {
+        let pool = seeded_pool().await;
+        for id in [146, 20] {
+            set_problem_priority(&pool, id, Problem::PINNED_PRIORITY)
+                .await
+                .unwrap();
+        }
+        add_or_replace_progress(&pool, 20, AttemptRating::Easy, None, None)
+            .await
+            .unwrap();
+
+        let pinned = fetch_pinned_problems(&pool, None).await.unwrap();
+        assert_eq!(
+            pinned
+                .iter()
+                .map(|p| (p.problem.id, p.attempted))
+                .collect::<Vec<_>>(),
+            [(146, false), (20, true)]
+        );
+        let pinned = fetch_pinned_problems(&pool, Some("grind-75"))
+            .await
+            .unwrap();
+        assert_eq!(
+            pinned.iter().map(|p| p.problem.id).collect::<Vec<_>>(),
+            [20]
+        );
+    }
+
+    #[tokio::test]
+    async fn skipped_problems_go_to_the_back_of_the_queue() {
+        let pool = seeded_pool().await;
+        set_problem_priority(&pool, 146, Problem::SKIPPED_PRIORITY)
+            .await
+            .unwrap();
+        let queue =
+            fetch_unattempted_problems(&pool, None, NextFilter::default(), NextStrategy::Order, 5)
+                .await
+                .unwrap();
+        assert_eq!(queue.iter().map(|p| p.id).collect::<Vec<_>>(), [1, 20, 146]);
+        assert!(queue[2].is_skipped() && !queue[2].is_pinned());
+    }
+
+    #[tokio::test]
+    async fn high_priority_problems_come_first_within_their_week() {
+        let pool = seeded_pool().await;
+        let next = |pool: SqlitePool| async move {
+            fetch_next_unattempted_problem(
+                &pool,
+                Some("grind-75"),
+                NextFilter::default(),
+                NextStrategy::Order,
+            )
+            .await
+            .unwrap()
+            .unwrap()
+            .id
+        };
+
+        set_priority_level(&pool, 20, PriorityLevel::High)
+            .await
+            .unwrap();
+        assert_eq!(next(pool.clone()).await, 20);
+        assert_eq!(
+            fetch_priority_level(&pool, 20).await.unwrap(),
+            PriorityLevel::High
+        );
+
+        set_problem_week(&pool, 20, 2).await.unwrap();
+        assert_eq!(next(pool.clone()).await, 1);
+
+        // Without a level the bank's order decides, even across weeks and
+        // for problems without one.
+        set_priority_level(&pool, 20, PriorityLevel::Normal)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE problems SET week = NULL WHERE id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(next(pool.clone()).await, 1);
+
+        set_problem_week(&pool, 1, 1).await.unwrap();
+        set_problem_week(&pool, 20, 1).await.unwrap();
+        set_priority_level(&pool, 20, PriorityLevel::Normal)
+            .await
+            .unwrap();
+        set_priority_level(&pool, 1, PriorityLevel::Low)
+            .await
+            .unwrap();
+        assert_eq!(next(pool.clone()).await, 20);
+        assert_eq!(
+            fetch_priority_level(&pool, 20).await.unwrap(),
+            PriorityLevel::Normal
+        );
+        assert!(
+            set_priority_level(&pool, 404, PriorityLevel::High)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn filters_next_by_difficulty_and_week() {
+        let pool = seeded_pool().await;
+        Problem {
+            id: 15,
+            order: 3,
+            name: "3Sum".to_string(),
+            difficulty: Some(LeetCodeDifficulty::Medium),
+            week: Some(2),
+            priority: 0,
+            deck: "grind-75".to_string(),
+            url: None,
+        }
+        .insert(&pool)
+        .await
+        .unwrap();
+        let ids = |filter: NextFilter<'static>, limit: u32| {
+            let pool = pool.clone();
+            async move {
+                fetch_unattempted_problems(
+                    &pool,
+                    Some("grind-75"),
+                    filter,
+                    NextStrategy::Order,
+                    limit,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|problem| problem.id)
+                .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(ids(NextFilter::default(), 10).await, [1, 20, 15]);
+        assert_eq!(ids(NextFilter::default(), 2).await, [1, 20]);
+        let medium = NextFilter {
+            difficulty: Some(LeetCodeDifficulty::Medium),
+            ..NextFilter::default()
+        };
+        assert_eq!(ids(medium, 10).await, [15]);
+        let week_one = NextFilter {
+            week: Some(1),
+            ..NextFilter::default()
+        };
+        assert_eq!(ids(week_one, 10).await, [1, 20]);
+        let easy_week_two = NextFilter {
+            difficulty: Some(LeetCodeDifficulty::Easy),
+            week: Some(2),
+            ..NextFilter::default()
+        };
+        assert!(ids(easy_week_two, 10).await.is_empty());
+        assert!(
+            fetch_next_unattempted_problem(
+                &pool,
+                Some("grind-75"),
+                easy_week_two,
+                NextStrategy::Order
+            )
+            .await
+            .unwrap()
+            .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn setting_priority_on_a_missing_problem_fails() {
+        let pool = seeded_pool().await;
+        assert!(set_problem_priority(&pool, 404, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn problem_pages_split_the_full_listing() {
+        let pool = seeded_pool().await;
+        let deck = Some("grind-75");
+        assert_eq!(count_problems(&pool, deck, None).await.unwrap(), 2);
+
+        let first = fetch_problems_page(&pool, deck, None, 1, 0).await.unwrap();
+        let second = fetch_problems_page(&pool, deck, None, 1, 1).await.unwrap();
+        let past_end = fetch_problems_page(&pool, deck, None, 1, 2).await.unwrap();
+        assert_eq!(first[0].id, 1);
+        assert_eq!(second[0].id, 20);
+        assert!(past_end.is_empty());
+        assert_eq!(fetch_all_problems(&pool, deck).await.unwrap().len(), 2);
+        assert_eq!(fetch_all_problems(&pool, None).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn fetch_problem_looks_up_by_id() {
+        let pool = seeded_pool().await;
+        let problem = fetch_problem(&pool, 20).await.unwrap().unwrap();
+        assert_eq!(problem.name, "Valid Parentheses");
+        assert!(fetch_problem(&pool, 404).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn log_attempt_creates_then_updates_progress() {
+        let pool = seeded_pool().await;
+        let first = log_attempt(
+            &pool,
+            1,
+            AttemptRating::Messy,
+            None,
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        let second = log_attempt(
+            &pool,
+            1,
+            AttemptRating::Easy,
+            None,
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        assert!(first);
+        assert!(!second);
+
+        let progress = fetch_progress(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(progress.number_of_attempts, 2);
+        assert_eq!(progress.attempt_rating, AttemptRating::Easy);
+    }
+
+    #[tokio::test]
+    async fn attempts_logged_in_a_rolled_back_transaction_are_discarded() {
+        let pool = seeded_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+        log_attempt(
+            &mut *tx,
+            1,
+            AttemptRating::Hard,
+            None,
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        tx.rollback().await.unwrap();
+
+        assert!(fetch_progress(&pool, 1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn foreign_keys_are_enforced() {
+        let pool = seeded_pool().await;
+        assert!(
+            add_or_replace_progress(&pool, 404, AttemptRating::Easy, None, None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn fresh_database_is_healthy() {
+        let pool = seeded_pool().await;
+        assert_eq!(check_integrity(&pool).await.unwrap(), vec!["ok"]);
+        assert!(fetch_orphan_progress(&pool).await.unwrap().is_empty());
+
+        let applied = fetch_applied_migrations(&pool).await.unwrap();
+        assert_eq!(applied.len(), MIGRATOR.iter().count());
+    }
+
+    #[tokio::test]
+    async fn upgrading_keeps_the_run_of_attempts_made_before_history_was_kept() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let migrations: Vec<_> = MIGRATOR.iter().collect();
+        let (before_history, after) = migrations.split_at(
+            migrations
+                .iter()
+                .position(|m| m.version == 20250904120000)
+                .unwrap(),
+        );
+        for migration in before_history {
+            pool.execute(&*migration.sql).await.unwrap();
+        }
+        pool.execute(
+            "INSERT INTO problems (id, \"order\", name) VALUES (1, 1, 'Two Sum'), (20, 2, 'Valid Parentheses');
+             INSERT INTO progress (problem_id, last_attempted, attempt_rating, number_of_attempts)
+             VALUES (1, '2025-08-20', 'Hard', 4), (20, '2025-08-20', 'ShortFail', 3);",
+        )
+        .await
+        .unwrap();
+        for migration in after {
+            pool.execute(&*migration.sql).await.unwrap();
+        }
+
+        let states: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT problem_id, repetitions FROM progress ORDER BY problem_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(states, vec![(1, 4), (20, 0)]);
+    }
+
+    #[tokio::test]
+    async fn orphaned_progress_is_found_and_deleted() {
+        let pool = seeded_pool().await;
+        add_or_replace_progress(&pool, 20, AttemptRating::Easy, None, None)
+            .await
+            .unwrap();
+        // Bypass the foreign key to simulate a database from before it was enforced.
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM problems WHERE id = 20")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        assert_eq!(fetch_orphan_progress(&pool).await.unwrap(), vec![20]);
+        assert_eq!(delete_orphan_progress(&pool).await.unwrap(), 1);
+        assert!(fetch_orphan_progress(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn next_problem_prefers_due_reviews() {
+        let pool = seeded_pool().await;
+        let today = date("2024-03-10");
+        add_or_replace_progress(
+            &pool,
+            20,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let next = fetch_next_problem(
+            &pool,
+            today,
+            Some("grind-75"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 20);
+
+        // Once reviewed today, problem 20 isn't due again until tomorrow.
+        log_attempt(
+            &pool,
+            20,
+            AttemptRating::Easy,
+            Some(today),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        let next = fetch_next_problem(
+            &pool,
+            today,
+            Some("grind-75"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 1);
+    }
+
+    #[tokio::test]
+    async fn next_problem_puts_failed_reviews_first() {
+        let pool = seeded_pool().await;
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Hard,
+            Some(date("2024-01-01")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        log_attempt(
+            &pool,
+            20,
+            AttemptRating::LongFail,
+            Some(date("2024-03-01")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+
+        // #1 is further overdue, but #20 failed last time.
+        let next = fetch_next_problem(
+            &pool,
+            date("2024-03-10"),
+            None,
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 20);
+    }
+
+    #[tokio::test]
+    async fn decks_scope_problems_and_progress() {
+        let pool = seeded_pool().await;
+        add_or_replace_progress(&pool, 146, AttemptRating::Hard, None, None)
+            .await
+            .unwrap();
+
+        let next = fetch_next_unattempted_problem(
+            &pool,
+            Some("company"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap();
+        assert!(next.is_none());
+        let company = fetch_all_progress(&pool, None, Some("company"))
+            .await
+            .unwrap();
+        assert_eq!(company.len(), 1);
+        let grind = fetch_all_progress(&pool, None, Some("grind-75"))
+            .await
+            .unwrap();
+        assert!(grind.is_empty());
+
+        let decks = fetch_deck_summaries(&pool).await.unwrap();
+        let summary: Vec<(&str, i64, i64)> = decks
+            .iter()
+            .map(|d| (d.deck.as_str(), d.total, d.attempted))
+            .collect();
+        assert_eq!(summary, vec![("company", 1, 1), ("grind-75", 2, 0)]);
+    }
+
+    #[tokio::test]
+    async fn a_problem_can_be_in_several_decks() {
+        let pool = seeded_pool().await;
+        let mut two_sum = fetch_problem(&pool, 1).await.unwrap().unwrap();
+        two_sum.deck = "blind-75".to_string();
+        assert_eq!(
+            two_sum.upsert(&pool).await.unwrap(),
+            UpsertOutcome::Unchanged
+        );
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Easy,
+            Some(date("2024-03-01")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+
+        for deck in ["grind-75", "blind-75"] {
+            let progress = fetch_all_progress(&pool, None, Some(deck)).await.unwrap();
+            assert_eq!(progress.len(), 1, "deck {}", deck);
+        }
+        assert_eq!(
+            fetch_problem_decks(&pool, 1).await.unwrap(),
+            ["blind-75", "grind-75"]
+        );
+
+        // Leaving one deck keeps the problem and its progress for the other.
+        let deleted = remove_from_deck(&pool, "grind-75", &[1], date("2024-03-02"))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 0);
+        assert_eq!(
+            count_problems(&pool, Some("grind-75"), None).await.unwrap(),
+            1
+        );
+        assert!(fetch_progress(&pool, 1).await.unwrap().is_some());
+
+        let archived = remove_from_deck(&pool, "blind-75", &[1], date("2024-03-02"))
+            .await
+            .unwrap();
+        assert_eq!(archived, 1);
+        assert_eq!(
+            fetch_archived_on(&pool, 1).await.unwrap(),
+            Some(date("2024-03-02"))
+        );
+        assert!(fetch_problem_decks(&pool, 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reschedule_rewrites_only_stale_dates() {
+        let pool = seeded_pool().await;
+        add_or_replace_progress(
+            &pool,
+            1,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            None,
+        )
+        .await
+        .unwrap();
+        add_or_replace_progress(
+            &pool,
+            20,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            None,
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE progress SET next_attempt_date = '2030-01-01' WHERE problem_id = 20")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(reschedule_all_progress(&pool).await.unwrap(), 1);
+
+        let fixed = fetch_progress(&pool, 20).await.unwrap().unwrap();
+        let untouched = fetch_progress(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(fixed.next_attempt_date, untouched.next_attempt_date);
+        assert_eq!(reschedule_all_progress(&pool).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn first_attempt_rate_uses_each_problems_first_rating() {
+        let pool = seeded_pool().await;
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Easy,
+            Some(date("2024-03-01")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::ShortFail,
+            Some(date("2024-03-05")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        log_attempt(
+            &pool,
+            20,
+            AttemptRating::Messy,
+            Some(date("2024-03-02")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        log_attempt(
+            &pool,
+            20,
+            AttemptRating::Easy,
+            Some(date("2024-03-06")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        log_attempt(
+            &pool,
+            146,
+            AttemptRating::Easy,
+            Some(date("2024-03-03")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+
+        let rates = fetch_first_attempt_rates(&pool, None, None).await.unwrap();
+
+        assert_eq!(
+            rates,
+            vec![
+                FirstAttemptRate {
+                    difficulty: Some(LeetCodeDifficulty::Easy),
+                    attempted: 2,
+                    easy: 1,
+                },
+                FirstAttemptRate {
+                    difficulty: Some(LeetCodeDifficulty::Medium),
+                    attempted: 1,
+                    easy: 1,
+                },
+            ]
+        );
+        assert_eq!(rates[0].percent(), Some(50.0));
+    }
+
+    #[tokio::test]
+    async fn next_strategies_order_by_difficulty() {
+        let pool = seeded_pool().await;
+        sqlx::query("UPDATE problems SET difficulty = NULL WHERE id = 20")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let pick = |strategy| {
+            let pool = pool.clone();
+            async move {
+                fetch_next_unattempted_problem(&pool, None, NextFilter::default(), strategy)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .id
+            }
+        };
+
+        assert_eq!(pick(NextStrategy::Order).await, 1);
+        assert_eq!(pick(NextStrategy::DifficultyAsc).await, 1);
+        assert_eq!(pick(NextStrategy::DifficultyDesc).await, 146);
+
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Easy,
+            None,
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        log_attempt(
+            &pool,
+            146,
+            AttemptRating::Easy,
+            None,
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        // Only the problem without a difficulty is left.
+        assert_eq!(pick(NextStrategy::DifficultyAsc).await, 20);
+    }
+
+    #[tokio::test]
+    async fn merge_keeps_the_more_practiced_progress_and_copies_the_rest() {
+        // Databases attached to an in-memory connection are in-memory too, so
+        // both sides of the merge need real files.
+        let dir = std::env::temp_dir().join(format!("track-merge-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("other.db");
+        let other = init_pool(&format!("sqlite:{}", path.display()))
+            .await
+            .unwrap();
+        for (id, name) in [(1, "Two Sum"), (999, "Only Elsewhere")] {
+            Problem {
+                id,
+                order: id,
+                name: name.to_string(),
+                difficulty: None,
+                week: None,
+                priority: 0,
+                deck: "default".to_string(),
+                url: None,
+            }
+            .insert(&other)
+            .await
+            .unwrap();
+        }
+        for day in ["2024-03-01", "2024-03-02"] {
+            log_attempt(
+                &other,
+                1,
+                AttemptRating::Easy,
+                Some(date(day)),
+                AttemptDetails::default(),
+            )
+            .await
+            .unwrap();
+        }
+        log_attempt(
+            &other,
+            999,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        other.close().await;
+
+        let pool = init_pool(&format!("sqlite:{}", dir.join("main.db").display()))
+            .await
+            .unwrap();
+        seed(&pool).await;
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Messy,
+            Some(date("2024-03-05")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        log_attempt(
+            &pool,
+            20,
+            AttemptRating::Messy,
+            Some(date("2024-03-05")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+
+        let summary = merge_database(&pool, &path).await.unwrap();
+
+        assert_eq!(
+            summary,
+            MergeSummary {
+                problems_added: 1,
+                problems_matched_by_name: 0,
+                progress_added: 1,
+                progress_updated: 1,
+                history_added: 3,
+                notes_added: 0,
+            }
+        );
+        let merged = fetch_progress(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(merged.number_of_attempts, 2);
+        assert_eq!(merged.attempt_rating, AttemptRating::Easy);
+        let kept = fetch_progress(&pool, 20).await.unwrap().unwrap();
+        assert_eq!(kept.attempt_rating, AttemptRating::Messy);
+
+        pool.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn merge_files_a_problem_stored_under_another_id_by_its_name() {
+        let dir = std::env::temp_dir().join(format!("track-merge-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("other.db");
+        let other = init_pool(&format!("sqlite:{}", path.display()))
+            .await
+            .unwrap();
+        // The other database has LRU Cache under an old id.
+        Problem {
+            id: 147,
+            order: 1,
+            name: "LRU Cache".to_string(),
+            difficulty: None,
+            week: None,
+            priority: 0,
+            deck: "company".to_string(),
+            url: None,
+        }
+        .insert(&other)
+        .await
+        .unwrap();
+        log_attempt(
+            &other,
+            147,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            AttemptDetails {
+                note: Some("doubly linked list".to_string()),
+                ..AttemptDetails::default()
+            },
+        )
+        .await
+        .unwrap();
+        other.close().await;
+
+        let pool = init_pool(&format!("sqlite:{}", dir.join("main.db").display()))
+            .await
+            .unwrap();
+        seed(&pool).await;
+
+        let summary = merge_database(&pool, &path).await.unwrap();
+
+        assert_eq!(summary.problems_added, 0);
+        assert_eq!(summary.problems_matched_by_name, 1);
+        assert_eq!(summary.progress_added, 1);
+        assert_eq!(summary.history_added, 1);
+        assert!(fetch_problem(&pool, 147).await.unwrap().is_none());
+        let progress = fetch_progress(&pool, 146).await.unwrap().unwrap();
+        assert_eq!(progress.attempt_rating, AttemptRating::Hard);
+        let history = fetch_attempt_history(&pool, 146).await.unwrap();
+        assert_eq!(history[0].note.as_deref(), Some("doubly linked list"));
+
+        pool.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn opening_a_database_from_a_newer_build_explains_the_unknown_migration() {
+        let dir = std::env::temp_dir().join(format!("track-newer-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let url = format!("sqlite:{}", dir.join("newer.db").display());
+        let pool = init_pool(&url).await.unwrap();
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (99991231000000, 'from the future', 1, x'00', 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+
+        let message = format!("{:#}", init_pool(&url).await.unwrap_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(message.contains("migration 99991231000000"), "{}", message);
+        assert!(message.contains("newer version"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn mastered_problems_are_never_due() {
+        let pool = seeded_pool().await;
+        assert!(master_problem(&pool, 1).await.is_err());
+
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        master_problem(&pool, 1).await.unwrap();
+
+        let far_future = date("2099-01-01");
+        let next = fetch_next_problem(
+            &pool,
+            far_future,
+            Some("grind-75"),
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(next.id, 20);
+        assert!(
+            fetch_overdue_problems(&pool, far_future, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            fetch_scheduled_reviews(&pool, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(reschedule_all_progress(&pool).await.unwrap(), 0);
+
+        let progress = fetch_all_progress(&pool, None, None).await.unwrap();
+        assert_eq!(progress[0].status(), ProgressStatus::Mastered);
+    }
+
+    #[tokio::test]
+    async fn archived_problems_keep_their_data_but_leave_every_listing() {
+        let pool = seeded_pool().await;
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            AttemptDetails {
+                note: Some("two pointers".to_string()),
+                ..AttemptDetails::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        set_problem_tags(&mut conn, 1, &["array".to_string()])
+            .await
+            .unwrap();
+        drop(conn);
+
+        let archived = remove_from_deck(&pool, "grind-75", &[1, 404], date("2024-03-02"))
+            .await
+            .unwrap();
+        assert_eq!(archived, 1);
+
+        // Nothing about the problem is lost...
+        assert!(fetch_problem(&pool, 1).await.unwrap().is_some());
+        assert!(fetch_progress(&pool, 1).await.unwrap().is_some());
+        assert_eq!(fetch_attempt_history(&pool, 1).await.unwrap().len(), 1);
+        assert_eq!(fetch_problem_tags(&pool, 1).await.unwrap(), ["array"]);
+
+        // ...but it's no longer listed, even without a deck filter.
+        assert_eq!(count_problems(&pool, None, None).await.unwrap(), 2);
+        assert!(
+            fetch_all_progress(&pool, None, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Joining a deck again restores it with its progress.
+        let mut problem = fetch_problem(&pool, 1).await.unwrap().unwrap();
+        problem.upsert(&pool).await.unwrap();
+        assert_eq!(fetch_archived_on(&pool, 1).await.unwrap(), None);
+        assert_eq!(
+            fetch_all_progress(&pool, None, None).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn review_dates_can_be_set_and_cleared_for_attempted_problems() {
+        let pool = seeded_pool().await;
+        assert!(
+            set_next_attempt_date(&pool, 1, Some(date("2024-04-01")))
+                .await
+                .is_err()
+        );
+
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        set_next_attempt_date(&pool, 1, Some(date("2024-04-01")))
+            .await
+            .unwrap();
+        let snoozed = fetch_progress(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(snoozed.next_attempt_date, Some(date("2024-04-01")));
+
+        set_next_attempt_date(&pool, 1, None).await.unwrap();
+        let cleared = fetch_progress(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(cleared.next_attempt_date, None);
+    }
+
+    #[tokio::test]
+    async fn due_includes_today_but_overdue_does_not() {
+        let pool = seeded_pool().await;
+        for (id, day) in [(1, "2024-03-01"), (20, "2024-03-05")] {
+            log_attempt(
+                &pool,
+                id,
+                AttemptRating::ShortFail,
+                Some(date(day)),
+                AttemptDetails::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // ShortFail comes back the next day: #1 on 03-02, #20 on 03-06.
+        let today = date("2024-03-06");
+        let due: Vec<i64> = fetch_due_problems(&pool, today, None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|review| review.problem_id)
+            .collect();
+        assert_eq!(due, vec![1, 20]);
+
+        let overdue = fetch_overdue_problems(&pool, today, None).await.unwrap();
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].problem_id, 1);
+    }
+
+    #[tokio::test]
+    async fn history_keeps_every_attempt() {
+        let pool = seeded_pool().await;
+        for (rating, day) in [
+            (AttemptRating::ShortFail, "2024-03-01"),
+            (AttemptRating::Messy, "2024-03-02"),
+            (AttemptRating::Easy, "2024-03-08"),
+        ] {
+            log_attempt(&pool, 1, rating, Some(date(day)), AttemptDetails::default())
+                .await
+                .unwrap();
+        }
+
+        let history = fetch_attempt_history(&pool, 1).await.unwrap();
+        let ratings: Vec<AttemptRating> = history.iter().map(|entry| entry.rating).collect();
+        assert_eq!(
+            ratings,
+            vec![
+                AttemptRating::ShortFail,
+                AttemptRating::Messy,
+                AttemptRating::Easy
+            ]
+        );
+        assert_eq!(history[2].attempt_number, 3);
+        assert!(fetch_attempt_history(&pool, 20).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn durations_are_logged_per_attempt() {
+        let pool = seeded_pool().await;
+        for minutes in [Some(40), Some(25)] {
+            let details = AttemptDetails {
+                duration_minutes: minutes,
+                ..Default::default()
+            };
+            log_attempt(&pool, 1, AttemptRating::Hard, None, details)
+                .await
+                .unwrap();
+        }
+
+        let history = fetch_attempt_history(&pool, 1).await.unwrap();
+        let minutes: Vec<Option<i64>> = history.iter().map(|e| e.duration_minutes).collect();
+        assert_eq!(minutes, vec![Some(40), Some(25)]);
+
+        let progress = fetch_all_progress(&pool, None, None).await.unwrap();
+        assert_eq!(progress[0].duration_minutes, Some(25));
+    }
+
+    #[tokio::test]
+    async fn problem_notes_can_be_set_replaced_and_cleared() {
+        let pool = seeded_pool().await;
+        let today = date("2024-03-01");
+        assert!(
+            set_problem_note(&pool, 999, Some("nope"), today)
+                .await
+                .is_err()
+        );
+
+        set_problem_note(&pool, 1, Some("hash map of complements"), today)
+            .await
+            .unwrap();
+        set_problem_note(&pool, 1, Some("  one pass is enough "), today)
+            .await
+            .unwrap();
+        assert_eq!(
+            fetch_problem_note(&pool, 1).await.unwrap().as_deref(),
+            Some("one pass is enough")
+        );
+
+        set_problem_note(&pool, 1, Some("   "), today)
+            .await
+            .unwrap();
+        assert_eq!(fetch_problem_note(&pool, 1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn progress_shows_the_problem_note_before_attempt_notes() {
+        let pool = seeded_pool().await;
+        let details = AttemptDetails {
+            note: Some("forgot the empty input".to_string()),
+            ..Default::default()
+        };
+        log_attempt(&pool, 1, AttemptRating::Messy, None, details)
+            .await
+            .unwrap();
+
+        let progress = fetch_all_progress(&pool, None, None).await.unwrap();
+        assert_eq!(progress[0].note.as_deref(), Some("forgot the empty input"));
+
+        set_problem_note(&pool, 1, Some("two pointers"), date("2024-03-01"))
+            .await
+            .unwrap();
+        let progress = fetch_all_progress(&pool, None, None).await.unwrap();
+        assert_eq!(progress[0].note.as_deref(), Some("two pointers"));
+        assert_eq!(
+            fetch_attempt_history(&pool, 1).await.unwrap()[0]
+                .note
+                .as_deref(),
+            Some("forgot the empty input")
+        );
+    }
+
+    #[tokio::test]
+    async fn reinserting_a_problem_only_fills_in_a_missing_url() {
+        let pool = seeded_pool().await;
+        let mut problem = fetch_problem(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(problem.url, None);
+
+        problem.name = "Renamed".to_string();
+        problem.url = Some("https://leetcode.com/problems/two-sum/".to_string());
+        problem.insert(&pool).await.unwrap();
+        problem.url = Some("https://example.com/".to_string());
+        problem.insert(&pool).await.unwrap();
+
+        let stored = fetch_problem(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(stored.name, "Two Sum");
+        assert_eq!(
+            stored.url.as_deref(),
+            Some("https://leetcode.com/problems/two-sum/")
+        );
+    }
+
+    #[tokio::test]
+    async fn resolved_ids_are_cached_by_slug() {
+        let pool = seeded_pool().await;
+        assert!(fetch_resolved_ids(&pool).await.unwrap().is_empty());
+
+        cache_resolved_id(&pool, "two-sum", 2).await.unwrap();
+        cache_resolved_id(&pool, "two-sum", 1).await.unwrap();
+        cache_resolved_id(&pool, "lru-cache", 146).await.unwrap();
+
+        let cached = fetch_resolved_ids(&pool).await.unwrap();
+        assert_eq!(cached.len(), 2);
+        assert_eq!(cached["two-sum"], 1);
+    }
+
+    #[tokio::test]
+    async fn upsert_updates_bank_fields_but_keeps_pins() {
+        let pool = seeded_pool().await;
+        set_problem_priority(&pool, 1, Problem::PINNED_PRIORITY)
+            .await
+            .unwrap();
+        let mut problem = fetch_problem(&pool, 1).await.unwrap().unwrap();
+
+        problem.priority = 0;
+        assert_eq!(
+            problem.upsert(&pool).await.unwrap(),
+            UpsertOutcome::Unchanged
+        );
+
+        problem.week = Some(4);
+        problem.difficulty = Some(LeetCodeDifficulty::Medium);
+        assert_eq!(problem.upsert(&pool).await.unwrap(), UpsertOutcome::Updated);
+
+        problem.id = 2;
+        problem.name = "Add Two Numbers".to_string();
+        assert_eq!(
+            problem.upsert(&pool).await.unwrap(),
+            UpsertOutcome::Inserted
+        );
+
+        let stored = fetch_problem(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(stored.week, Some(4));
+        assert_eq!(stored.difficulty, Some(LeetCodeDifficulty::Medium));
+        assert!(stored.is_pinned());
+    }
+
+    #[tokio::test]
+    async fn upsert_files_a_known_name_under_its_stored_id() {
+        let pool = seeded_pool().await;
+        log_attempt(
+            &pool,
+            20,
+            AttemptRating::Easy,
+            None,
+            AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        // The same problem, resolved to a different id by another source.
+        let mut problem = fetch_problem(&pool, 20).await.unwrap().unwrap();
+        problem.id = 981;
+        problem.week = Some(3);
+
+        let mut tx = pool.begin().await.unwrap();
+        assert_eq!(
+            problem.upsert(&mut *tx).await.unwrap(),
+            UpsertOutcome::Updated
+        );
+        tx.commit().await.unwrap();
+
+        assert_eq!(problem.id, 20);
+        assert!(fetch_problem(&pool, 981).await.unwrap().is_none());
+        assert_eq!(
+            fetch_problem(&pool, 20).await.unwrap().unwrap().week,
+            Some(3)
+        );
+        assert!(fetch_progress(&pool, 20).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn deleting_the_latest_attempt_rewinds_progress() {
+        let pool = seeded_pool().await;
+        assert!(delete_latest_attempt(&pool, None).await.unwrap().is_none());
+
+        for (id, rating, day) in [
+            (1, AttemptRating::Messy, "2024-03-01"),
+            (1, AttemptRating::Easy, "2024-03-05"),
+            (20, AttemptRating::Hard, "2024-03-06"),
+        ] {
+            log_attempt(
+                &pool,
+                id,
+                rating,
+                Some(date(day)),
+                AttemptDetails::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // Without an id, the newest attempt overall goes, here the only one at #20.
+        let (problem_id, removed) = delete_latest_attempt(&pool, None).await.unwrap().unwrap();
+        assert_eq!((problem_id, removed.rating), (20, AttemptRating::Hard));
+        assert!(fetch_progress(&pool, 20).await.unwrap().is_none());
+
+        let (_, removed) = delete_latest_attempt(&pool, Some(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(removed.rating, AttemptRating::Easy);
+        let progress = fetch_progress(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(progress.attempt_rating, AttemptRating::Messy);
+        assert_eq!(progress.number_of_attempts, 1);
+        assert_eq!(progress.last_attempted, date("2024-03-01"));
+        assert_eq!(
+            progress.next_attempt_date,
+            preview_next_attempt_date(AttemptRating::Messy, ReviewState::NEW, date("2024-03-01"))
+        );
+    }
+
+    #[tokio::test]
+    async fn difficulty_breakdown_counts_latest_ratings_and_attempts() {
+        let pool = seeded_pool().await;
+        for (id, rating) in [
+            (1, AttemptRating::Messy),
+            (1, AttemptRating::Easy),
+            (20, AttemptRating::ShortFail),
+            (146, AttemptRating::Hard),
+        ] {
+            log_attempt(&pool, id, rating, None, AttemptDetails::default())
+                .await
+                .unwrap();
+        }
+
+        let breakdown = fetch_difficulty_breakdown(&pool, None).await.unwrap();
+
+        assert_eq!(breakdown.len(), 2);
+        let easy = &breakdown[0];
+        assert_eq!(easy.difficulty, Some(LeetCodeDifficulty::Easy));
+        assert_eq!((easy.attempted, easy.attempts), (2, 3));
+        assert_eq!((easy.easy, easy.short_fail, easy.messy), (1, 1, 0));
+        assert_eq!(easy.average_attempts(), Some(1.5));
+        assert_eq!(breakdown[1].difficulty, Some(LeetCodeDifficulty::Medium));
+        assert_eq!(breakdown[1].hard, 1);
+    }
+
+    #[tokio::test]
+    async fn resolves_problems_by_name_slug_or_prefix() {
+        let pool = seeded_pool().await;
+        let by = |s: &str| s.parse::<ProblemRef>().unwrap();
+
+        for (text, id) in [
+            ("20", 20),
+            ("valid parentheses", 20),
+            ("Valid Paren", 20),
+            ("lru-cache", 146),
+            ("https://leetcode.com/problems/two-sum/description/", 1),
+        ] {
+            assert_eq!(
+                resolve_problem(&pool, &by(text)).await.unwrap(),
+                id,
+                "{}",
+                text
+            );
+        }
+
+        assert!(
+            resolve_problem(&pool, &by("no such problem"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn ambiguous_prefixes_list_the_candidates() {
+        let pool = seeded_pool().await;
+        let mut two = fetch_problem(&pool, 20).await.unwrap().unwrap();
+        two.id = 125;
+        two.name = "Valid Palindrome".to_string();
+        two.insert(&pool).await.unwrap();
+
+        let err = resolve_problem(&pool, &"valid".parse().unwrap())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("#20 Valid Parentheses"), "{}", err);
+        assert!(err.contains("#125 Valid Palindrome"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn tags_filter_problems_and_break_down_ratings() {
+        let pool = seeded_pool().await;
+        let mut conn = pool.acquire().await.unwrap();
+        set_problem_tags(&mut conn, 1, &["Array".to_string(), " hash ".to_string()])
+            .await
+            .unwrap();
+        set_problem_tags(&mut conn, 146, &["hash".to_string()])
+            .await
+            .unwrap();
+        drop(conn);
+        add_or_replace_progress(&pool, 146, AttemptRating::Messy, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fetch_problem_tags(&pool, 1).await.unwrap(),
+            ["array", "hash"]
+        );
+        assert_eq!(count_problems(&pool, None, Some("hash")).await.unwrap(), 2);
+        let page = fetch_problems_page(&pool, None, Some("array"), -1, 0)
+            .await
+            .unwrap();
+        assert_eq!(page.iter().map(|p| p.id).collect::<Vec<_>>(), [1]);
+        let next = fetch_next_unattempted_problem(
+            &pool,
+            None,
+            NextFilter::tagged(Some("hash")),
+            NextStrategy::Order,
+        )
+        .await
+        .unwrap();
+        assert_eq!(next.map(|p| p.id), Some(1));
+
+        let breakdown = fetch_tag_breakdown(&pool, None).await.unwrap();
+        let summary: Vec<(&str, i64, i64, i64)> = breakdown
+            .iter()
+            .map(|row| (row.tag.as_str(), row.total, row.attempted, row.messy))
+            .collect();
+        assert_eq!(summary, [("array", 1, 0, 0), ("hash", 2, 1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn weakest_tag_picks_from_the_worst_topic() {
+        let pool = seeded_pool().await;
+        let mut conn = pool.acquire().await.unwrap();
+        set_problem_tags(&mut conn, 1, &["array".to_string()])
+            .await
+            .unwrap();
+        set_problem_tags(&mut conn, 20, &["stack".to_string()])
+            .await
+            .unwrap();
+        set_problem_tags(&mut conn, 146, &["stack".to_string(), "design".to_string()])
+            .await
+            .unwrap();
+        drop(conn);
+        let today = date("2024-03-01");
+        assert!(
+            fetch_weakest_tag_problem(&pool, today, None, NextStrategy::Order)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // LRU Cache failed, so its stack and design tags are worst. Design has
+        // nothing else left to offer once LRU Cache isn't due; stack still has
+        // Valid Parentheses.
+        for (id, rating) in [(1, AttemptRating::Hard), (146, AttemptRating::ShortFail)] {
+            log_attempt(&pool, id, rating, Some(today), AttemptDetails::default())
+                .await
+                .unwrap();
+        }
+
+        let (tag, problem) = fetch_weakest_tag_problem(&pool, today, None, NextStrategy::Order)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((tag.tag.as_str(), problem.id), ("stack", 20));
+        assert_eq!(tag.struggle_rate(), Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn next_unattempted_by_week_skips_attempted_problems() {
+        let pool = seeded_pool().await;
+        add_or_replace_progress(&pool, 1, AttemptRating::Easy, None, None)
+            .await
+            .unwrap();
+
+        let next = fetch_next_unattempted_by_week(&pool, Some("grind-75"))
+            .await
+            .unwrap();
+        assert_eq!(next.iter().map(|p| p.id).collect::<Vec<_>>(), [20]);
+    }
+
+    #[tokio::test]
+    async fn snapshots_round_trip_through_json() {
+        let pool = seeded_pool().await;
+        log_attempt(
+            &pool,
+            1,
+            AttemptRating::Hard,
+            Some(date("2024-03-01")),
+            AttemptDetails {
+                duration_minutes: Some(25),
+                ..AttemptDetails::default()
+            },
+        )
+        .await
+        .unwrap();
+        set_problem_note(&pool, 1, Some("hash map"), date("2024-03-01"))
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        set_problem_tags(&mut conn, 1, &["array".to_string()])
+            .await
+            .unwrap();
+        drop(conn);
+
+        let snapshot = fetch_snapshot(&pool, date("2024-03-02")).await.unwrap();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot = Snapshot::from_json(&json).unwrap();
+
+        // Like `track merge`, importing attaches a file, which needs a
+        // file-backed database to attach to.
+        let dir = std::env::temp_dir().join(format!("track-snapshot-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let copy = init_pool(&format!("sqlite:{}", dir.join("copy.db").display()))
+            .await
+            .unwrap();
+        let summary = import_snapshot(&copy, &snapshot).await.unwrap();
+        assert_eq!(
+            summary,
+            MergeSummary {
+                problems_added: 3,
+                problems_matched_by_name: 0,
+                progress_added: 1,
+                progress_updated: 0,
+                history_added: 1,
+                notes_added: 1,
+            }
+        );
+        assert_eq!(fetch_problem_tags(&copy, 1).await.unwrap(), ["array"]);
+        assert_eq!(
+            count_problems(&copy, Some("company"), None).await.unwrap(),
+            1
+        );
+        let history = fetch_attempt_history(&copy, 1).await.unwrap();
+        assert_eq!(history[0].duration_minutes, Some(25));
+
+        // Importing the same snapshot again changes nothing.
+        let again = import_snapshot(&copy, &snapshot).await.unwrap();
+        assert_eq!(again.problems_added + again.history_added, 0);
+
+        copy.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_snapshots_from_newer_versions() {
+        let json = r#"{"version": 99, "exported_on": "2024-03-02", "problems": [], "progress": [], "history": [], "notes": []}"#;
+        assert!(Snapshot::from_json(json).is_err());
+    }
+
+    #[tokio::test]
+    async fn reviews_due_within_a_window_include_overdue_ones() {
+        let pool = seeded_pool().await;
+        for id in [1, 20, 146] {
+            log_attempt(
+                &pool,
+                id,
+                AttemptRating::Hard,
+                Some(date("2024-03-01")),
+                AttemptDetails::default(),
+            )
+            .await
+            .unwrap();
+        }
+        for (id, day) in [(1, "2024-02-20"), (20, "2024-03-10"), (146, "2024-05-01")] {
+            sqlx::query("UPDATE progress SET next_attempt_date = ? WHERE problem_id = ?")
+                .bind(date(day))
+                .bind(id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let ids = |reviews: Vec<ScheduledReview>| -> Vec<i64> {
+            reviews.iter().map(|r| r.problem_id).collect()
+        };
+        let today = date("2024-03-01");
+        assert_eq!(
+            ids(fetch_reviews_due_within(&pool, today, 9, None)
+                .await
+                .unwrap()),
+            [1, 20]
+        );
+        assert_eq!(
+            ids(fetch_reviews_due_within(&pool, today, 0, None)
+                .await
+                .unwrap()),
+            ids(fetch_due_problems(&pool, today, None).await.unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn saved_metadata_survives_a_bank_without_difficulty() {
+        let pool = seeded_pool().await;
+        let mut conn = pool.acquire().await.unwrap();
+        set_problem_tags(&mut conn, 1, &["from-bank".to_string()])
+            .await
+            .unwrap();
+        let meta = ProblemMeta {
+            question_id: 1,
+            title: "Two Sum".to_string(),
+            difficulty: LeetCodeDifficulty::Medium,
+            ac_rate: Some(55.2),
+            topic_tags: vec!["array".to_string(), "hash-table".to_string()],
+        };
+        save_problem_meta(&mut conn, 1, &meta, date("2024-03-01"))
+            .await
+            .unwrap();
+        drop(conn);
+
+        let stored = fetch_problem_meta(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(stored.acceptance_rate, Some(55.2));
+        assert_eq!(
+            fetch_problem_tags(&pool, 1).await.unwrap(),
+            ["array", "from-bank", "hash-table"]
+        );
+
+        // Rebuilding from a bank that leaves the difficulty out keeps it.
+        let mut rebuilt = fetch_problem(&pool, 1).await.unwrap().unwrap();
+        rebuilt.difficulty = None;
+        assert_eq!(
+            rebuilt.upsert(&pool).await.unwrap(),
+            UpsertOutcome::Unchanged
+        );
+        let stored = fetch_problem(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(stored.difficulty, Some(LeetCodeDifficulty::Medium));
+        assert_eq!(
+            remove_from_deck(&pool, "grind-75", &[1], date("2024-03-02"))
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn daily_challenges_join_the_end_of_the_deck() {
+        let pool = seeded_pool().await;
+        let daily = |id: i64, title: &str| DailyChallenge {
+            date: date("2024-03-01"),
+            title_slug: title.to_lowercase().replace(' ', "-"),
+            meta: ProblemMeta {
+                question_id: id,
+                title: title.to_string(),
+                difficulty: LeetCodeDifficulty::Hard,
+                ac_rate: None,
+                topic_tags: vec![],
+            },
+        };
+
+        assert!(
+            add_daily_problem(
+                &pool,
+                &daily(42, "Trapping Rain Water"),
+                "daily",
+                date("2024-03-01")
+            )
+            .await
+            .unwrap()
+        );
+        assert!(
+            !add_daily_problem(&pool, &daily(1, "Two Sum"), "daily", date("2024-03-02"))
+                .await
+                .unwrap()
+        );
+
+        let problems = fetch_all_problems(&pool, Some("daily")).await.unwrap();
+        let mut ids: Vec<(i64, i64)> = problems.iter().map(|p| (p.id, p.order)).collect();
+        ids.sort();
+        assert_eq!(ids, [(1, 1), (42, 1)]);
+        // Two Sum keeps its place in its own deck.
+        assert_eq!(
+            count_problems(&pool, Some("grind-75"), None).await.unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn smoothing_counts_other_problems_reviews() {
+        let pool = seeded_pool().await;
+        for id in [1, 20] {
+            log_attempt(
+                &pool,
+                id,
+                AttemptRating::Easy,
+                Some(date("2024-03-01")),
+                AttemptDetails::default(),
+            )
+            .await
+            .unwrap();
+            sqlx::query(
+                "UPDATE progress SET next_attempt_date = '2024-03-13' WHERE problem_id = ?",
+            )
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let schedule = Schedule {
+            daily_review_cap: Some(2),
+            ..Schedule::default()
+        };
+        let mut conn = pool.acquire().await.unwrap();
+
+        // Problem 1's own review doesn't count against the day.
+        let (attempted, due) = (date("2024-03-01"), date("2024-03-13"));
+        let kept = smoothed_review_date(&mut conn, &schedule, 1, attempted, due).await;
+        assert_eq!(kept.unwrap(), due);
+        let moved = smoothed_review_date(&mut conn, &schedule, 146, attempted, due).await;
+        assert_eq!(moved.unwrap(), date("2024-03-14"));
+    }
+
+    #[tokio::test]
+    async fn leeches_are_suspended_until_unsuspended() {
+        let pool = seeded_pool().await;
+        let today = date("2024-03-10");
+        for day in ["2024-03-01", "2024-03-02", "2024-03-03", "2024-03-04"] {
+            log_attempt(
+                &pool,
+                1,
+                AttemptRating::ShortFail,
+                Some(date(day)),
+                AttemptDetails::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let leeches = fetch_leeches(&pool, None).await.unwrap();
+        assert_eq!(leeches.len(), 1);
+        assert_eq!((leeches[0].failures, leeches[0].suspended), (4, true));
+        let next = fetch_next_problem(
+            &pool,
+            today,
+            None,
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await;
+        assert_eq!(next.unwrap().unwrap().id, 146);
+
+        set_suspended(&pool, 1, false, today).await.unwrap();
+        assert!(!is_suspended(&pool, 1).await.unwrap());
+        let next = fetch_next_problem(
+            &pool,
+            today,
+            None,
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await;
+        assert_eq!(next.unwrap().unwrap().id, 1);
+
+        set_suspended(&pool, 20, true, today).await.unwrap();
+        let new =
+            fetch_unattempted_problems(&pool, None, NextFilter::default(), NextStrategy::Order, 5)
+                .await;
+        assert_eq!(new.unwrap().iter().map(|p| p.id).collect::<Vec<_>>(), [146]);
+        assert!(set_suspended(&pool, 999, true, today).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn only_one_session_runs_at_a_time() {
+        let pool = seeded_pool().await;
+        let at = |time: &str| {
+            NaiveDateTime::parse_from_str(&format!("2024-03-01 {}", time), "%Y-%m-%d %H:%M:%S")
+                .unwrap()
+        };
+
+        let session = start_session(&pool, 1, at("09:00:00")).await.unwrap();
+        assert_eq!(fetch_open_session(&pool).await.unwrap(), Some(session));
+        assert!(start_session(&pool, 20, at("09:05:00")).await.is_err());
+        assert!(start_session(&pool, 404, at("09:05:00")).await.is_err());
+
+        let mut conn = pool.acquire().await.unwrap();
+        let stopped = stop_session(&mut conn, at("09:24:40"), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stopped.stopped_at, Some(at("09:24:40")));
+        assert_eq!(stopped.minutes(at("09:24:40")), 25);
+        assert_eq!(stopped.minutes(at("09:00:10")), 1);
+        assert!(
+            stop_session(&mut conn, at("09:30:00"), None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        drop(conn);
+        assert!(start_session(&pool, 20, at("10:00:00")).await.is_ok());
+
+        // Breaks taken out of a session don't count towards its minutes.
+        let mut conn = pool.acquire().await.unwrap();
+        let stopped = stop_session(&mut conn, at("10:55:00"), Some(50 * 60))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stopped.minutes(at("10:55:00")), 50);
+    }
 }
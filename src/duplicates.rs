@@ -0,0 +1,174 @@
+// src/duplicates.rs
+
+/// What to compare when looking for duplicate problems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DuplicateKey {
+    /// Entries that share a LeetCode id.
+    Id,
+    /// Entries with different ids whose names are nearly the same.
+    Name,
+}
+
+/// A problem as seen by the duplicate finder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// The LeetCode ID.
+    pub id: i64,
+    /// Where the entry sits in its bank.
+    pub order: i64,
+    /// The problem's name.
+    pub name: String,
+}
+
+/// Groups entries that share an id. Only groups of two or more are returned,
+/// in order of their first entry.
+pub fn find_id_duplicates(candidates: &[Candidate]) -> Vec<Vec<Candidate>> {
+    let mut groups: Vec<Vec<Candidate>> = Vec::new();
+    let mut index_by_id: HashMap<i64, usize> = HashMap::new();
+    for candidate in candidates {
+        match index_by_id.get(&candidate.id) {
+            Some(&index) => groups[index].push(candidate.clone()),
+            None => {
+                index_by_id.insert(candidate.id, groups.len());
+                groups.push(vec![candidate.clone()]);
+            }
+        }
+    }
+
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// Clusters entries with different ids whose names look like the same problem.
+///
+/// Names are compared case-insensitively with punctuation and spacing
+/// dropped, and count as the same if they're within a small edit distance
+/// (one edit per ten characters, and at least one). Clusters are transitive:
+/// if A matches B and B matches C, all three are reported together.
+pub fn find_name_duplicates(candidates: &[Candidate]) -> Vec<Vec<Candidate>> {
+    let normalized: Vec<String> = candidates.iter().map(|c| normalize(&c.name)).collect();
+
+    // Union-find over candidate indices.
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for a in 0..candidates.len() {
+        for b in (a + 1)..candidates.len() {
+            if candidates[a].id == candidates[b].id {
+                continue;
+            }
+            let longest = normalized[a]
+                .chars()
+                .count()
+                .max(normalized[b].chars().count());
+            let allowed = (longest / 10).max(1);
+            if edit_distance(&normalized[a], &normalized[b]) <= allowed {
+                let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+                parent[rb] = ra;
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<Candidate>> = Vec::new();
+    let mut index_by_root: HashMap<usize, usize> = HashMap::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        let r = root(&mut parent, i);
+        match index_by_root.get(&r) {
+            Some(&index) => clusters[index].push(candidate.clone()),
+            None => {
+                index_by_root.insert(r, clusters.len());
+                clusters.push(vec![candidate.clone()]);
+            }
+        }
+    }
+
+    clusters.retain(|cluster| cluster.len() > 1);
+    clusters
+}
+
+/// Lowercases a name and keeps only its letters and digits.
+pub(crate) fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// The Levenshtein distance between two strings, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: i64, order: i64, name: &str) -> Candidate {
+        Candidate {
+            id,
+            order,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn groups_entries_sharing_an_id() {
+        let candidates = vec![
+            candidate(1, 1, "Two Sum"),
+            candidate(20, 2, "Valid Parentheses"),
+            candidate(1, 9, "Two Sum"),
+        ];
+
+        let groups = find_id_duplicates(&candidates);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            groups[0].iter().map(|c| c.order).collect::<Vec<_>>(),
+            vec![1, 9]
+        );
+    }
+
+    #[test]
+    fn clusters_near_identical_names_across_ids() {
+        let candidates = vec![
+            candidate(1, 1, "Two Sum"),
+            candidate(1001, 2, "two-sum"),
+            candidate(20, 3, "Valid Parentheses"),
+            candidate(2020, 4, "Valid Parenthesis"),
+            candidate(21, 5, "Merge Two Sorted Lists"),
+        ];
+
+        let clusters = find_name_duplicates(&candidates);
+
+        let ids: Vec<Vec<i64>> = clusters
+            .iter()
+            .map(|cluster| cluster.iter().map(|c| c.id).collect())
+            .collect();
+        assert_eq!(ids, vec![vec![1, 1001], vec![20, 2020]]);
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
@@ -0,0 +1,154 @@
+// src/export.rs
+
+/// The file formats `track export` can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Comma-separated values, for spreadsheets.
+    #[default]
+    Csv,
+}
+
+/// The header of `render_problems_csv`.
+const PROBLEM_COLUMNS: [&str; 12] = [
+    "id",
+    "name",
+    "difficulty",
+    "week",
+    "deck",
+    "url",
+    "attempts",
+    "last_attempted",
+    "rating",
+    "next_attempt_date",
+    "mastered",
+    "status",
+];
+
+/// Renders one CSV row per problem with its progress. Problems that haven't
+/// been attempted have empty progress columns.
+pub fn render_problems_csv(problems: &[Problem], progress: &[ProgressView]) -> String {
+    let by_id: HashMap<i64, &ProgressView> = progress.iter().map(|p| (p.problem_id, p)).collect();
+
+    let mut csv = csv_row(&PROBLEM_COLUMNS);
+    for problem in problems {
+        let mut fields = vec![
+            problem.id.to_string(),
+            problem.name.clone(),
+            optional(problem.difficulty.map(|d| format!("{:?}", d))),
+            optional(problem.week),
+            problem.deck.clone(),
+            problem.link(),
+        ];
+        match by_id.get(&problem.id) {
+            Some(item) => fields.extend([
+                item.number_of_attempts.to_string(),
+                item.last_attempted.to_string(),
+                item.attempt_rating.to_string(),
+                optional(item.next_attempt_date),
+                item.mastered.to_string(),
+                item.status().to_string(),
+            ]),
+            None => {
+                fields.push("0".to_string());
+                fields.resize(PROBLEM_COLUMNS.len(), String::new());
+            }
+        }
+        csv.push_str(&csv_row(&fields));
+    }
+    csv
+}
+
+/// Renders one CSV row per logged attempt, oldest first.
+pub fn render_history_csv(attempts: &[AttemptRecord]) -> String {
+    let mut csv = csv_row(&[
+        "problem_id",
+        "name",
+        "difficulty",
+        "attempt_number",
+        "attempted_on",
+        "rating",
+        "language",
+        "duration_minutes",
+        "note",
+    ]);
+    for attempt in attempts {
+        csv.push_str(&csv_row(&[
+            attempt.problem_id.to_string(),
+            attempt.name.clone(),
+            optional(attempt.difficulty.map(|d| format!("{:?}", d))),
+            attempt.attempt_number.to_string(),
+            attempt.attempted_on.to_string(),
+            attempt.rating.to_string(),
+            optional(attempt.language.as_deref()),
+            optional(attempt.duration_minutes),
+            optional(attempt.note.as_deref()),
+        ]));
+    }
+    csv
+}
+
+/// An optional value as a CSV field, empty when missing.
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Joins fields into one CSV line, ending in CRLF as RFC 4180 asks.
+fn csv_row(fields: &[impl AsRef<str>]) -> String {
+    let quoted: Vec<String> = fields.iter().map(|f| quote(f.as_ref())).collect();
+    format!("{}\r\n", quoted.join(","))
+}
+
+/// Quotes a field if it holds a comma, quote or line break, doubling any
+/// quotes inside it.
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+use crate::db::{AttemptRecord, ProgressView};
+use crate::problems::Problem;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem_attempts::AttemptRating;
+    use crate::problems::LeetCodeDifficulty;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn quotes_fields_that_need_it() {
+        assert_eq!(quote("Two Sum"), "Two Sum");
+        assert_eq!(quote("Add, Search"), "\"Add, Search\"");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn history_has_a_header_and_a_row_per_attempt() {
+        let attempts = [AttemptRecord {
+            problem_id: 1,
+            name: "Two Sum".to_string(),
+            difficulty: Some(LeetCodeDifficulty::Easy),
+            attempt_number: 1,
+            attempted_on: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            rating: AttemptRating::Easy,
+            language: None,
+            duration_minutes: Some(12),
+            note: Some("hash map, one pass".to_string()),
+        }];
+
+        let csv = render_history_csv(&attempts);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert!(lines[0].starts_with("problem_id,name,difficulty,"));
+        assert_eq!(
+            lines[1],
+            "1,Two Sum,Easy,1,2024-03-01,Easy,,12,\"hash map, one pass\""
+        );
+        assert_eq!(lines[2], "");
+    }
+}
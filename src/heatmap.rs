@@ -0,0 +1,100 @@
+// src/heatmap.rs
+
+/// The shade used for a day, indexed by its attempt count (capped at 4).
+pub const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Plain-ASCII stand-ins for `SHADES`, for terminals and fonts without the
+/// block characters.
+pub const ASCII_SHADES: [char; 5] = ['.', ':', '+', '*', '#'];
+
+/// Renders a GitHub-style activity grid of the last `weeks` weeks.
+///
+/// Each column is a Monday-to-Sunday week, ending with the week containing
+/// `today`, and each row is a weekday. A cell's shade reflects how many
+/// attempts fall on that day, drawn from `shades`. Days after `today` are
+/// left blank.
+pub fn render_heatmap(
+    dates: &[NaiveDate],
+    today: NaiveDate,
+    weeks: u32,
+    shades: &[char; 5],
+) -> String {
+    let weeks = weeks.max(1) as i64;
+
+    let mut counts: HashMap<NaiveDate, usize> = HashMap::new();
+    for date in dates {
+        *counts.entry(*date).or_insert(0) += 1;
+    }
+
+    let this_monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let first_monday = this_monday - Duration::weeks(weeks - 1);
+
+    let mut grid = String::new();
+    for (row, label) in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        .iter()
+        .enumerate()
+    {
+        grid.push_str(label);
+        grid.push(' ');
+        for week in 0..weeks {
+            let day = first_monday + Duration::weeks(week) + Duration::days(row as i64);
+            let cell = if day > today {
+                ' '
+            } else {
+                let count = counts.get(&day).copied().unwrap_or(0);
+                shades[count.min(shades.len() - 1)]
+            };
+            grid.push(cell);
+        }
+        grid.push('\n');
+    }
+
+    grid.push_str(&format!(
+        "\n{} to {}   Less {} More\n",
+        first_monday,
+        today,
+        shades.iter().collect::<String>()
+    ));
+    grid
+}
+
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn shades_days_by_attempt_count() {
+        // 2024-03-13 is a Wednesday.
+        let today = date("2024-03-13");
+        let dates = [
+            date("2024-03-04"),
+            date("2024-03-11"),
+            date("2024-03-11"),
+            date("2024-03-12"),
+        ];
+
+        let heatmap = render_heatmap(&dates, today, 2, &SHADES);
+        let rows: Vec<&str> = heatmap.lines().collect();
+
+        assert_eq!(rows[0], "Mon ░▒");
+        assert_eq!(rows[1], "Tue ·░");
+        assert_eq!(rows[2], "Wed ··");
+        // Thursday of the current week hasn't happened yet.
+        assert_eq!(rows[3], "Thu · ");
+    }
+
+    #[test]
+    fn ascii_shades_stay_ascii() {
+        let today = date("2024-03-13");
+        let heatmap = render_heatmap(&[today, today], today, 4, &ASCII_SHADES);
+        assert!(heatmap.is_ascii());
+        assert_eq!(heatmap.lines().nth(2), Some("Wed ...+"));
+    }
+}
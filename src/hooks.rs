@@ -0,0 +1,271 @@
+// src/hooks.rs
+
+/// Commands to run when things happen, from the `[hooks]` table of
+/// `track.toml`.
+///
+/// Each command runs through the shell with the event as a JSON object on
+/// stdin. `TRACK_EVENT` names the event, and each of the object's top-level
+/// strings, numbers and booleans is also set as `TRACK_<FIELD>`, e.g.
+/// `TRACK_PROBLEM_ID`. What the command prints goes to stderr, so it never
+/// mixes with track's results or, under `track mcp`, its protocol.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Run after an attempt is logged, whichever way it's logged. Attempts
+    /// logged in bulk run it once per problem, after they're all saved.
+    pub on_attempt: Option<String>,
+    /// Run after `track --build` builds a bank.
+    pub on_build: Option<String>,
+    /// Run when `track due` or `track notify` finds reviews due.
+    pub on_due: Option<String>,
+}
+
+/// Something a hook can run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// An attempt was logged.
+    Attempt,
+    /// A bank was built.
+    Build,
+    /// Reviews were found due.
+    Due,
+}
+
+impl HookEvent {
+    /// The event's name, as set in `TRACK_EVENT`.
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::Attempt => "attempt",
+            HookEvent::Build => "build",
+            HookEvent::Due => "due",
+        }
+    }
+}
+
+impl Hooks {
+    /// The command configured for `event`, if any.
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        let command = match event {
+            HookEvent::Attempt => &self.on_attempt,
+            HookEvent::Build => &self.on_build,
+            HookEvent::Due => &self.on_due,
+        };
+        command
+            .as_deref()
+            .filter(|command| !command.trim().is_empty())
+    }
+
+    /// Runs the hook for `event` with `payload`, waiting for it to finish.
+    /// Does nothing if no hook is configured.
+    ///
+    /// # Errors
+    /// Returns an error if the command can't be started or exits with a
+    /// failure.
+    pub async fn run(
+        &self,
+        event: HookEvent,
+        payload: &impl serde::Serialize,
+    ) -> anyhow::Result<()> {
+        let Some(command) = self.command(event) else {
+            return Ok(());
+        };
+        let payload = serde_json::to_value(payload)?;
+
+        let mut shell = if cfg!(windows) {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+        let mut child = shell
+            .arg(command)
+            .envs(hook_env(event, &payload))
+            .stdin(Stdio::piped())
+            .stdout(std::io::stderr())
+            .spawn()
+            .with_context(|| format!("Failed to run the on_{} hook '{}'", event.name(), command))?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let mut json = serde_json::to_string(&payload)?;
+        json.push('\n');
+        // A hook that doesn't read its stdin closes it early; that's fine.
+        let _ = stdin.write_all(json.as_bytes()).await;
+        drop(stdin);
+
+        let status = child.wait().await?;
+        anyhow::ensure!(
+            status.success(),
+            "The on_{} hook '{}' failed ({}).",
+            event.name(),
+            command,
+            status
+        );
+        Ok(())
+    }
+}
+
+/// The environment a hook for `event` gets: `TRACK_EVENT`, plus `TRACK_<FIELD>`
+/// for each of `payload`'s top-level strings, numbers and booleans.
+pub fn hook_env(event: HookEvent, payload: &Value) -> Vec<(String, String)> {
+    let mut env = vec![("TRACK_EVENT".to_string(), event.name().to_string())];
+    if let Value::Object(fields) = payload {
+        for (name, value) in fields {
+            let value = match value {
+                Value::String(text) => text.clone(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                _ => continue,
+            };
+            env.push((format!("TRACK_{}", name.to_uppercase()), value));
+        }
+    }
+    env
+}
+
+/// What `on_attempt` hooks are sent: the attempt, the problem's progress after
+/// it, and overall counts.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AttemptEvent {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The problem's name.
+    pub name: String,
+    /// The problem's difficulty, if the bank gave one.
+    pub difficulty: Option<LeetCodeDifficulty>,
+    /// The attempt's rating.
+    pub rating: AttemptRating,
+    /// The rating's score, from 1 (ShortFail) to 5 (Easy).
+    pub score: u8,
+    /// The day of the attempt.
+    pub attempted_on: NaiveDate,
+    /// How many times the problem has been attempted, this one included.
+    pub number_of_attempts: i64,
+    /// When the problem comes back for review, if it's scheduled.
+    pub next_attempt_date: Option<NaiveDate>,
+    /// How many problems have been attempted in all.
+    pub problems_attempted: usize,
+    /// The current daily streak.
+    pub current_streak: u32,
+    /// The longest daily streak.
+    pub longest_streak: u32,
+}
+
+impl AttemptEvent {
+    /// Builds the event for the attempt just logged at `problem_id`.
+    pub async fn fetch(
+        pool: &SqlitePool,
+        problem_id: i64,
+        today: NaiveDate,
+    ) -> anyhow::Result<Self> {
+        let problem = fetch_problem(pool, problem_id).await?.with_context(|| {
+            format!("No problem with id {} exists in the database.", problem_id)
+        })?;
+        let progress = fetch_progress(pool, problem_id)
+            .await?
+            .with_context(|| format!("Problem {} has no progress yet.", problem_id))?;
+        let problems_attempted = fetch_all_progress(pool, None, None).await?.len();
+        let streaks = compute_streaks(&fetch_attempt_dates(pool).await?, today);
+
+        Ok(AttemptEvent {
+            problem_id,
+            name: problem.name,
+            difficulty: problem.difficulty,
+            rating: progress.attempt_rating,
+            score: progress.attempt_rating.score(),
+            attempted_on: progress.last_attempted,
+            number_of_attempts: progress.number_of_attempts,
+            next_attempt_date: progress.next_attempt_date,
+            problems_attempted,
+            current_streak: streaks.current,
+            longest_streak: streaks.longest,
+        })
+    }
+}
+
+/// Tells the on_attempt hook and the webhook at `webhook_url` about the
+/// attempt just logged at `problem_id`. Every way of logging an attempt
+/// calls this once the attempt is saved. Since it's saved either way,
+/// failures only get a warning on stderr.
+pub async fn announce_attempt(
+    hooks: &Hooks,
+    webhook_url: Option<&str>,
+    pool: &SqlitePool,
+    problem_id: i64,
+) {
+    let webhook_url = webhook_url.filter(|url| !url.trim().is_empty());
+    if hooks.command(HookEvent::Attempt).is_none() && webhook_url.is_none() {
+        return;
+    }
+    let event = match AttemptEvent::fetch(pool, problem_id, Local::now().date_naive()).await {
+        Ok(event) => event,
+        Err(e) => {
+            eprintln!("Warning: {:#}", e);
+            return;
+        }
+    };
+    if let Err(e) = hooks.run(HookEvent::Attempt, &event).await {
+        eprintln!("Warning: {:#}", e);
+    }
+    if let Some(url) = webhook_url
+        && let Err(e) = post_webhook(url, &WebhookPayload::new(&event)).await
+    {
+        eprintln!("Warning: {:#}", e);
+    }
+}
+
+use crate::db::{fetch_all_progress, fetch_attempt_dates, fetch_problem, fetch_progress};
+use crate::problem_attempts::AttemptRating;
+use crate::problems::LeetCodeDifficulty;
+use crate::streaks::compute_streaks;
+use crate::webhook::{WebhookPayload, post_webhook};
+use anyhow::Context;
+use chrono::{Local, NaiveDate};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_an_env_var_per_scalar_field() {
+        let payload = serde_json::json!({
+            "problem_id": 1,
+            "name": "Two Sum",
+            "mastered": false,
+            "next_attempt_date": null,
+            "tags": ["array"],
+        });
+
+        let env = hook_env(HookEvent::Attempt, &payload);
+
+        let get = |name: &str| env.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str());
+        assert_eq!(get("TRACK_EVENT"), Some("attempt"));
+        assert_eq!(get("TRACK_PROBLEM_ID"), Some("1"));
+        assert_eq!(get("TRACK_NAME"), Some("Two Sum"));
+        assert_eq!(get("TRACK_MASTERED"), Some("false"));
+        assert_eq!(get("TRACK_NEXT_ATTEMPT_DATE"), None);
+        assert_eq!(get("TRACK_TAGS"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_the_configured_command_with_the_event_on_stdin() {
+        let hooks = Hooks {
+            on_build: Some(r#"read line && [ "$TRACK_DECK" = grind-75 ] && [ "$line" = '{"deck":"grind-75"}' ]"#.to_string()),
+            on_due: Some("exit 3".to_string()),
+            ..Hooks::default()
+        };
+        let payload = serde_json::json!({ "deck": "grind-75" });
+
+        hooks.run(HookEvent::Build, &payload).await.unwrap();
+        assert!(hooks.run(HookEvent::Due, &payload).await.is_err());
+        // No on_attempt hook is configured, so nothing runs.
+        hooks.run(HookEvent::Attempt, &payload).await.unwrap();
+    }
+}
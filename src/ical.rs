@@ -0,0 +1,81 @@
+// src/ical.rs
+
+/// Renders scheduled reviews as an iCalendar document.
+///
+/// Each review becomes one all-day VEVENT on its `next_attempt_date`. The
+/// `stamp` is written as every event's DTSTAMP, which iCalendar requires.
+pub fn render_calendar(reviews: &[ScheduledReview], stamp: NaiveDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//track//LeetCode reviews//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for review in reviews {
+        let start = review.next_attempt_date;
+        let end = start + Duration::days(1);
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:track-review-{}@track", review.problem_id));
+        lines.push(format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("Review #{}: {}", review.problem_id, review.name))
+        ));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    // iCalendar lines are terminated by CRLF, including the last one.
+    let mut calendar = lines.join("\r\n");
+    calendar.push_str("\r\n");
+    calendar
+}
+
+/// Escapes the characters that have special meaning in iCalendar TEXT values.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+use crate::db::ScheduledReview;
+use chrono::{Duration, NaiveDateTime};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn renders_an_all_day_event_per_review() {
+        let reviews = vec![ScheduledReview {
+            problem_id: 53,
+            name: "Maximum Subarray".to_string(),
+            difficulty: None,
+            next_attempt_date: NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+        }];
+        let stamp = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        let calendar = render_calendar(&reviews, stamp);
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(calendar.contains("\r\nUID:track-review-53@track\r\n"));
+        assert!(calendar.contains("\r\nDTSTART;VALUE=DATE:20240310\r\n"));
+        assert!(calendar.contains("\r\nDTEND;VALUE=DATE:20240311\r\n"));
+        assert!(calendar.contains("\r\nSUMMARY:Review #53: Maximum Subarray\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn escapes_special_characters_in_summary() {
+        assert_eq!(escape_text("Add, Search; Words"), "Add\\, Search\\; Words");
+    }
+}
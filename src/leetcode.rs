@@ -0,0 +1,236 @@
+// src/leetcode.rs
+
+/// LeetCode's GraphQL endpoint.
+pub const GRAPHQL_URL: &str = "https://leetcode.com/graphql";
+
+/// How long one request may take before it's abandoned.
+const TIMEOUT_SECONDS: &str = "20";
+
+/// Looks a problem's number up by its slug. `questionFrontendId` is the
+/// number LeetCode shows (875 for Koko Eating Bananas); `questionId` is an
+/// internal one that often differs, so it's never used.
+const FRONTEND_ID_QUERY: &str = "query questionFrontendId($titleSlug: String!) { question(titleSlug: $titleSlug) { questionFrontendId } }";
+
+/// Whether `slug` looks like a LeetCode problem slug: lowercase letters,
+/// digits and dashes only, e.g. `two-sum` or `3sum`.
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// A LeetCode account to make requests as, from the cookies of a browser
+/// logged in to it.
+#[derive(Debug, Clone, Copy)]
+pub struct Session<'a> {
+    /// The `LEETCODE_SESSION` cookie.
+    pub session: &'a str,
+    /// The `csrftoken` cookie, which LeetCode also wants as a header.
+    pub csrf_token: Option<&'a str>,
+}
+
+/// Sends `query` with `variables` to LeetCode's GraphQL API with curl and
+/// returns the response's `data`.
+///
+/// # Errors
+/// Returns an error if curl can't be run, the request fails, or LeetCode
+/// answers with GraphQL errors or no data.
+pub async fn graphql(query: &str, variables: Value) -> anyhow::Result<Value> {
+    request(query, variables, None).await
+}
+
+/// Like `graphql`, but signed in as `session`'s account.
+pub async fn graphql_signed_in(
+    query: &str,
+    variables: Value,
+    session: Session<'_>,
+) -> anyhow::Result<Value> {
+    request(query, variables, Some(session)).await
+}
+
+/// Sends a GraphQL request with curl. Everything but curl's own flags goes
+/// in a config read from stdin, so cookies never appear in the process
+/// list.
+async fn request(
+    query: &str,
+    variables: Value,
+    session: Option<Session<'_>>,
+) -> anyhow::Result<Value> {
+    let body = serde_json::json!({ "query": query, "variables": variables }).to_string();
+    let config = curl_config(&body, session);
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            TIMEOUT_SECONDS,
+            "--config",
+            "-",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl to reach LeetCode. Is curl installed?")?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin.write_all(config.as_bytes()).await?;
+    drop(stdin);
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("curl failed ({}): {}", output.status, stderr.trim());
+    }
+    parse_response(&output.stdout)
+}
+
+/// The curl config that posts `body` to `GRAPHQL_URL`, as `session` if set.
+fn curl_config(body: &str, session: Option<Session<'_>>) -> String {
+    let mut options = vec![
+        ("url", GRAPHQL_URL.to_string()),
+        ("header", "Content-Type: application/json".to_string()),
+        ("data-binary", body.to_string()),
+    ];
+    if let Some(Session {
+        session,
+        csrf_token,
+    }) = session
+    {
+        let csrf_token = csrf_token.unwrap_or_default();
+        options.push(("header", "Referer: https://leetcode.com".to_string()));
+        options.push(("header", format!("x-csrftoken: {}", csrf_token)));
+        options.push((
+            "cookie",
+            format!("LEETCODE_SESSION={}; csrftoken={}", session, csrf_token),
+        ));
+    }
+    options
+        .iter()
+        .map(|(name, value)| format!("{} = \"{}\"\n", name, quote_config_value(value)))
+        .collect()
+}
+
+/// Escapes `value` for a double-quoted string in a curl config.
+fn quote_config_value(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted
+}
+
+/// Whether `error` came from curl not being installed, which no retry fixes.
+pub fn is_curl_missing(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+    })
+}
+
+/// The `data` of a GraphQL response, or an error carrying its `errors`.
+fn parse_response(body: &[u8]) -> anyhow::Result<Value> {
+    let mut response: Value =
+        serde_json::from_slice(body).context("LeetCode's response wasn't JSON.")?;
+    if let Some(errors) = response.get("errors").and_then(Value::as_array) {
+        let messages: Vec<&str> = errors
+            .iter()
+            .filter_map(|error| error["message"].as_str())
+            .collect();
+        anyhow::bail!("LeetCode returned errors: {}", messages.join("; "));
+    }
+    match response.get_mut("data").map(Value::take) {
+        Some(data) if !data.is_null() => Ok(data),
+        _ => anyhow::bail!("LeetCode's response had no data."),
+    }
+}
+
+/// Looks up the number LeetCode shows for the problem with `slug`.
+///
+/// # Errors
+/// Returns an error if `slug` isn't a valid slug or the request fails.
+/// Returns `Ok(None)` if LeetCode has no problem with that slug.
+pub async fn fetch_frontend_id(slug: &str) -> anyhow::Result<Option<i64>> {
+    anyhow::ensure!(
+        is_valid_slug(slug),
+        "'{}' isn't a LeetCode problem slug.",
+        slug
+    );
+    let data = graphql(FRONTEND_ID_QUERY, serde_json::json!({ "titleSlug": slug })).await?;
+    frontend_id(&data)
+}
+
+/// The id in the `data` of a `FRONTEND_ID_QUERY` response. LeetCode sends it
+/// as a string, and `question` is null for an unknown slug.
+fn frontend_id(data: &Value) -> anyhow::Result<Option<i64>> {
+    let question = &data["question"];
+    if question.is_null() {
+        return Ok(None);
+    }
+    let id = question["questionFrontendId"]
+        .as_str()
+        .with_context(|| format!("LeetCode sent no questionFrontendId: {}", question))?;
+    id.parse()
+        .map(Some)
+        .with_context(|| format!("LeetCode's questionFrontendId '{}' isn't a number.", id))
+}
+
+use anyhow::Context;
+use serde_json::Value;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_slug_characters() {
+        assert!(is_valid_slug("two-sum"));
+        assert!(is_valid_slug("3sum"));
+        assert!(!is_valid_slug(""));
+        assert!(!is_valid_slug("Two-Sum"));
+        assert!(!is_valid_slug(r#"two-sum"}, "x": "#));
+        assert!(!is_valid_slug("two sum"));
+    }
+
+    #[test]
+    fn puts_the_body_and_cookies_in_the_curl_config() {
+        let session = Session {
+            session: "abc",
+            csrf_token: Some("xyz"),
+        };
+        let config = curl_config(r#"{"query":"a \"b\""}"#, Some(session));
+        assert!(config.starts_with("url = \"https://leetcode.com/graphql\"\n"));
+        assert!(config.contains(r#"data-binary = "{\"query\":\"a \\\"b\\\"\"}""#));
+        assert!(config.contains("cookie = \"LEETCODE_SESSION=abc; csrftoken=xyz\"\n"));
+        assert!(config.contains("header = \"x-csrftoken: xyz\"\n"));
+        assert!(!curl_config("{}", None).contains("cookie"));
+    }
+
+    #[test]
+    fn reads_the_frontend_id_from_a_response() {
+        let data =
+            parse_response(br#"{"data":{"question":{"questionFrontendId":"875"}}}"#).unwrap();
+        assert_eq!(frontend_id(&data).unwrap(), Some(875));
+
+        let unknown = parse_response(br#"{"data":{"question":null}}"#).unwrap();
+        assert_eq!(frontend_id(&unknown).unwrap(), None);
+
+        let message = parse_response(br#"{"errors":[{"message":"rate limited"}],"data":null}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("rate limited"));
+    }
+}
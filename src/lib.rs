@@ -0,0 +1,95 @@
+#![warn(missing_docs)]
+//! Tracks LeetCode practice in a local SQLite database.
+//!
+//! The `track` binary is a thin CLI over this crate. Open a database with
+//! [`init_pool`], then read and write progress through the functions in
+//! [`db`]. The ones most callers need are re-exported at the crate root.
+//!
+//! ```no_run
+//! use track::db::{NextFilter, NextStrategy};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let pool = track::init_pool("sqlite:lc_tracking.db").await?;
+//! let today = chrono::Local::now().date_naive();
+//! let next = track::fetch_next_problem(&pool, today, None, NextFilter::default(), NextStrategy::Order).await?;
+//! if let Some(problem) = next {
+//!     println!("Next up: #{} {}", problem.id, problem.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// Exports shaky problems as a tab-separated Anki deck.
+pub mod anki;
+/// Edits bank JSON files for the `track bank` commands.
+pub mod bank_file;
+/// Turns CSV and Markdown lists of LeetCode links into bank entries.
+pub mod bank_import;
+/// Parses files of attempts to log in one go.
+pub mod bulk;
+/// Loads `track.toml`.
+pub mod config;
+/// Parses the dates accepted on the command line.
+pub mod dates;
+/// Reads and writes the SQLite database.
+pub mod db;
+/// Finds duplicate entries in problem banks.
+pub mod duplicates;
+/// Exports problems, progress and history to files.
+pub mod export;
+/// Renders the attempt heatmap.
+pub mod heatmap;
+/// Runs user commands configured in `track.toml` when things happen.
+pub mod hooks;
+/// Exports scheduled reviews as an iCalendar file.
+pub mod ical;
+/// Talks to LeetCode's GraphQL API.
+pub mod leetcode;
+/// The Model Context Protocol server `track mcp` runs.
+pub mod mcp;
+/// Canonical problem metadata fetched from LeetCode.
+pub mod meta;
+/// Plans a day's practice for `track today`.
+pub mod plan;
+/// Work and break cycles for `track pomodoro`.
+pub mod pomodoro;
+/// Problem banks bundled into the binary, such as the Blind 75.
+pub mod presets;
+/// Attempt ratings and the progress kept per problem.
+pub mod problem_attempts;
+/// Reads problem bank JSON files.
+pub mod problem_bank;
+/// Builds a problem bank into the database.
+pub mod problem_bank_populator;
+/// The problems themselves.
+pub mod problems;
+/// When problems come back for review.
+pub mod schedule;
+/// Difficulty-weighted scores for `track goal`.
+pub mod scoring;
+/// Matches search queries against problem names and notes.
+pub mod search;
+/// The local web dashboard `track serve` runs.
+pub mod serve;
+/// Whole-database JSON snapshots for `track export --all` and `track import`.
+pub mod snapshot;
+/// The summary shown by `track stats`.
+pub mod stats;
+/// Daily practice streaks.
+pub mod streaks;
+/// Pulls accepted submissions from LeetCode for `track sync`.
+pub mod sync;
+/// Renders column-aligned, optionally colored tables.
+pub mod table;
+/// The interactive screen `track tui` shows.
+pub mod tui;
+/// POSTs logged attempts to the configured `webhook_url`.
+pub mod webhook;
+
+pub use db::{
+    ProgressView, ScheduledReview, fetch_all_problems, fetch_all_progress, fetch_next_problem,
+    fetch_problem, fetch_progress, init_pool, log_attempt,
+};
+pub use problem_attempts::{AttemptRating, ProblemAttempt, ProgressStatus};
+pub use problem_bank::{ProblemBankProblem, load_problems};
+pub use problems::{LeetCodeDifficulty, Problem};
@@ -1,18 +1,119 @@
 /// A CLI to track your LeetCode progress.
+///
+/// The global --db, --deck, --static-dir and --color flags can also be set
+/// with the TRACK_DB, TRACK_DECK, TRACK_STATIC_DIR and TRACK_COLOR environment
+/// variables. A flag given on the command line always wins over the
+/// environment.
+///
+/// Besides 0 for success, 1 for errors and 2 for bad usage, `track next`
+/// exits with 3 when there's nothing left to attempt and `track due --check`
+/// exits with 4 when reviews are due, for cron jobs and git hooks.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(group = ArgGroup::new("bank_source").args(["build", "preset", "from_csv", "from_markdown"]))]
 struct Cli {
     /// The command to execute. If no command is given, help is shown.
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Populates the database from a problem bank JSON file in the ./static/ directory.
+    /// Populates the database from a problem bank JSON file in the static directory.
     #[arg(long)]
     build: Option<String>,
 
+    /// Populates the database from a bank bundled with track: blind75 or
+    /// neetcode150. Their ids are known, so nothing is looked up.
+    #[arg(long)]
+    preset: Option<String>,
+
+    /// Populates the database from a CSV list of problems, one row per
+    /// LeetCode URL. Ids are looked up from the URLs.
+    #[arg(long, value_name = "FILE")]
+    from_csv: Option<PathBuf>,
+
+    /// With --from-csv, the column holding the URLs, counting from 1.
+    /// Without it, a column headed url or link is used, else the first
+    /// column with a LeetCode URL in it.
+    #[arg(long, requires = "from_csv")]
+    url_column: Option<usize>,
+
+    /// Populates the database from the LeetCode links in a Markdown file,
+    /// such as a checklist or a table. "Week N" headings set the weeks.
+    #[arg(long, value_name = "FILE")]
+    from_markdown: Option<PathBuf>,
+
+    /// How many problem ids to resolve at once while building.
+    #[arg(long, default_value_t = DEFAULT_CONCURRENCY, requires = "bank_source")]
+    concurrency: usize,
+
+    /// How many times to try each problem's id lookup before giving up.
+    #[arg(long, default_value_t = DEFAULT_RESOLVE_ATTEMPTS, requires = "bank_source")]
+    resolve_attempts: u32,
+
+    /// Validates the bank and reports what --build would change, without writing anything.
+    #[arg(long, requires = "bank_source")]
+    dry_run: bool,
+
+    /// With --build, takes problems that are no longer in the bank out of the
+    /// deck. Ones left in no deck are archived, keeping their history.
+    #[arg(long, requires = "bank_source")]
+    prune: bool,
+
+    /// With --build, fetches each problem's difficulty, topic tags and
+    /// acceptance rate from LeetCode, so the bank can leave them out.
+    #[arg(long, requires = "bank_source")]
+    fetch_meta: bool,
+
     /// Shows current progress and statistics for all attempted problems.
     #[arg(long)]
     progress: bool,
+
+    /// With --progress, only includes problems last attempted on or after this date.
+    #[arg(long, value_parser = parse_date_arg, requires = "progress")]
+    since: Option<NaiveDate>,
+
+    /// With --progress, only lists problems with this status.
+    #[arg(long, value_enum, requires = "progress")]
+    status: Option<ProgressStatus>,
+
+    /// Limits commands to one deck (each built bank is its own deck). With
+    /// --build, the deck to file the bank under instead of the bank's file
+    /// name. Also accepted as --bank.
+    #[arg(long, visible_alias = "bank", global = true, env = "TRACK_DECK")]
+    deck: Option<String>,
+
+    /// The sqlite database file to use. Defaults to `db` in track.toml, then
+    /// lc_tracking.db if the working directory has one, then
+    /// ~/.local/share/track/track.db (under $XDG_DATA_HOME if set).
+    #[arg(long, global = true, env = "TRACK_DB")]
+    db: Option<PathBuf>,
+
+    /// The directory holding bank files.
+    #[arg(long, global = true, env = "TRACK_STATIC_DIR", default_value = DEFAULT_STATIC_DIR)]
+    static_dir: PathBuf,
+
+    /// When to use colored output. `auto` colors a terminal unless the
+    /// NO_COLOR environment variable is set.
+    #[arg(long, global = true, env = "TRACK_COLOR", value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Same as --color never.
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Prints machine-readable JSON instead of text. Supported by next, all,
+    /// due, stats and --progress.
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Only prints results (IDs, counts, listings and JSON), not headers,
+    /// confirmations or progress. Errors still go to stderr.
+    #[arg(long, short, global = true)]
+    quiet: bool,
+
+    /// Doesn't POST attempts to the `webhook_url` in track.toml, including
+    /// those logged through `serve` and `mcp`.
+    #[arg(long, global = true)]
+    no_webhook: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -23,183 +124,3678 @@ enum Commands {
         /// Display the problem details in a long, descriptive format.
         #[arg(long, short)]
         long: bool,
+
+        /// How to pick among unattempted problems. Pinned problems still come first.
+        #[arg(long, value_enum, default_value_t = NextStrategy::Order)]
+        strategy: NextStrategy,
+
+        /// Prefer reviews that are due (failed ones first) over unattempted problems.
+        #[arg(long, short)]
+        review: bool,
+
+        /// Only considers problems with this tag, e.g. "graph".
+        #[arg(long, value_parser = parse_tag_arg)]
+        tag: Option<String>,
+
+        /// Picks a due or unattempted problem from the tag with your worst
+        /// record (most failed and Messy ratings).
+        #[arg(long, conflicts_with_all = ["tag", "difficulty", "week"])]
+        weakest: bool,
+
+        /// Only considers problems of this difficulty.
+        #[arg(long, value_enum)]
+        difficulty: Option<LeetCodeDifficulty>,
+
+        /// Only considers problems in this study-plan week.
+        #[arg(long)]
+        week: Option<i64>,
+
+        /// Lists this many unattempted candidates, in the order they'd be
+        /// suggested, instead of just the next one.
+        #[arg(
+            long,
+            value_parser = clap::value_parser!(u32).range(1..),
+            conflicts_with_all = ["long", "review", "weakest"]
+        )]
+        count: Option<u32>,
     },
 
     /// Logs an attempt for a specific problem.
     Attempt {
-        /// The LeetCode ID of the problem.
-        id: i64,
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
         /// Your rating of the attempt (1=ShortFail, 2=LongFail, 3=Messy, 4=Hard, 5=Easy).
         #[arg(value_parser = clap::value_parser!(u8).range(1..=5))]
         rating: u8,
-        /// The date of the attempt in YYYY-MM-DD format (optional, defaults to today).
-        date: Option<String>,
+        /// The date of the attempt: YYYY-MM-DD, 'yesterday', '3d', etc. (optional, defaults to today).
+        #[arg(value_parser = parse_date_arg)]
+        date: Option<NaiveDate>,
+        /// The programming language you solved it in, e.g. "rust" or "python".
+        #[arg(long)]
+        lang: Option<String>,
+        /// How many minutes the attempt took.
+        #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+        minutes: Option<i64>,
+        /// A note about this attempt, e.g. "used two pointers, forgot the empty case".
+        #[arg(long)]
+        note: Option<String>,
+    },
+
+    /// Starts timing an attempt at a problem. `track stop` logs it with the
+    /// time taken.
+    Start {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Stops the timer from `track start` and logs the attempt with the
+    /// minutes it measured.
+    Stop {
+        /// Your rating of the attempt (1=ShortFail, 2=LongFail, 3=Messy, 4=Hard, 5=Easy).
+        #[arg(long, short, value_parser = clap::value_parser!(u8).range(1..=5), required_unless_present = "discard")]
+        rating: Option<u8>,
+        /// The programming language you solved it in, e.g. "rust" or "python".
+        #[arg(long)]
+        lang: Option<String>,
+        /// A note about this attempt.
+        #[arg(long)]
+        note: Option<String>,
+        /// Stops the timer without logging an attempt.
+        #[arg(long, conflicts_with_all = ["rating", "lang", "note"])]
+        discard: bool,
+    },
+
+    /// Works on a problem in pomodoro cycles: a countdown for each work
+    /// stretch and break, with a desktop notification as each one ends.
+    ///
+    /// When the cycles end, or on Ctrl-C, the time spent working (not the
+    /// breaks) is logged with the attempt. Notifications use notify-send on
+    /// Linux and osascript on macOS.
+    Pomodoro {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+        /// Minutes per work stretch.
+        #[arg(long, default_value_t = 25, value_parser = clap::value_parser!(u64).range(1..))]
+        work: u64,
+        /// Minutes per break.
+        #[arg(long = "break", default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+        break_minutes: u64,
+        /// How many work stretches to run.
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+        cycles: u32,
+        /// Your rating of the attempt (1-5). Asked for at the end if left out.
+        #[arg(long, short, value_parser = clap::value_parser!(u8).range(1..=5))]
+        rating: Option<u8>,
+    },
+
+    /// Serves a dashboard in the browser: your progress, the due queue, an
+    /// activity heatmap, per-week and per-tag progress, and a form to log
+    /// attempts.
+    ///
+    /// With --api it serves a JSON API for other clients instead:
+    /// `GET /problems` (optionally `?tag=`), `GET /due`, `GET /stats`, and
+    /// `POST /attempts` with a body like `{"problem": 1, "rating": 4}`
+    /// (plus optional `date`, `lang`, `minutes` and `note`).
+    /// `POST /attempts/by-url` takes the problem's LeetCode `url` instead of
+    /// `problem`, adding the problem if it isn't stored yet, so a userscript
+    /// can log an attempt from the problem's page. It needs a --token.
+    /// Only with a --token can pages on other sites, such as LeetCode's,
+    /// call the API.
+    ///
+    /// It only listens on localhost unless given --host. Stop it with Ctrl-C.
+    Serve {
+        /// The port to listen on.
+        #[arg(long, default_value_t = DEFAULT_PORT)]
+        port: u16,
+        /// The address to listen on. Use 0.0.0.0 to accept connections from
+        /// other devices, ideally with a --token.
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Open the dashboard in the browser once it's up.
+        #[arg(long, conflicts_with = "api")]
+        open: bool,
+        /// Serve the JSON API instead of the dashboard.
+        #[arg(long)]
+        api: bool,
+        /// Require API requests to send `Authorization: Bearer <TOKEN>`.
+        #[arg(
+            long,
+            env = "TRACK_API_TOKEN",
+            requires = "api",
+            hide_env_values = true
+        )]
+        token: Option<String>,
+    },
+
+    /// Runs a Model Context Protocol server on stdin and stdout, so an AI
+    /// assistant can use your tracker.
+    ///
+    /// Its tools are get_next_problem, log_attempt, get_due_reviews and
+    /// get_progress_stats. Register `track mcp` as a stdio server in the
+    /// assistant's MCP settings.
+    Mcp,
+
+    /// Logs attempts for problems you've solved on LeetCode but haven't
+    /// tracked yet, one per day each was accepted.
+    ///
+    /// Needs curl and the LEETCODE_SESSION and csrftoken cookies from a
+    /// browser logged in to LeetCode. Problems that already have progress are
+    /// left alone, as are solved problems that aren't in any deck.
+    Sync {
+        /// Your LEETCODE_SESSION cookie.
+        #[arg(long, env = "LEETCODE_SESSION", hide_env_values = true)]
+        session: String,
+        /// Your csrftoken cookie.
+        #[arg(long, env = "LEETCODE_CSRF", hide_env_values = true)]
+        csrf_token: Option<String>,
+        /// The rating to log synced attempts with, since LeetCode doesn't
+        /// know how they went (1=ShortFail, 2=LongFail, 3=Messy, 4=Hard, 5=Easy).
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..=5))]
+        rating: u8,
+        /// Lists the attempts that would be logged without logging them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Logs many attempts at once from a file of `<id> <rating> [date]` lines.
+    ///
+    /// Every line is checked before anything is written, and all attempts are
+    /// applied in a single transaction: either the whole file is logged or none of it.
+    BulkAttempt {
+        /// The file to read attempts from.
+        path: PathBuf,
+    },
+
+    /// Starts an interactive session: shows the next due (or unattempted)
+    /// problem, asks for a 1-5 rating and an optional note, logs them, and
+    /// moves on until you quit.
+    Review {
+        /// Stop once nothing is due instead of moving on to unattempted problems.
+        #[arg(long)]
+        due_only: bool,
     },
 
     /// Shows all problems in the database, grouped by week.
-    All,
+    All {
+        /// Which page of problems to show, starting from 1.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        page: u32,
+        /// How many problems to show per page.
+        #[arg(long, default_value_t = 25, value_parser = clap::value_parser!(u32).range(1..))]
+        page_size: u32,
+        /// Only lists problems with this tag, e.g. "dp".
+        #[arg(long, value_parser = parse_tag_arg)]
+        tag: Option<String>,
+    },
+
+    /// Opens a problem's page in the default browser.
+    Open {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Shows everything known about a single problem: its details, decks
+    /// and tags, progress and review interval, every attempt and its notes.
+    #[command(visible_alias = "info")]
+    Show {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Checks the database's health: integrity, migrations, and orphaned rows.
+    ///
+    /// Exits non-zero if the integrity check fails or orphaned progress rows exist.
+    Doctor {
+        /// Delete progress rows that reference problems that no longer exist.
+        #[arg(long)]
+        fix_orphans: bool,
+    },
+
+    /// Shows a GitHub-style grid of attempts per day.
+    Heatmap {
+        /// How many weeks back to show.
+        #[arg(default_value_t = 12)]
+        weeks: u32,
+
+        /// Draws the grid with plain ASCII instead of block characters.
+        #[arg(long)]
+        ascii: bool,
+    },
+
+    /// Pins a problem so `next` suggests it before anything else.
+    Pin {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Removes a problem's pin or skip, returning it to its normal place in the queue.
+    Unpin {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Lists the pinned problems, in the order `next` offers them. Attempted
+    /// ones aren't offered any more and can be unpinned.
+    Pins,
+
+    /// Moves a problem to the back of the queue, behind every other
+    /// unattempted problem, until it's unpinned.
+    Skip {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Sets a problem's priority level. Within a week, `next` and `today`
+    /// suggest high-priority problems first and low-priority ones last.
+    SetPriority {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+        /// The level: high, normal or low.
+        #[arg(value_enum)]
+        level: PriorityLevel,
+    },
+
+    /// Pushes a problem's next review back by some days. An overdue review
+    /// is pushed back from today.
+    Postpone {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+
+        /// How many days to push the review back by.
+        #[arg(value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+    },
+
+    /// Stops `next` and review sessions from suggesting a problem. Leeches,
+    /// problems failed four or more times, are suspended automatically.
+    Suspend {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Lets `next` suggest a suspended problem again, even a leech.
+    Unsuspend {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Shows how many problems in each week have been attempted.
+    Weeks,
+
+    /// Shows your current and longest daily-attempt streaks.
+    Streak,
+
+    /// Shows an overview: due reviews, the next problem of each week and how
+    /// your latest ratings are spread. `track tui` is the interactive version.
+    Dashboard {
+        /// The most due reviews to list.
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+    },
+
+    /// Opens an interactive overview in the terminal: due reviews, the next
+    /// problem of each week and how your latest ratings are spread.
+    ///
+    /// Tab switches between the two lists and the arrow keys (or j and k)
+    /// move through them. Press 1-5 to log that rating for the problem under
+    /// the cursor, or Enter to be asked for one. r reloads and q quits.
+    Tui,
+
+    /// Lists every deck with how many of its problems have been attempted.
+    #[command(visible_alias = "banks")]
+    Decks,
+
+    /// Recomputes every review date with the current scheduling rules.
+    Reschedule,
+
+    /// Moves a problem's next review to a specific date, bypassing the scheduler.
+    ///
+    /// The date holds until the next logged attempt or `reschedule`.
+    Snooze {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+
+        /// The new review date (e.g. 2024-05-01, tomorrow, +3d, +2w).
+        #[arg(value_parser = parse_date_arg, required_unless_present = "clear")]
+        until: Option<NaiveDate>,
+
+        /// Removes the review date instead, leaving the problem unscheduled.
+        #[arg(long, conflicts_with = "until")]
+        clear: bool,
+    },
+
+    /// Marks an attempted problem mastered so it's never scheduled for review
+    /// again. Logging another attempt puts it back into rotation.
+    Master {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Shows today's LeetCode Daily Challenge and offers to log an attempt.
+    ///
+    /// The problem is added to the "daily" deck (or --deck), and to the
+    /// database if it isn't there yet.
+    Daily {
+        /// Logs an attempt with this rating without asking
+        /// (1=ShortFail, 2=LongFail, 3=Messy, 4=Hard, 5=Easy).
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=5))]
+        rating: Option<u8>,
+    },
+
+    /// Fetches each problem's difficulty, topic tags and acceptance rate from
+    /// LeetCode again, as `--build --fetch-meta` does.
+    RefreshMeta {
+        /// Only fetches problems that have no metadata yet.
+        #[arg(long)]
+        missing: bool,
+
+        /// How many lookups to run at once.
+        #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+    },
+
+    /// Takes problems that are no longer in a bank file out of its deck.
+    /// Problems left in no other deck are archived: hidden everywhere, but
+    /// their progress, history and notes are kept. Only the bank's deck (or
+    /// --deck) is touched.
+    Prune {
+        /// The bank file in the static directory to compare against.
+        bank: String,
+
+        /// Lists what would be removed without deleting anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skips the confirmation prompt.
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// Lists problems that look like duplicates of each other.
+    Duplicates {
+        /// A bank file in the static directory to check. Checks the database if omitted.
+        bank: Option<String>,
+
+        /// Group by shared id, or by near-identical names across ids.
+        #[arg(long, value_enum, default_value_t = DuplicateKey::Name)]
+        by: DuplicateKey,
+    },
+
+    /// Checks and edits problem bank files.
+    Bank {
+        #[command(subcommand)]
+        command: BankCommand,
+    },
+
+    /// Merges another track database into this one.
+    ///
+    /// Where both have progress for a problem, the one with more attempts is
+    /// kept (ties go to the more recent). Everything else is copied over.
+    Merge {
+        /// Path to the other sqlite database.
+        other_db: String,
+    },
+
+    /// Merges a JSON snapshot from `track export --all` into the database.
+    ///
+    /// Follows the same rules as `track merge`, so importing into an empty
+    /// database rebuilds it from the snapshot.
+    Import {
+        /// Path to the snapshot file.
+        path: PathBuf,
+    },
+
+    /// Shows reviews that are past their scheduled date, most overdue first.
+    Overdue,
+
+    /// Finds problems by name, or failing that by their notes.
+    ///
+    /// Matching ignores case and punctuation, and tolerates missing letters,
+    /// so "two sum ii" or "lru cach" find what you meant.
+    Search {
+        /// What to look for.
+        query: String,
+
+        /// The most results to show.
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+
+    /// Sets the note kept for a problem, replacing any earlier one.
+    ///
+    /// Without TEXT, the note is opened in $VISUAL or $EDITOR. Saving an empty
+    /// note removes it.
+    Note {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+        /// The note's text.
+        text: Option<String>,
+    },
+
+    /// Shows a problem's note and the notes from each of its attempts.
+    Notes {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Removes the most recent attempt at a problem, rewinding its progress
+    /// to the attempt before.
+    DeleteAttempt {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Removes the most recently logged attempt, whichever problem it was for.
+    Undo,
+
+    /// Shows every logged attempt at a problem, oldest first.
+    History {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+    },
+
+    /// Lists reviews due today or earlier, most overdue first.
+    Due {
+        /// Only print how many reviews are due, for scripts and status bars.
+        #[arg(long)]
+        count: bool,
+
+        /// Don't list the reviews; exit with status 4 if any are due.
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Sends a desktop notification saying how many reviews are due and
+    /// naming the first few, or does nothing if none are.
+    ///
+    /// Meant to be run from cron or a systemd timer. Notifications use
+    /// notify-send on Linux and osascript on macOS.
+    Notify,
+
+    /// Exports attempted problems rated below Easy as an Anki deck.
+    ///
+    /// The file is tab-separated with one card per line. Import it in Anki with
+    /// File > Import, choose the "Basic" note type, and map field 1 to Front
+    /// ("Problem #<id>: <name>") and field 2 to Back ("<url> (<difficulty>)").
+    Anki {
+        /// Where to write the tab-separated deck.
+        path: PathBuf,
+    },
+
+    /// Exports your problems and progress or your attempt history as CSV, the
+    /// whole database as a JSON snapshot, your due reviews as an Anki deck, or
+    /// your scheduled reviews as a calendar.
+    Export {
+        /// The file format to write.
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// Writes one row per logged attempt instead of one per problem.
+        #[arg(long)]
+        history: bool,
+
+        /// Writes the whole database, every deck included, as a JSON snapshot
+        /// that `track import` can load on another machine.
+        #[arg(long, conflicts_with_all = ["format", "history"])]
+        all: bool,
+
+        /// Writes the problems due for review as a tab-separated Anki deck.
+        /// Each card's front is the problem's name and URL; the back is its
+        /// note and your last approach. Import it with File > Import.
+        #[arg(long, conflicts_with_all = ["format", "history", "all"])]
+        anki: bool,
+
+        /// Writes every scheduled review as an all-day iCalendar (.ics) event,
+        /// for subscribing to or importing into a calendar.
+        #[arg(long, conflicts_with_all = ["format", "history", "all", "anki"])]
+        ics: bool,
+
+        /// With --ics, only reviews due within this many days (overdue ones
+        /// included).
+        #[arg(long, requires = "ics")]
+        days: Option<u32>,
+
+        /// Where to write the export. Defaults to standard output.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Exports every scheduled review as an iCalendar (.ics) file.
+    Calendar {
+        /// Where to write the .ics file.
+        path: PathBuf,
+    },
+
+    /// Shows progress toward your weekly goal of distinct problems attempted.
+    Goal {
+        /// The goal for this week. Overrides `weekly_goal` in track.toml.
+        #[arg(long)]
+        target: Option<u32>,
+    },
+
+    /// Plans today's session: every due review plus today's share of the
+    /// week's new problems (`weekly_new` in track.toml, 7 by default).
+    Today {
+        /// New problems to start this week. Overrides `weekly_new` in track.toml.
+        #[arg(long)]
+        weekly_new: Option<u32>,
+    },
+
+    /// Shows aggregate statistics: totals, ratings, difficulties, due reviews and streaks.
+    Stats,
+
+    /// Prints a shell completion script to stdout.
+    ///
+    /// Bash, zsh and fish scripts also complete problem IDs from the
+    /// database. For example: `track completions bash > ~/.local/share/bash-completion/completions/track`.
+    Completions {
+        /// The shell to generate completions for.
+        shell: Shell,
+    },
+
+    /// Prints every problem as `<id>\t<name>`, for the completion scripts.
+    #[command(name = "__complete-ids", hide = true)]
+    CompleteIds,
 }
 
-/// Converts the 1-5 integer rating from the CLI to the AttemptRating enum.
-fn map_rating(rating_num: u8) -> AttemptRating {
-    match rating_num {
-        1 => AttemptRating::ShortFail,
-        2 => AttemptRating::LongFail,
-        3 => AttemptRating::Messy,
-        4 => AttemptRating::Hard,
-        5 => AttemptRating::Easy,
-        _ => unreachable!(),
-    }
+#[derive(Subcommand, Debug)]
+enum BankCommand {
+    /// Checks a bank file without touching the database: bad fields and
+    /// difficulties, missing URLs, duplicate ids or orders, and gaps in the
+    /// weeks.
+    ///
+    /// Exits non-zero if the bank wouldn't build. Warnings alone don't fail.
+    Validate {
+        /// The bank file, as a path or a name in the static directory.
+        file: PathBuf,
+    },
+
+    /// Writes a bank file from the problem links on a public list page, such
+    /// as the NeetCode roadmap or a LeetCode study plan.
+    ///
+    /// Problems keep the page's order and are split into weeks of
+    /// --per-week. Ids are left for --build to look up. Pages that load
+    /// their list with JavaScript have no links to find; save those as
+    /// Markdown and use --from-markdown instead.
+    ImportUrl {
+        /// The page to fetch.
+        url: String,
+        /// Where to write the bank. Defaults to a file named after the page
+        /// in the static directory.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// How many problems go in each week.
+        #[arg(long, default_value_t = DEFAULT_PROBLEMS_PER_WEEK, value_parser = clap::value_parser!(u32).range(1..))]
+        per_week: u32,
+        /// Overwrites the file if it already exists.
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Moves a problem in a bank file to just before or after another, then
+    /// renumbers `order` from 1 with no gaps.
+    ///
+    /// The moved problem takes the week of the one it's moved next to.
+    /// Without a problem, only renumbers. The database isn't touched until
+    /// the bank is built again.
+    #[command(group = ArgGroup::new("position").args(["before", "after"]).requires("problem"))]
+    Reorder {
+        /// The bank file, as a path or a name in the static directory.
+        file: PathBuf,
+        /// The problem to move: its LeetCode ID, its name (or a unique prefix)
+        /// or its URL slug.
+        #[arg(requires = "position")]
+        problem: Option<String>,
+        /// Moves it to just before this problem.
+        #[arg(long)]
+        before: Option<String>,
+        /// Moves it to just after this problem.
+        #[arg(long)]
+        after: Option<String>,
+    },
+
+    /// Sets every week in a bank file from its order: the first --per-week
+    /// problems go in week 1, the next in week 2, and so on.
+    ///
+    /// The database isn't touched until the bank is built again.
+    Autoweek {
+        /// The bank file, as a path or a name in the static directory.
+        file: PathBuf,
+        /// How many problems go in each week.
+        #[arg(long, default_value_t = DEFAULT_PROBLEMS_PER_WEEK, value_parser = clap::value_parser!(u32).range(1..))]
+        per_week: u32,
+    },
+
+    /// Shows the problems added to, removed from and changed in one bank
+    /// file compared with another.
+    ///
+    /// Problems are matched by LeetCode ID, or by URL where an id is 0.
+    /// Either bank may also be a preset, e.g. blind75.
+    Diff {
+        /// The bank to compare from, as a path or a name in the static directory.
+        old: PathBuf,
+        /// The bank to compare to, as a path or a name in the static directory.
+        new: PathBuf,
+    },
+
+    /// Combines bank files into a new one, dropping problems already in an
+    /// earlier bank.
+    ///
+    /// Problems are matched by LeetCode ID, or by URL where an id is 0, and
+    /// the earliest bank's entry is kept. Each bank's weeks continue from the
+    /// previous one's, and `order` runs on from 1. Banks may also be presets,
+    /// e.g. blind75.
+    Merge {
+        /// The banks to combine, in order, as paths or names in the static
+        /// directory.
+        #[arg(num_args = 2.., required = true)]
+        files: Vec<PathBuf>,
+        /// Where to write the combined bank.
+        #[arg(long)]
+        out: PathBuf,
+        /// Overwrites --out if it already exists.
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Adds a problem to a deck, and with --file to a bank file too.
+    ///
+    /// Its id is looked up from the URL unless --id is given. The deck is
+    /// --deck, else the bank file's name.
+    Add {
+        /// The problem's name.
+        #[arg(long)]
+        name: String,
+        /// The problem's LeetCode URL.
+        #[arg(long)]
+        url: String,
+        /// The study-plan week it belongs to.
+        #[arg(long)]
+        week: Option<i64>,
+        /// The problem's difficulty.
+        #[arg(long, value_enum)]
+        difficulty: Option<LeetCodeDifficulty>,
+        /// The problem's LeetCode ID, to skip looking it up.
+        #[arg(long)]
+        id: Option<i64>,
+        /// A topic tag, e.g. graph. Repeat for several.
+        #[arg(long = "tag", value_parser = parse_tag_arg)]
+        tags: Vec<String>,
+        /// Also adds it to this bank file, a path or a name in the static directory.
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Moves a problem to another week, and with --file in a bank file too.
+    SetWeek {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+        /// The week to move it to.
+        week: i64,
+        /// Also moves it in this bank file, a path or a name in the static directory.
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Takes a problem out of a deck, and with --file out of a bank file too.
+    ///
+    /// The deck is --deck, else the bank file's name, else the only deck the
+    /// problem is in. A problem left in no deck is archived, keeping its
+    /// progress and history.
+    Remove {
+        /// The problem: its LeetCode ID, its name (or a unique prefix) or its URL slug.
+        id: ProblemRef,
+        /// Also removes it from this bank file, a path or a name in the static directory.
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    // --- Database Setup ---
-    let db_url = "sqlite:lc_tracking.db";
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect_with(
-            db_url
-                .parse::<sqlx::sqlite::SqliteConnectOptions>()?
-                .create_if_missing(true),
-        )
-        .await?;
-    sqlx::migrate!("./migrations").run(&pool).await?;
+/// The exit status of `track next` when there's nothing left to attempt.
+const EXIT_NOTHING_LEFT: i32 = 3;
 
-    // --- Parse CLI commands ---
-    let cli = Cli::parse();
+/// The exit status of `track due --check` when reviews are due.
+const EXIT_REVIEWS_DUE: i32 = 4;
 
-    // --- Handle top-level flags first ---
-    if let Some(bank_name) = cli.build {
-        println!("\n--- Starting Problem Bank Population ---");
-        if let Err(e) = populate_problem_bank(&pool, &bank_name).await {
-            eprintln!("Error during population: {:?}", e);
-        } else {
-            println!("--- Population Task Finished ---");
+/// Set from --quiet before any command runs.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// `println!` for chatter that --quiet hides. Results a script would read
+/// still use `println!`.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            println!($($arg)*);
         }
-        return Ok(());
+    };
+}
+
+/// A clap `value_parser` for tags, which are stored trimmed and lowercased.
+fn parse_tag_arg(input: &str) -> Result<String, String> {
+    let tag = normalize_tag(input);
+    if tag.is_empty() {
+        return Err("a tag can't be blank".to_string());
     }
+    Ok(tag)
+}
 
-    if cli.progress {
-        println!("\n--- Current Progress ---");
-        let progress_list = fetch_all_progress(&pool).await?;
-        if progress_list.is_empty() {
-            println!("No problems have been attempted yet. Use the 'attempt' command to start!");
-        } else {
-            for item in &progress_list {
-                println!(
-                    "  - #{:<5} {:<40} Rating: {:<10} Attempts: {}",
-                    item.problem_id,
-                    item.name,
-                    format!("{:?}", item.attempt_rating),
-                    item.number_of_attempts
-                );
-            }
-            let mut stats: HashMap<AttemptRating, u32> = HashMap::new();
-            for item in &progress_list {
-                *stats.entry(item.attempt_rating).or_insert(0) += 1;
-            }
-            println!("\n--- Statistics ---");
-            println!("Total Problems Attempted: {}", progress_list.len());
-            for (rating, count) in stats {
-                println!("  - {:<10}: {}", format!("{:?}", rating), count);
-            }
+/// One row of `--progress --json`: the progress plus its derived status.
+#[derive(serde::Serialize)]
+struct ProgressEntry<'a> {
+    #[serde(flatten)]
+    progress: &'a ProgressView,
+    status: ProgressStatus,
+}
+
+/// Whether tables should be colored: `auto` only colors a terminal, and
+/// only if NO_COLOR is unset or empty.
+fn use_color(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
         }
-        return Ok(());
     }
+}
 
-    // --- Handle Subcommands ---
-    if let Some(command) = cli.command {
-        match command {
-            Commands::Next { long } => match fetch_next_unattempted_problem(&pool).await {
-                Ok(Some(problem)) => {
-                    if long {
-                        println!("\n--- Next Problem to Attempt ---");
-                        println!("Order: #{}", problem.order);
-                        println!("Name:  {}", problem.name);
-                        println!("ID:    {}", problem.id);
-                        if let Some(diff) = problem.difficulty {
-                            println!("Diff:  {:?}", diff);
+/// Prints `value` as pretty JSON, for `--json`.
+fn print_json(value: &impl serde::Serialize) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Prints what `track merge` or `track import` brought in from `source`.
+fn print_merge_summary(source: &str, summary: &MergeSummary) {
+    say!("Merged {}:", source);
+    say!("  Problems added:   {}", summary.problems_added);
+    if summary.problems_matched_by_name > 0 {
+        say!(
+            "  Matched by name:  {} (stored under another id there)",
+            summary.problems_matched_by_name
+        );
+    }
+    say!("  Progress added:   {}", summary.progress_added);
+    say!("  Progress updated: {}", summary.progress_updated);
+    say!("  History imported: {}", summary.history_added);
+    say!("  Notes added:      {}", summary.notes_added);
+}
+
+/// Warns that the current streak ends today unless an attempt is logged.
+fn print_streak_warning(dates: &[NaiveDate], today: NaiveDate) {
+    if let Some(days) = streak_at_risk(dates, today) {
+        say!(
+            "\nYour {}-day streak ends today unless you log an attempt.",
+            days
+        );
+    }
+}
+
+/// Prints one line per attempt, oldest first, with its note underneath.
+fn print_attempt_history(history: &[HistoryEntry]) {
+    for entry in history {
+        let mut line = format!(
+            "  {:>3}. {}  {:<10}",
+            entry.attempt_number, entry.attempted_on, entry.rating
+        );
+        if let Some(language) = &entry.language {
+            line.push_str(&format!(" {}", language));
+        }
+        if let Some(minutes) = entry.duration_minutes {
+            line.push_str(&format!(" {} min", minutes));
+        }
+        println!("{}", line.trim_end());
+        if let Some(note) = &entry.note {
+            println!("       {}", note);
+        }
+    }
+}
+
+/// Renders a fixed-width text bar showing `done` out of `total`.
+fn progress_bar(done: u64, total: u64, width: usize) -> String {
+    let filled = if total == 0 {
+        0
+    } else {
+        ((done.min(total) as f64 / total as f64) * width as f64).round() as usize
+    };
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+/// Runs `track tui` on `term` until the user quits, logging the attempts
+/// picked on the screen like `track attempt` would.
+async fn run_tui(
+    term: &Term,
+    config: &Config,
+    pool: &SqlitePool,
+    deck: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut screen = Screen::new(Overview::load(pool, deck, Local::now().date_naive()).await?);
+    loop {
+        let (rows, columns) = term.size();
+        term.move_cursor_to(0, 0)?;
+        term.write_str(&screen.render(columns as usize, rows as usize).join("\n"))?;
+        term.clear_to_end_of_screen()?;
+        term.flush()?;
+
+        // Reading a key blocks, so it's done off the runtime's threads.
+        let reader = term.clone();
+        let key = tokio::task::spawn_blocking(move || reader.read_key()).await??;
+        let status = match screen.handle_key(&key) {
+            Action::Redraw => continue,
+            Action::Quit => return Ok(()),
+            Action::Reload => "Reloaded.".to_string(),
+            Action::Log { problem_id, rating } => {
+                match log_attempt(pool, problem_id, rating, None, AttemptDetails::default()).await {
+                    Ok(_) => {
+                        after_attempt(config, pool, problem_id).await;
+                        let next = fetch_progress(pool, problem_id)
+                            .await?
+                            .and_then(|progress| progress.next_attempt_date);
+                        match next {
+                            Some(date) => format!(
+                                "Logged {} for #{}. Next review: {}.",
+                                rating, problem_id, date
+                            ),
+                            None => format!("Logged {} for #{}.", rating, problem_id),
                         }
-                    } else {
-                        println!("{}", problem.id);
-                    }
-                }
-                Ok(None) => {
-                    if long {
-                        println!("\n🎉 Congratulations! You have attempted all problems!");
                     }
+                    Err(e) => format!("Couldn't log the attempt: {:#}", e),
                 }
-                Err(e) => {
-                    eprintln!("Error fetching next problem: {:?}", e);
-                }
-            },
-            Commands::Attempt { id, rating, date } => {
-                println!("\n--- Logging attempt for problem {} ---", id);
-                let attempt_rating = map_rating(rating);
-                let attempt_date = date
-                    .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d"))
-                    .transpose()
-                    .context("Failed to parse date. Please use YYYY-MM-DD format.")?;
-
-                if fetch_progress(&pool, id).await?.is_some() {
-                    println!("Updating existing progress...");
-                    update_progress(&pool, id, attempt_rating, attempt_date).await?;
-                } else {
-                    println!("Logging first attempt...");
-                    add_or_replace_progress(&pool, id, attempt_rating, attempt_date).await?;
-                }
-                println!(
-                    "Successfully logged attempt for problem {} with rating: {:?}",
-                    id, attempt_rating
-                );
             }
-            Commands::All => {
-                println!("\n--- All Problems ---");
-                let all_problems = fetch_all_problems(&pool).await?;
-                if all_problems.is_empty() {
-                    println!("No problems found in the database. Use the --build command to populate it.");
-                } else {
-                    let mut last_printed_week: Option<i64> = None;
-                    for problem in &all_problems {
-                        if problem.week != last_printed_week {
-                            if let Some(week_num) = problem.week {
-                                println!("\nWeek: {}", week_num);
-                            } else {
-                                println!("\nWeek: Unassigned");
-                            }
-                            last_printed_week = problem.week;
-                        }
-                        println!("  {}: {} - {}", problem.order, problem.name, problem.id);
-                        if let Some(diff) = problem.difficulty {
-                            println!("    Difficulty: {:?}", diff);
-                        }
-                    }
-                }
+        };
+        screen.set_overview(Overview::load(pool, deck, Local::now().date_naive()).await?);
+        screen.status = status;
+    }
+}
+
+/// Runs the interactive `review` loop until the user quits, stdin closes, or
+/// there's nothing left to work on. Returns how many problems were reviewed.
+async fn run_review_session(
+    config: &Config,
+    pool: &SqlitePool,
+    deck: Option<&str>,
+    due_only: bool,
+) -> anyhow::Result<u32> {
+    let stdin = io::stdin();
+    let mut reviewed = 0;
+
+    loop {
+        let today = Local::now().date_naive();
+        let Some(problem) = fetch_next_problem(
+            pool,
+            today,
+            deck,
+            NextFilter::default(),
+            NextStrategy::Order,
+        )
+        .await?
+        else {
+            println!("\nNothing is due and every problem has been attempted.");
+            return Ok(reviewed);
+        };
+
+        let progress = fetch_progress(pool, problem.id).await?;
+        // Due reviews always come first, so an unattempted problem means
+        // nothing is left to review.
+        if due_only && progress.is_none() {
+            println!("\nNothing else is due. Nice work!");
+            return Ok(reviewed);
+        }
+
+        println!("\n--- #{}: {} ---", problem.id, problem.name);
+        if let Some(diff) = problem.difficulty {
+            println!("Diff:  {:?}", diff);
+        }
+        match progress {
+            Some(progress) => println!(
+                "Last:  {} ({} attempt(s))",
+                progress.attempt_rating, progress.number_of_attempts
+            ),
+            None => println!("Last:  not yet attempted"),
+        }
+        if let Some(note) = fetch_problem_note(pool, problem.id).await? {
+            println!("Note:  {}", note);
+        }
+
+        let rating = loop {
+            print!("Rating 1-5 (1=ShortFail ... 5=Easy), or q to quit: ");
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                // EOF (Ctrl-D): end the session cleanly.
+                println!();
+                return Ok(reviewed);
+            }
+            let input = line.trim();
+            if input.eq_ignore_ascii_case("q") {
+                return Ok(reviewed);
+            }
+            match input.parse::<u8>().ok().and_then(AttemptRating::from_score) {
+                Some(rating) => break rating,
+                None => println!("Please enter a number from 1 to 5."),
             }
+        };
+
+        print!("Note (optional, Enter to skip): ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        let at_eof = stdin.lock().read_line(&mut line)? == 0;
+        let note = Some(line.trim().to_string()).filter(|note| !note.is_empty());
+
+        let details = AttemptDetails {
+            note,
+            ..Default::default()
+        };
+        log_attempt(pool, problem.id, rating, Some(today), details).await?;
+        after_attempt(config, pool, problem.id).await;
+        reviewed += 1;
+        println!("Logged {} for #{}.", rating, problem.id);
+        if at_eof {
+            println!();
+            return Ok(reviewed);
         }
-    } else {
-        // If no command or flag was given, print help.
-        Cli::parse_from(["", "--help"]);
     }
+}
 
-    Ok(())
+/// Prints the text form of `stats`, as shared by `stats` and `--progress`.
+fn print_stats(stats: &Stats) {
+    println!("Total Problems Attempted: {}", stats.total_attempted);
+    for (rating, count) in stats.by_rating.iter().rev() {
+        println!("  - {:<10}: {}", rating, count);
+    }
+    let by_difficulty = &stats.by_difficulty;
+    println!("By Difficulty:");
+    println!("  - {:<10}: {}", "Easy", by_difficulty.easy);
+    println!("  - {:<10}: {}", "Medium", by_difficulty.medium);
+    println!("  - {:<10}: {}", "Hard", by_difficulty.hard);
+    if by_difficulty.unknown > 0 {
+        println!("  - {:<10}: {}", "Unknown", by_difficulty.unknown);
+    }
+    if let Some(average) = stats.average_attempts() {
+        println!("Average Attempts per Problem: {:.1}", average);
+    }
+    println!("Due for Review: {}", stats.due);
+    println!("Current Streak: {} day(s)", stats.streaks.current);
+    println!("Longest Streak: {} day(s)", stats.streaks.longest);
 }
 
-pub mod db;
-pub mod problem_attempts;
-pub mod problem_bank;
-pub mod problem_bank_populator;
-pub mod problems;
+/// Opens `$VISUAL` (or `$EDITOR`) on a temporary file holding `initial` and
+/// returns what was saved.
+fn edit_in_editor(initial: &str) -> anyhow::Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .context("No note text given and neither $VISUAL nor $EDITOR is set.")?;
+    let path = std::env::temp_dir().join(format!("track-note-{}.txt", std::process::id()));
+    fs::write(&path, initial).with_context(|| format!("Failed to write '{}'", path.display()))?;
 
-use crate::problem_bank_populator::populate_problem_bank;
-use anyhow::Context;
-use clap::Parser;
-use clap::Subcommand;
-use db::*;
-use problem_attempts::AttemptRating;
-use problem_attempts::ProblemAttempt;
-use problems::Problem;
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
-use sqlx::types::chrono::NaiveDate;
-use std::collections::HashMap;
+    // Editors are often configured with arguments, e.g. "code --wait".
+    let mut words = editor.split_whitespace();
+    let program = words.next().expect("editor is not blank");
+    let edited = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", editor))
+        .and_then(|status| {
+            if !status.success() {
+                anyhow::bail!(
+                    "Editor '{}' exited with {}; the note was not saved.",
+                    editor,
+                    status
+                );
+            }
+            fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read '{}'", path.display()))
+        });
+    let _ = fs::remove_file(&path);
+    edited
+}
+
+/// Reports an attempt removed by `delete-attempt` or `undo`, and where the
+/// problem's progress stands now.
+async fn print_removed_attempt(
+    pool: &SqlitePool,
+    problem_id: i64,
+    removed: &HistoryEntry,
+) -> anyhow::Result<()> {
+    say!(
+        "Removed attempt #{} at problem {} ({} on {}).",
+        removed.attempt_number,
+        problem_id,
+        removed.rating,
+        removed.attempted_on
+    );
+    match fetch_progress(pool, problem_id).await? {
+        Some(progress) => say!(
+            "Progress is back to {} on {} ({} attempt(s)).",
+            progress.attempt_rating,
+            progress.last_attempted,
+            progress.number_of_attempts
+        ),
+        None => say!("Problem {} is unattempted again.", problem_id),
+    }
+    Ok(())
+}
+
+/// Prints the per-week and per-difficulty tables of `track stats`.
+fn print_stats_breakdowns(report: &StatsReport) {
+    if !report.weeks.is_empty() {
+        println!("\nCompletion by Week:");
+        for row in &report.weeks {
+            let label = match row.week {
+                Some(week_num) => format!("Week {}", week_num),
+                None => "Unassigned".to_string(),
+            };
+            println!(
+                "  - {:<10}: {:>3}/{:<3} ({:.0}%)",
+                label,
+                row.attempted,
+                row.total,
+                row.percent()
+            );
+        }
+    }
+
+    if !report.difficulties.is_empty() {
+        println!("\nLatest Rating by Difficulty:");
+        println!(
+            "  {:<10} {:>5} {:>5} {:>5} {:>8} {:>9} {:>8}",
+            "", "Easy", "Hard", "Messy", "LongFail", "ShortFail", "Avg Att"
+        );
+        for row in &report.difficulties {
+            let label = match row.difficulty {
+                Some(diff) => format!("{:?}", diff),
+                None => "Unrated".to_string(),
+            };
+            println!(
+                "  {:<10} {:>5} {:>5} {:>5} {:>8} {:>9} {:>8.1}",
+                label,
+                row.easy,
+                row.hard,
+                row.messy,
+                row.long_fail,
+                row.short_fail,
+                row.average_attempts().unwrap_or(0.0)
+            );
+        }
+    }
+
+    if !report.tags.is_empty() {
+        println!("\nLatest Rating by Tag:");
+        println!(
+            "  {:<16} {:>9} {:>5} {:>5} {:>5} {:>8} {:>9}",
+            "", "Attempted", "Easy", "Hard", "Messy", "LongFail", "ShortFail"
+        );
+        for row in &report.tags {
+            println!(
+                "  {:<16} {:>9} {:>5} {:>5} {:>5} {:>8} {:>9}",
+                row.tag,
+                format!("{}/{}", row.attempted, row.total),
+                row.easy,
+                row.hard,
+                row.messy,
+                row.long_fail,
+                row.short_fail
+            );
+        }
+    }
+
+    if !report.leeches.is_empty() {
+        println!("\nLeeches (failed 4+ times):");
+        for leech in &report.leeches {
+            let state = if leech.suspended {
+                "suspended"
+            } else {
+                "unsuspended"
+            };
+            println!(
+                "  - #{:<5} {:<40} {} failures, {}",
+                leech.problem_id, leech.name, leech.failures, state
+            );
+        }
+        println!("Run `track unsuspend <id>` to bring one back.");
+    }
+}
+
+/// Runs the hook configured for `event`. A failing hook only gets a warning,
+/// since what it was told about has already happened.
+async fn run_hook(hooks: &Hooks, event: HookEvent, payload: &impl serde::Serialize) {
+    if let Err(e) = hooks.run(event, payload).await {
+        eprintln!("Warning: {:#}", e);
+    }
+}
+
+/// Tells the on_attempt hook and the webhook in `config` about the attempt
+/// just logged at `problem_id`.
+async fn after_attempt(config: &Config, pool: &SqlitePool, problem_id: i64) {
+    announce_attempt(
+        &config.hooks,
+        config.webhook_url.as_deref(),
+        pool,
+        problem_id,
+    )
+    .await;
+}
+
+/// `after_attempt` for attempts logged in bulk: once per problem, in the
+/// order they first appear, since the event describes a problem's latest
+/// attempt and would repeat for the rest.
+async fn after_attempts(
+    config: &Config,
+    pool: &SqlitePool,
+    problem_ids: impl IntoIterator<Item = i64>,
+) {
+    let mut seen = HashSet::new();
+    for problem_id in problem_ids {
+        if seen.insert(problem_id) {
+            after_attempt(config, pool, problem_id).await;
+        }
+    }
+}
+
+/// Tells the on_due hook about the reviews due, if there are any.
+async fn run_due_hook(hooks: &Hooks, due: &[ScheduledReview]) {
+    if !due.is_empty() {
+        let payload = serde_json::json!({ "count": due.len(), "problems": due });
+        run_hook(hooks, HookEvent::Due, &payload).await;
+    }
+}
+
+/// Reads the problems of a `--from-csv` file, or of a `--from-markdown` one
+/// unless `is_csv`.
+fn read_imported_bank(
+    path: &Path,
+    is_csv: bool,
+    url_column: Option<usize>,
+) -> anyhow::Result<Vec<ProblemBankProblem>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if is_csv {
+        problems_from_csv(&contents, url_column)
+    } else {
+        Ok(problems_from_markdown(&contents))
+    }
+}
+
+/// Opens the bank file `file` names, as `resolve_bank_path` finds it, or the
+/// preset of that name if there's no such file.
+fn open_bank_or_preset(file: &Path, static_dir: &Path) -> anyhow::Result<BankFile> {
+    let path = resolve_bank_path(file, static_dir);
+    if !path.exists()
+        && let Ok(preset) = find_preset(&file.to_string_lossy())
+    {
+        return BankFile::parse(Path::new(preset.name), preset.contents);
+    }
+    BankFile::open(&path)
+}
+
+/// Prints `diff_banks(old, new)`: a line per added (+), removed (-) and
+/// changed (~) problem, then the totals.
+fn print_bank_diff(old: &BankFile, new: &BankFile) {
+    let diff = diff_banks(old, new);
+    let describe = |bank: &BankFile, index: usize| {
+        let (id, _) = bank.key(index);
+        match id {
+            0 => bank.name(index).to_string(),
+            id => format!("#{} {}", id, bank.name(index)),
+        }
+    };
+    for &index in &diff.removed {
+        println!("- {}", describe(old, index));
+    }
+    for &index in &diff.added {
+        let week = bank_week_suffix(new.position(index).1);
+        println!("+ {}{}", describe(new, index), week);
+    }
+    for changed in &diff.changed {
+        println!(
+            "~ {}: {}",
+            describe(new, changed.new),
+            changed.changes.join(", ")
+        );
+    }
+    if diff.is_empty() {
+        println!(
+            "{} and {} hold the same problems.",
+            old.path().display(),
+            new.path().display()
+        );
+    } else {
+        println!(
+            "{} added, {} removed, {} changed.",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len()
+        );
+    }
+}
+
+/// " (week N)", or nothing for a problem without a week.
+fn bank_week_suffix(week: Option<i64>) -> String {
+    week.map(|week| format!(" (week {})", week))
+        .unwrap_or_default()
+}
+
+/// Fetches the list page at `url` and writes its problems as a bank file:
+/// `out`, else one named after the page in `static_dir`.
+async fn import_bank_from_url(
+    url: &str,
+    out: Option<&Path>,
+    per_week: u32,
+    force: bool,
+    static_dir: &Path,
+) -> anyhow::Result<()> {
+    say!("Fetching {}...", url);
+    let page = fetch_page(url).await?;
+    let mut problems = problems_from_html(&page);
+    anyhow::ensure!(
+        !problems.is_empty(),
+        "No LeetCode problem links were found at {}. If the page loads its list with JavaScript, \
+         save the list as Markdown and build it with --from-markdown.",
+        url
+    );
+    assign_weeks(&mut problems, per_week);
+
+    let file_name = bank_file_name(url);
+    let path = out.map_or_else(|| static_dir.join(&file_name), Path::to_path_buf);
+    anyhow::ensure!(
+        force || !path.exists(),
+        "{} already exists. Pass --force to overwrite it.",
+        path.display()
+    );
+    let mut bank = BankFile::new(&path);
+    for problem in &problems {
+        bank.push_entry(problem);
+    }
+    bank.save()?;
+
+    let weeks = problems.last().and_then(|p| p.week).unwrap_or_default();
+    println!(
+        "Wrote {} problems in {} weeks to {}.",
+        problems.len(),
+        weeks,
+        path.display()
+    );
+    let build_name = if out.is_none() {
+        file_name
+    } else {
+        path.display().to_string()
+    };
+    say!("Build it with `track --build {}`.", build_name);
+    Ok(())
+}
+
+/// Prints what `check_bank` finds in `file`, which is looked for in
+/// `static_dir` if it isn't a path to a file. Returns whether the bank would
+/// build.
+fn validate_bank_file(file: &Path, static_dir: &Path) -> anyhow::Result<bool> {
+    let path = resolve_bank_path(file, static_dir);
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read bank file {}", path.display()))?;
+    let check =
+        check_bank(&contents).with_context(|| format!("Couldn't read {}", path.display()))?;
+
+    say!(
+        "Checked {} problem(s) in {}.",
+        check.entries,
+        path.display()
+    );
+    if !check.errors.is_empty() {
+        println!("{} error(s), which would stop --build:", check.errors.len());
+        for error in &check.errors {
+            println!("  - {}", error);
+        }
+    }
+    if !check.warnings.is_empty() {
+        println!("{} warning(s):", check.warnings.len());
+        for warning in &check.warnings {
+            println!("  - {}", warning);
+        }
+    }
+    if check.errors.is_empty() && check.warnings.is_empty() {
+        println!("No problems found.");
+    }
+    Ok(check.is_valid())
+}
+
+/// Asks for a 1-5 rating until one is given. Returns `None` if Enter is
+/// pressed on its own.
+fn prompt_for_rating() -> io::Result<Option<AttemptRating>> {
+    loop {
+        print!("Rating 1-5 (1=ShortFail ... 5=Easy), or Enter to skip: ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line)?;
+        let input = line.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        match input.parse::<u8>().ok().and_then(AttemptRating::from_score) {
+            Some(rating) => return Ok(Some(rating)),
+            None => println!("Please enter a number from 1 to 5."),
+        }
+    }
+}
+
+/// Counts down each phase, with a desktop notification as each one ends.
+/// Returns the time spent in work phases; Ctrl-C stops early, keeping the
+/// work done so far.
+async fn run_pomodoro(phases: &[Phase]) -> anyhow::Result<std::time::Duration> {
+    let countdown = io::stdout().is_terminal() && !QUIET.load(Ordering::Relaxed);
+    let cycles = phases.iter().map(|phase| phase.cycle).max().unwrap_or(0);
+    let mut focused = std::time::Duration::ZERO;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    for (index, phase) in phases.iter().enumerate() {
+        let label = match phase.kind {
+            PhaseKind::Work => format!("Work {}/{}", phase.cycle, cycles),
+            PhaseKind::Break => "Break".to_string(),
+        };
+        if !countdown {
+            say!("{} ({} min)", label, phase.duration.as_secs() / 60);
+        }
+        let started = tokio::time::Instant::now();
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+        while started.elapsed() < phase.duration {
+            if countdown {
+                let left = phase.duration.saturating_sub(started.elapsed());
+                print!("\r{:<10} {} left ", label, format_countdown(left));
+                io::stdout().flush()?;
+            }
+            tokio::select! {
+                _ = ticker.tick() => {}
+                result = &mut ctrl_c => {
+                    result.context("Failed to listen for Ctrl-C")?;
+                    if phase.kind == PhaseKind::Work {
+                        focused += started.elapsed();
+                    }
+                    if countdown {
+                        println!();
+                    }
+                    say!("Stopped early.");
+                    return Ok(focused);
+                }
+            }
+        }
+        if phase.kind == PhaseKind::Work {
+            focused += phase.duration;
+        }
+        if countdown {
+            println!();
+        }
+
+        let message = match phases.get(index + 1).map(|next| next.kind) {
+            Some(PhaseKind::Break) => "Time for a break.",
+            Some(PhaseKind::Work) => "Back to work.",
+            None => "All cycles done.",
+        };
+        say!("{}", message);
+        // A missing notifier shouldn't interrupt the session.
+        let _ = notify_desktop("track pomodoro", message);
+    }
+    Ok(focused)
+}
+
+/// How many problems are named in `track notify`'s notification.
+const NOTIFY_NAMES: usize = 3;
+
+/// The summary and body of `track notify`'s notification for the problems
+/// due, most overdue first.
+fn due_notification(names: &[&str]) -> (String, String) {
+    let summary = match names.len() {
+        1 => "1 review due".to_string(),
+        n => format!("{} reviews due", n),
+    };
+    let mut body = names
+        .iter()
+        .take(NOTIFY_NAMES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > NOTIFY_NAMES {
+        body.push_str(&format!(" and {} more", names.len() - NOTIFY_NAMES));
+    }
+    (summary, body)
+}
+
+/// Shows a desktop notification with notify-send on Linux or osascript on
+/// macOS.
+fn notify_desktop(summary: &str, body: &str) -> anyhow::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = std::process::Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            quote(body),
+            quote(summary)
+        ));
+        command
+    } else if cfg!(windows) {
+        anyhow::bail!("Desktop notifications aren't supported on Windows.");
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        command.arg(summary).arg(body);
+        command
+    };
+
+    let status = command.status().with_context(|| {
+        format!(
+            "Failed to send a desktop notification. Is {} installed?",
+            command.get_program().to_string_lossy()
+        )
+    })?;
+    if !status.success() {
+        anyhow::bail!("The desktop notification could not be shown ({}).", status);
+    }
+    Ok(())
+}
+
+/// Opens `url` with the platform's default handler.
+fn open_in_browser(url: &str) -> anyhow::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        // The empty string is `start`'s window title, so the URL isn't taken as one.
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+
+    let status = command
+        .arg(url)
+        .status()
+        .context("Failed to launch a browser")?;
+    if !status.success() {
+        anyhow::bail!(
+            "The browser could not be opened ({}). Visit {} instead.",
+            status,
+            url
+        );
+    }
+    Ok(())
+}
+
+/// Prints one line of the first-attempt Easy rate breakdown.
+fn print_first_attempt_rate(label: &str, rate: &FirstAttemptRate) {
+    println!(
+        "  - {:<10}: {}/{} ({:.0}%)",
+        label,
+        rate.easy,
+        rate.attempted,
+        rate.percent().unwrap_or(0.0)
+    );
+}
+
+/// Writes the completion script for `shell` to `out`. Bash, zsh and fish
+/// scripts get a hook that completes problem IDs by running
+/// `track __complete-ids`.
+fn print_completions(shell: Shell, out: &mut dyn Write) -> io::Result<()> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    let id_commands = id_subcommands(&command);
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, name, &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    let script = match shell {
+        Shell::Bash => {
+            // The problem comes straight after the subcommand.
+            let hook = format!(
+                r#"
+_track_with_ids() {{
+    if [[ ${{COMP_CWORD}} -eq 2 ]]; then
+        case "${{COMP_WORDS[1]}}" in
+            {})
+                COMPREPLY=( $(compgen -W "$(track __complete-ids 2>/dev/null | cut -f1)" -- "${{COMP_WORDS[2]}}") )
+                return 0
+                ;;
+        esac
+    fi
+    _track "$@"
+}}
+"#,
+                id_commands.join("|")
+            );
+            script.replace("complete -F _track ", "complete -F _track_with_ids ") + &hook
+        }
+        Shell::Zsh => {
+            // Names can hold colons, which _describe would take as the end
+            // of the ID.
+            let hook = r#"(( $+functions[_track_ids] )) ||
+_track_ids() {
+    local -a ids
+    local line
+    for line in ${(f)"$(track __complete-ids 2>/dev/null)"}; do
+        ids+=("${line%%$'\t'*}:${${line#*$'\t'}//:/\\:}")
+    done
+    _describe -t ids 'problem' ids
+}
+
+"#;
+            script
+                .lines()
+                .map(|line| {
+                    let line = if line.starts_with("':id -- ") {
+                        line.replace(":_default'", ":_track_ids'")
+                    } else {
+                        line.to_string()
+                    };
+                    // The hook has to exist before the script's first call.
+                    if line.starts_with(r#"if [ "$funcstack[1]" = "_track" ]"#) {
+                        format!("{}{}\n", hook, line)
+                    } else {
+                        line + "\n"
+                    }
+                })
+                .collect()
+        }
+        Shell::Fish => {
+            let mut script = script.into_owned();
+            for command in &id_commands {
+                script.push_str(&format!(
+                    "complete -c track -n \"__fish_track_using_subcommand {}\" -f -a \"(track __complete-ids 2>/dev/null)\"\n",
+                    command
+                ));
+            }
+            script
+        }
+        _ => script.into_owned(),
+    };
+    out.write_all(script.as_bytes())
+}
+
+/// The subcommands whose first positional argument is a problem.
+fn id_subcommands(command: &clap::Command) -> Vec<String> {
+    command
+        .get_subcommands()
+        .filter(|sub| {
+            sub.get_positionals()
+                .next()
+                .is_some_and(|arg| arg.get_id() == "id")
+        })
+        .map(|sub| sub.get_name().to_string())
+        .collect()
+}
+
+/// Converts the 1-5 integer rating from the CLI to the AttemptRating enum.
+fn map_rating(rating_num: u8) -> AttemptRating {
+    AttemptRating::from_score(rating_num).expect("clap restricts ratings to 1..=5")
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    run().await.map_err(explain_lock_error)
+}
+
+async fn run() -> anyhow::Result<()> {
+    // --- Parse CLI commands ---
+    let cli = Cli::parse();
+    QUIET.store(cli.quiet, Ordering::Relaxed);
+    let color_choice = if cli.no_color {
+        ColorChoice::Never
+    } else {
+        cli.color
+    };
+    let color = use_color(color_choice);
+
+    // Completions don't touch the database, so handle them before opening it.
+    if let Some(Commands::Completions { shell }) = cli.command {
+        print_completions(shell, &mut io::stdout())?;
+        return Ok(());
+    }
+    // Nor does validating, importing, comparing or rearranging a bank file,
+    // which should work before the bank is built.
+    if let Some(Commands::Bank { command }) = &cli.command {
+        match command {
+            BankCommand::Validate { file } => {
+                if !validate_bank_file(file, &cli.static_dir)? {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            BankCommand::ImportUrl {
+                url,
+                out,
+                per_week,
+                force,
+            } => {
+                import_bank_from_url(url, out.as_deref(), *per_week, *force, &cli.static_dir)
+                    .await?;
+                return Ok(());
+            }
+            BankCommand::Reorder {
+                file,
+                problem,
+                before,
+                after,
+            } => {
+                let mut bank = BankFile::open(&resolve_bank_path(file, &cli.static_dir))?;
+                let target = before.as_deref().or(after.as_deref());
+                if let (Some(problem), Some(target)) = (problem, target) {
+                    let index = bank.lookup(problem)?;
+                    let target = bank.lookup(target)?;
+                    anyhow::ensure!(
+                        index != target,
+                        "Can't move {} next to itself.",
+                        bank.name(index)
+                    );
+                    let name = bank.name(index).to_string();
+                    let target_name = bank.name(target).to_string();
+                    bank.move_entry(index, target, after.is_some());
+                    let index = bank.lookup(problem)?;
+                    let (order, week) = bank.position(index);
+                    println!(
+                        "Moved {} {} {}: it's now #{}{}.",
+                        name,
+                        if after.is_some() { "after" } else { "before" },
+                        target_name,
+                        order,
+                        week.map(|week| format!(" in week {}", week))
+                            .unwrap_or_default()
+                    );
+                } else {
+                    let changed = bank.renumber();
+                    println!("Renumbered {} of {} problems.", changed, bank.len());
+                }
+                bank.save()?;
+                say!("Run `track --build {}` to apply it.", file.display());
+                return Ok(());
+            }
+            BankCommand::Diff { old, new } => {
+                let old = open_bank_or_preset(old, &cli.static_dir)?;
+                let new = open_bank_or_preset(new, &cli.static_dir)?;
+                print_bank_diff(&old, &new);
+                return Ok(());
+            }
+            BankCommand::Merge { files, out, force } => {
+                let banks = files
+                    .iter()
+                    .map(|file| open_bank_or_preset(file, &cli.static_dir))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                anyhow::ensure!(
+                    *force || !out.exists(),
+                    "{} already exists. Pass --force to overwrite it.",
+                    out.display()
+                );
+                let (merged, duplicates) = merge_banks(out, &banks);
+                merged.save()?;
+                println!(
+                    "Wrote {} problems from {} banks to {}, leaving out {} duplicate(s).",
+                    merged.len(),
+                    banks.len(),
+                    out.display(),
+                    duplicates
+                );
+                return Ok(());
+            }
+            BankCommand::Autoweek { file, per_week } => {
+                let mut bank = BankFile::open(&resolve_bank_path(file, &cli.static_dir))?;
+                let changed = bank.assign_weeks(*per_week);
+                bank.save()?;
+                println!(
+                    "Put {} problems into {} weeks of {}; {} changed week.",
+                    bank.len(),
+                    bank.weeks(),
+                    per_week,
+                    changed
+                );
+                say!("Run `track --build {}` to apply it.", file.display());
+                return Ok(());
+            }
+            _ => {}
+        }
+    }
+
+    // --- Configuration ---
+    let mut config = Config::load()?;
+    if cli.no_webhook {
+        config.webhook_url = None;
+    }
+    schedule::install(config.schedule.clone());
+
+    // --- Database Setup ---
+    let db_path = match &cli.db {
+        Some(path) => path.clone(),
+        None => config.db_path()?,
+    };
+    // Completing an ID shouldn't create a database as a side effect.
+    if matches!(cli.command, Some(Commands::CompleteIds)) && !db_path.exists() {
+        return Ok(());
+    }
+    if let Some(dir) = db_path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create database directory '{}'", dir.display()))?;
+    }
+    let pool = init_pool(&format!("sqlite:{}", db_path.display())).await?;
+
+    // --- Handle top-level flags first ---
+    let preset = cli.preset.as_deref().map(find_preset).transpose()?;
+    let import_path = cli
+        .from_csv
+        .as_deref()
+        .or(cli.from_markdown.as_deref())
+        .map(|file| resolve_bank_path(file, &cli.static_dir));
+    let import_name = import_path
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned());
+    if let Some(bank_name) = preset
+        .map(|p| p.name.to_string())
+        .or(cli.build)
+        .or(import_name)
+    {
+        say!("\n--- Starting Problem Bank Population ---");
+        let deck = cli.deck.unwrap_or_else(|| match preset {
+            Some(preset) => preset.deck.to_string(),
+            None => default_deck_name(&bank_name),
+        });
+        let options = PopulateOptions {
+            concurrency: cli.concurrency,
+            resolve_attempts: cli.resolve_attempts,
+            dry_run: cli.dry_run,
+            prune: cli.prune,
+            fetch_meta: cli.fetch_meta,
+            deck: Some(deck.clone()),
+            show_progress: io::stdout().is_terminal() && !cli.json && !cli.quiet,
+            quiet: cli.quiet,
+            static_dir: cli.static_dir,
+            trust_ids: false,
+        };
+        let populated = match (preset, &import_path) {
+            (Some(preset), _) => populate_preset(&pool, preset, &options).await,
+            (None, Some(path)) => {
+                match read_imported_bank(path, cli.from_csv.is_some(), cli.url_column) {
+                    Ok(problems) => {
+                        populate_imported_bank(&pool, &bank_name, &problems, &options).await
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            (None, None) => populate_problem_bank(&pool, &bank_name, &options).await,
+        };
+        if let Err(e) = populated {
+            eprintln!("Error during population: {:?}", e);
+            std::process::exit(1);
+        } else {
+            say!("--- Population Task Finished ---");
+        }
+        if !cli.dry_run {
+            let payload = serde_json::json!({
+                "bank": bank_name,
+                "deck": deck,
+                "problems": count_problems(&pool, Some(&deck), None).await?,
+            });
+            run_hook(&config.hooks, HookEvent::Build, &payload).await;
+        }
+        return Ok(());
+    }
+
+    let deck = cli.deck.as_deref();
+
+    if cli.progress {
+        let progress_list = fetch_all_progress(&pool, cli.since, deck).await?;
+        if cli.json {
+            let dates = fetch_attempt_dates(&pool).await?;
+            let stats = compute_stats(&progress_list, &dates, Local::now().date_naive());
+            let problems: Vec<ProgressEntry> = progress_list
+                .iter()
+                .map(|progress| ProgressEntry {
+                    progress,
+                    status: progress.status(),
+                })
+                .filter(|entry| cli.status.is_none_or(|wanted| wanted == entry.status))
+                .collect();
+            return print_json(&serde_json::json!({
+                "since": cli.since,
+                "problems": problems,
+                "stats": stats,
+            }));
+        }
+
+        say!("\n--- Current Progress ---");
+        if progress_list.is_empty() {
+            println!("No problems have been attempted yet. Use the 'attempt' command to start!");
+        } else {
+            let mut table =
+                Table::new(&["ID", "Name", "Rating", "Attempts", "Time", "Status", "Note"]);
+            for item in &progress_list {
+                let status = item.status();
+                if cli.status.is_some_and(|wanted| wanted != status) {
+                    continue;
+                }
+                let time = match item.duration_minutes {
+                    Some(minutes) => format!("{}m", minutes),
+                    None => "-".to_string(),
+                };
+                table.push(vec![
+                    format!("#{}", item.problem_id).into(),
+                    item.name.as_str().into(),
+                    Cell::colored(item.attempt_rating, rating_color(item.attempt_rating)),
+                    item.number_of_attempts.to_string().into(),
+                    time.into(),
+                    status.to_string().into(),
+                    item.note.as_deref().unwrap_or_default().into(),
+                ]);
+            }
+            match cli.status {
+                Some(wanted) if table.is_empty() => {
+                    println!("No problems are currently '{}'.", wanted)
+                }
+                _ => print!("{}", table.render(color)),
+            }
+            let dates = fetch_attempt_dates(&pool).await?;
+            let stats = compute_stats(&progress_list, &dates, Local::now().date_naive());
+            match cli.since {
+                Some(since) => say!("\n--- Statistics (since {}) ---", since),
+                None => say!("\n--- Statistics ---"),
+            }
+            print_stats(&stats);
+            let weights = config.scoring;
+            let problems = fetch_all_problems(&pool, deck).await?;
+            let score = compute_score(&progress_list, &problems, &weights);
+            println!(
+                "Score: {} / {} points {}",
+                score.earned,
+                score.available,
+                progress_bar(score.earned as u64, score.available as u64, 20)
+            );
+            println!("By Language:");
+            for (language, count) in fetch_language_counts(&pool, cli.since, deck).await? {
+                println!("  - {:<10}: {}", language, count);
+            }
+            let rates = fetch_first_attempt_rates(&pool, cli.since, deck).await?;
+            if !rates.is_empty() {
+                println!("First-Attempt Easy Rate:");
+                let overall = FirstAttemptRate {
+                    difficulty: None,
+                    attempted: rates.iter().map(|r| r.attempted).sum(),
+                    easy: rates.iter().map(|r| r.easy).sum(),
+                };
+                for rate in &rates {
+                    let label = match rate.difficulty {
+                        Some(diff) => format!("{:?}", diff),
+                        None => "Unrated".to_string(),
+                    };
+                    print_first_attempt_rate(&label, rate);
+                }
+                print_first_attempt_rate("Overall", &overall);
+            }
+        }
+        return Ok(());
+    }
+
+    // --- Handle Subcommands ---
+    if let Some(command) = cli.command {
+        match command {
+            Commands::Next {
+                long,
+                strategy,
+                review,
+                tag,
+                weakest,
+                difficulty,
+                week,
+                count,
+            } => {
+                let today = Local::now().date_naive();
+                let filter = NextFilter {
+                    tag: tag.as_deref(),
+                    difficulty,
+                    week,
+                };
+                if let Some(count) = count {
+                    let candidates =
+                        fetch_unattempted_problems(&pool, deck, filter, strategy, count).await?;
+                    if cli.json {
+                        print_json(&candidates)?;
+                    } else {
+                        for problem in &candidates {
+                            let difficulty = problem
+                                .difficulty
+                                .map(|d| format!(" ({:?})", d))
+                                .unwrap_or_default();
+                            println!("#{:<5} {}{}", problem.id, problem.name, difficulty);
+                        }
+                    }
+                    if candidates.is_empty() {
+                        std::process::exit(EXIT_NOTHING_LEFT);
+                    }
+                    return Ok(());
+                }
+                let next = if weakest {
+                    match fetch_weakest_tag_problem(&pool, today, deck, strategy).await {
+                        Ok(Some((topic, problem))) => {
+                            if long && !cli.json {
+                                println!(
+                                    "\nWeakest topic: {} ({:.0}% struggle rate over {} attempted)",
+                                    topic.tag,
+                                    topic.struggle_rate().unwrap_or(0.0) * 100.0,
+                                    topic.attempted
+                                );
+                            }
+                            Ok(Some(problem))
+                        }
+                        Ok(None) => {
+                            eprintln!(
+                                "No tagged problems have been attempted yet; picking the usual next problem."
+                            );
+                            fetch_next_problem(&pool, today, deck, NextFilter::default(), strategy)
+                                .await
+                        }
+                        Err(e) => Err(e),
+                    }
+                } else if review {
+                    fetch_next_problem(&pool, today, deck, filter, strategy).await
+                } else {
+                    fetch_next_unattempted_problem(&pool, deck, filter, strategy).await
+                };
+                match next? {
+                    None if cli.json => {
+                        print_json(&None::<Problem>)?;
+                        std::process::exit(EXIT_NOTHING_LEFT);
+                    }
+                    Some(problem) if cli.json => return print_json(&problem),
+                    Some(problem) => {
+                        if long {
+                            let progress = fetch_progress(&pool, problem.id).await?;
+                            let attempts_so_far =
+                                progress.as_ref().map_or(0, |p| p.number_of_attempts);
+                            let review_state = progress
+                                .as_ref()
+                                .map_or(ReviewState::NEW, ProblemAttempt::review_state);
+                            if attempts_so_far == 0 {
+                                say!("\n--- Next Problem to Attempt ---");
+                            } else {
+                                say!(
+                                    "\n--- Next Review ({} attempt(s) so far) ---",
+                                    attempts_so_far
+                                );
+                            }
+                            println!("Order: #{}", problem.order);
+                            println!("Name:  {}", problem.name);
+                            println!("ID:    {}", problem.id);
+                            if let Some(diff) = problem.difficulty {
+                                println!("Diff:  {:?}", diff);
+                            }
+                            if let Some(url) = &problem.url {
+                                println!("URL:   {}", url);
+                            }
+                            let level = fetch_priority_level(&pool, problem.id).await?;
+                            if level != PriorityLevel::Normal {
+                                println!("Priority: {}", level);
+                            }
+                            print_streak_warning(&fetch_attempt_dates(&pool).await?, today);
+                            println!("\nIf you rate it today, it comes back:");
+                            for rating in AttemptRating::ALL {
+                                match preview_next_attempt_date(rating, review_state, today) {
+                                    Some(next) => {
+                                        println!("  {} {:<10} {}", rating.score(), rating, next)
+                                    }
+                                    None => println!("  {} {:<10} never", rating.score(), rating),
+                                }
+                            }
+                        } else {
+                            println!("{}", problem.id);
+                        }
+                    }
+                    None => {
+                        if long && !filter.allows_all() {
+                            say!("\nNo unattempted problems match those filters.");
+                        } else if long {
+                            say!("\n🎉 Congratulations! You have attempted all problems!");
+                        }
+                        std::process::exit(EXIT_NOTHING_LEFT);
+                    }
+                }
+            }
+            Commands::Attempt {
+                id,
+                rating,
+                date,
+                lang,
+                minutes,
+                note,
+            } => {
+                let id = resolve_problem(&pool, &id).await?;
+                say!("\n--- Logging attempt for problem {} ---", id);
+                let attempt_rating = map_rating(rating);
+                let language = lang
+                    .map(|l| l.trim().to_lowercase())
+                    .filter(|l| !l.is_empty());
+
+                let details = AttemptDetails {
+                    language,
+                    duration_minutes: minutes,
+                    note: note.filter(|n| !n.trim().is_empty()),
+                };
+                if log_attempt(&pool, id, attempt_rating, date, details).await? {
+                    say!("Logged first attempt.");
+                } else {
+                    say!("Updated existing progress.");
+                }
+                say!(
+                    "Successfully logged attempt for problem {} with rating: {}",
+                    id,
+                    attempt_rating
+                );
+                after_attempt(&config, &pool, id).await;
+            }
+            Commands::Start { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let session = start_session(&pool, id, Local::now().naive_local()).await?;
+                say!(
+                    "Started timing problem {} at {}. Run `track stop --rating <1-5>` when you're done.",
+                    id,
+                    session.started_at.format("%H:%M")
+                );
+            }
+            Commands::Stop {
+                rating,
+                lang,
+                note,
+                discard,
+            } => {
+                let now = Local::now().naive_local();
+                let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+                let session = stop_session(&mut tx, now, None)
+                    .await?
+                    .context("No timer is running. Start one with `track start <id>`.")?;
+                let minutes = session.minutes(now);
+                if discard {
+                    tx.commit().await.context("Failed to stop the session.")?;
+                    say!(
+                        "Discarded {} minute(s) on problem {}.",
+                        minutes,
+                        session.problem_id
+                    );
+                    return Ok(());
+                }
+                let attempt_rating =
+                    map_rating(rating.expect("clap requires a rating unless discarding"));
+                let details = AttemptDetails {
+                    language: lang
+                        .map(|l| l.trim().to_lowercase())
+                        .filter(|l| !l.is_empty()),
+                    duration_minutes: Some(minutes),
+                    note: note.filter(|n| !n.trim().is_empty()),
+                };
+                log_attempt(
+                    &mut *tx,
+                    session.problem_id,
+                    attempt_rating,
+                    Some(now.date()),
+                    details,
+                )
+                .await?;
+                tx.commit()
+                    .await
+                    .context("Failed to log the timed attempt.")?;
+                say!(
+                    "Logged {} for problem {} after {} minute(s).",
+                    attempt_rating,
+                    session.problem_id,
+                    minutes
+                );
+                after_attempt(&config, &pool, session.problem_id).await;
+            }
+            Commands::Pomodoro {
+                id,
+                work,
+                break_minutes,
+                cycles,
+                rating,
+            } => {
+                let id = resolve_problem(&pool, &id).await?;
+                start_session(&pool, id, Local::now().naive_local()).await?;
+                let minutes = |m: u64| std::time::Duration::from_secs(m * 60);
+                let phases = plan_phases(cycles, minutes(work), minutes(break_minutes));
+                let focused = run_pomodoro(&phases).await?;
+
+                let rating = match rating {
+                    Some(rating) => Some(map_rating(rating)),
+                    None if io::stdin().is_terminal() => prompt_for_rating()?,
+                    None => None,
+                };
+                let now = Local::now().naive_local();
+                let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+                let session = stop_session(&mut tx, now, Some(focused.as_secs() as i64))
+                    .await?
+                    .context("The pomodoro session was stopped from elsewhere.")?;
+                let minutes = session.minutes(now);
+                match rating {
+                    Some(rating) => {
+                        let details = AttemptDetails {
+                            duration_minutes: Some(minutes),
+                            ..Default::default()
+                        };
+                        log_attempt(&mut *tx, id, rating, Some(now.date()), details).await?;
+                        say!(
+                            "Logged {} for problem {} after {} focused minute(s).",
+                            rating,
+                            id,
+                            minutes
+                        );
+                    }
+                    None => say!(
+                        "Recorded {} focused minute(s) on problem {}; no attempt logged.",
+                        minutes,
+                        id
+                    ),
+                }
+                tx.commit()
+                    .await
+                    .context("Failed to log the pomodoro session.")?;
+                if rating.is_some() {
+                    after_attempt(&config, &pool, id).await;
+                }
+            }
+            Commands::Mcp => {
+                let server = McpServer {
+                    pool: pool.clone(),
+                    deck: deck.map(str::to_string),
+                    hooks: config.hooks.clone(),
+                    webhook_url: config.webhook_url.clone(),
+                };
+                // Stdout carries the protocol, so nothing else may be printed.
+                let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+                server.run(stdin, tokio::io::stdout()).await?;
+            }
+            Commands::Serve {
+                port,
+                host,
+                open,
+                api,
+                token,
+            } => {
+                let listener = tokio::net::TcpListener::bind((host.as_str(), port))
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Failed to listen on {}:{}. Is the port already in use?",
+                            host, port
+                        )
+                    })?;
+                let url = format!("http://{}:{}/", host, port);
+                let what = if api { "the API" } else { "the dashboard" };
+                say!("Serving {} at {} (Ctrl-C to stop).", what, url);
+                if open {
+                    open_in_browser(&url)?;
+                }
+                let server = Server {
+                    pool: pool.clone(),
+                    deck: deck.map(str::to_string),
+                    api,
+                    token: token.filter(|token| !token.is_empty()),
+                    hooks: config.hooks.clone(),
+                    webhook_url: config.webhook_url.clone(),
+                };
+                tokio::select! {
+                    result = server.run(listener) => result?,
+                    result = tokio::signal::ctrl_c() => result.context("Failed to listen for Ctrl-C")?,
+                }
+            }
+            Commands::Sync {
+                session,
+                csrf_token,
+                rating,
+                dry_run,
+            } => {
+                let submissions = fetch_accepted_submissions(Session {
+                    session: &session,
+                    csrf_token: csrf_token.as_deref(),
+                })
+                .await?;
+                let slugs = fetch_problem_ids_by_slug(&pool, deck).await?;
+                let tracked: HashSet<i64> = fetch_all_progress(&pool, None, None)
+                    .await?
+                    .iter()
+                    .map(|p| p.problem_id)
+                    .collect();
+                let attempts = plan_sync(&submissions, &slugs, &tracked);
+                let untracked = submissions
+                    .iter()
+                    .filter(|s| !slugs.contains_key(&s.title_slug))
+                    .map(|s| s.title_slug.as_str())
+                    .collect::<HashSet<_>>()
+                    .len();
+                let problems = attempts
+                    .iter()
+                    .map(|a| a.problem_id)
+                    .collect::<HashSet<_>>()
+                    .len();
+
+                if dry_run {
+                    for attempt in &attempts {
+                        println!(
+                            "  {}  #{:<5} {}",
+                            attempt.attempted_on, attempt.problem_id, attempt.language
+                        );
+                    }
+                    println!(
+                        "Would log {} attempt(s) at {} problem(s).",
+                        attempts.len(),
+                        problems
+                    );
+                } else {
+                    let attempt_rating = map_rating(rating);
+                    let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+                    for attempt in &attempts {
+                        let details = AttemptDetails {
+                            language: Some(attempt.language.clone()),
+                            ..Default::default()
+                        };
+                        log_attempt(
+                            &mut *tx,
+                            attempt.problem_id,
+                            attempt_rating,
+                            Some(attempt.attempted_on),
+                            details,
+                        )
+                        .await?;
+                    }
+                    tx.commit()
+                        .await
+                        .context("Failed to commit synced attempts.")?;
+                    after_attempts(&config, &pool, attempts.iter().map(|a| a.problem_id)).await;
+                    say!(
+                        "Logged {} attempt(s) at {} problem(s) from {} accepted submission(s).",
+                        attempts.len(),
+                        problems,
+                        submissions.len()
+                    );
+                }
+                if untracked > 0 {
+                    let scope = match deck {
+                        Some(deck) => format!("deck '{}'", deck),
+                        None => "any deck".to_string(),
+                    };
+                    say!(
+                        "Skipped {} solved problem(s) that aren't in {}.",
+                        untracked,
+                        scope
+                    );
+                }
+            }
+            Commands::BulkAttempt { path } => {
+                let contents = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read '{}'", path.display()))?;
+                let ParsedBulkFile {
+                    attempts,
+                    mut errors,
+                } = parse_bulk_attempts(&contents, Local::now().date_naive());
+
+                // Apply every valid line even if others failed to parse, so
+                // unknown ids are reported in the same pass. The transaction is
+                // only committed if nothing went wrong.
+                let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+                for (line_number, attempt) in &attempts {
+                    let result = match fetch_problem(&mut *tx, attempt.problem_id).await {
+                        Ok(Some(_)) => log_attempt(
+                            &mut *tx,
+                            attempt.problem_id,
+                            attempt.rating,
+                            attempt.date,
+                            AttemptDetails::default(),
+                        )
+                        .await
+                        .map(|_| ()),
+                        Ok(None) => Err(anyhow::anyhow!(
+                            "No problem with id {} exists in the database.",
+                            attempt.problem_id
+                        )),
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = result {
+                        errors.push((*line_number, e));
+                    }
+                }
+                errors.sort_by_key(|(line_number, _)| *line_number);
+
+                if errors.is_empty() {
+                    tx.commit()
+                        .await
+                        .context("Failed to commit bulk attempts.")?;
+                    after_attempts(&config, &pool, attempts.iter().map(|(_, a)| a.problem_id))
+                        .await;
+                    say!(
+                        "Logged {} attempt(s) from {}.",
+                        attempts.len(),
+                        path.display()
+                    );
+                } else {
+                    tx.rollback()
+                        .await
+                        .context("Failed to roll back bulk attempts.")?;
+                    eprintln!("Found {} problem(s) in {}:", errors.len(), path.display());
+                    for (line_number, e) in &errors {
+                        eprintln!("  - line {}: {:#}", line_number, e);
+                    }
+                    eprintln!("Nothing was written. Fix the lines above and try again.");
+                    std::process::exit(1);
+                }
+            }
+            Commands::Review { due_only } => {
+                say!("\n--- Review Session ---");
+                let reviewed = run_review_session(&config, &pool, deck, due_only).await?;
+                say!("\nReviewed {} problem(s) this session.", reviewed);
+            }
+            Commands::All {
+                page,
+                page_size,
+                tag,
+            } => {
+                let tag = tag.as_deref();
+                let total = count_problems(&pool, deck, tag).await?;
+                let page_count = (total as u64).div_ceil(page_size as u64).max(1);
+                let offset = (page as i64 - 1) * page_size as i64;
+                let page_problems =
+                    fetch_problems_page(&pool, deck, tag, page_size as i64, offset).await?;
+                if cli.json {
+                    return print_json(&serde_json::json!({
+                        "page": page,
+                        "page_count": page_count,
+                        "total": total,
+                        "problems": page_problems,
+                    }));
+                }
+                say!("\n--- All Problems ---");
+                if total == 0 {
+                    println!(
+                        "No problems found in the database. Use the --build command to populate it."
+                    );
+                } else if page_problems.is_empty() {
+                    println!("No problems on this page.");
+                } else {
+                    let mut table = Table::new(&["Order", "ID", "Name", "Diff", "Week", "URL"]);
+                    for problem in &page_problems {
+                        let pin = if problem.is_pinned() {
+                            " [pinned]"
+                        } else if problem.is_skipped() {
+                            " [skipped]"
+                        } else {
+                            ""
+                        };
+                        table.push(vec![
+                            problem.order.to_string().into(),
+                            problem.id.to_string().into(),
+                            format!("{}{}", problem.name, pin).into(),
+                            match problem.difficulty {
+                                Some(diff) => {
+                                    Cell::colored(format!("{:?}", diff), difficulty_color(diff))
+                                }
+                                None => "-".into(),
+                            },
+                            problem
+                                .week
+                                .map_or("-".to_string(), |week| week.to_string())
+                                .into(),
+                            problem.url.as_deref().unwrap_or_default().into(),
+                        ]);
+                    }
+                    print!("{}", table.render(color));
+                }
+                if total > 0 {
+                    println!("\nPage {}/{} — {} problems", page, page_count, total);
+                }
+            }
+            Commands::Open { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let problem = fetch_problem(&pool, id).await?.with_context(|| {
+                    format!("No problem with id {} exists in the database.", id)
+                })?;
+                let url = problem.link();
+                say!("Opening {}", url);
+                open_in_browser(&url)?;
+            }
+            Commands::Show { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let problem = fetch_problem(&pool, id).await?.with_context(|| {
+                    format!("No problem with id {} exists in the database.", id)
+                })?;
+
+                say!("\n--- Problem #{} ---", problem.id);
+                println!("Name:     {}", problem.name);
+                println!("Order:    #{}", problem.order);
+                match problem.week {
+                    Some(week_num) => println!("Week:     {}", week_num),
+                    None => println!("Week:     Unassigned"),
+                }
+                if let Some(diff) = problem.difficulty {
+                    println!("Diff:     {:?}", diff);
+                }
+                if let Some(url) = &problem.url {
+                    println!("URL:      {}", url);
+                }
+                if problem.is_pinned() {
+                    println!("Pinned:   yes");
+                } else if problem.is_skipped() {
+                    println!("Skipped:  yes");
+                }
+                if is_suspended(&pool, id).await? {
+                    println!("Suspended: yes");
+                }
+                let decks = fetch_problem_decks(&pool, id).await?;
+                if !decks.is_empty() {
+                    println!("Decks:    {}", decks.join(", "));
+                }
+                let tags = fetch_problem_tags(&pool, id).await?;
+                if !tags.is_empty() {
+                    println!("Tags:     {}", tags.join(", "));
+                }
+                if let Some(meta) = fetch_problem_meta(&pool, id).await?
+                    && let Some(rate) = meta.acceptance_rate
+                {
+                    println!("Accept:   {:.1}% (as of {})", rate, meta.fetched_on);
+                }
+
+                say!("\n--- Progress ---");
+                match fetch_progress(&pool, id).await? {
+                    Some(progress) => {
+                        println!("Last attempt:  {}", progress.last_attempted);
+                        println!("Rating:        {}", progress.attempt_rating);
+                        println!("Attempts:      {}", progress.number_of_attempts);
+                        match progress.next_attempt_date {
+                            Some(next) => {
+                                println!("Next review:   {}", next);
+                                println!(
+                                    "Interval:      {} day(s)",
+                                    (next - progress.last_attempted).num_days()
+                                );
+                            }
+                            None if progress.mastered => {
+                                println!("Next review:   never (mastered)")
+                            }
+                            None => println!("Next review:   not scheduled"),
+                        }
+                        if let Some(language) = &progress.language {
+                            println!("Language:      {}", language);
+                        }
+                        println!("Status:        {}", progress.status());
+                    }
+                    None => println!("Not yet attempted."),
+                }
+
+                let history = fetch_attempt_history(&pool, id).await?;
+                if !history.is_empty() {
+                    say!("\n--- History ---");
+                    print_attempt_history(&history);
+                }
+                if let Some(note) = fetch_problem_note(&pool, id).await? {
+                    say!("\n--- Notes ---");
+                    println!("{}", note);
+                }
+            }
+            Commands::Doctor { fix_orphans } => {
+                let mut healthy = true;
+
+                say!("\n--- Integrity Check ---");
+                let messages = check_integrity(&pool).await?;
+                if messages == ["ok"] {
+                    println!("ok");
+                } else {
+                    healthy = false;
+                    for message in &messages {
+                        println!("  - {}", message);
+                    }
+                }
+
+                say!("\n--- Migrations ---");
+                let applied = fetch_applied_migrations(&pool).await?;
+                for bundled in MIGRATOR.iter() {
+                    let status = match applied.iter().find(|m| m.version == bundled.version) {
+                        None => "not applied",
+                        Some(m) if !m.success => "FAILED",
+                        Some(m) if m.checksum != *bundled.checksum => "checksum mismatch",
+                        Some(_) => "applied",
+                    };
+                    println!(
+                        "  - {} {:<35} {}",
+                        bundled.version, bundled.description, status
+                    );
+                }
+                for m in &applied {
+                    if !MIGRATOR.iter().any(|bundled| bundled.version == m.version) {
+                        println!(
+                            "  - {} {:<35} applied, but not bundled with this binary",
+                            m.version, m.description
+                        );
+                    }
+                }
+
+                say!("\n--- Orphaned Progress ---");
+                let orphans = fetch_orphan_progress(&pool).await?;
+                if orphans.is_empty() {
+                    println!("None found.");
+                } else if fix_orphans {
+                    let deleted = delete_orphan_progress(&pool).await?;
+                    println!(
+                        "Deleted {} orphaned progress row(s): {:?}",
+                        deleted, orphans
+                    );
+                } else {
+                    healthy = false;
+                    println!(
+                        "Progress exists for {} problem(s) missing from the problems table: {:?}",
+                        orphans.len(),
+                        orphans
+                    );
+                    println!("Run `track doctor --fix-orphans` to delete them.");
+                }
+
+                if !healthy {
+                    std::process::exit(1);
+                }
+            }
+            Commands::Heatmap { weeks, ascii } => {
+                say!("\n--- Activity (last {} weeks) ---", weeks);
+                let dates = fetch_attempt_dates(&pool).await?;
+                let shades = if ascii { &ASCII_SHADES } else { &SHADES };
+                print!(
+                    "{}",
+                    render_heatmap(&dates, Local::now().date_naive(), weeks, shades)
+                );
+            }
+            Commands::Pin { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                set_problem_priority(&pool, id, Problem::PINNED_PRIORITY).await?;
+                say!("Pinned problem {}. It will be suggested next.", id);
+            }
+            Commands::Unpin { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                set_problem_priority(&pool, id, 0).await?;
+                say!("Unpinned problem {}.", id);
+            }
+            Commands::Pins => {
+                let pinned = fetch_pinned_problems(&pool, deck).await?;
+                if cli.json {
+                    return print_json(&pinned);
+                }
+                if pinned.is_empty() {
+                    println!("Nothing is pinned. Pin a problem with `track pin <id>`.");
+                }
+                for PinnedProblem { problem, attempted } in &pinned {
+                    println!(
+                        "  #{:<5} {}{}",
+                        problem.id,
+                        problem.name,
+                        if *attempted { " (attempted)" } else { "" }
+                    );
+                }
+            }
+            Commands::Skip { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                set_problem_priority(&pool, id, Problem::SKIPPED_PRIORITY).await?;
+                say!(
+                    "Skipped problem {}. It moves to the back of the queue until you unpin it.",
+                    id
+                );
+            }
+            Commands::SetPriority { id, level } => {
+                let id = resolve_problem(&pool, &id).await?;
+                set_priority_level(&pool, id, level).await?;
+                say!("Problem {} now has {} priority.", id, level);
+            }
+            Commands::Postpone { id, days } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let progress = fetch_progress(&pool, id).await?.with_context(|| {
+                    format!(
+                        "Problem {} has no progress yet, so there's no review to postpone.",
+                        id
+                    )
+                })?;
+                let due = progress.next_attempt_date.with_context(|| {
+                    format!("Problem {} has no review scheduled to postpone.", id)
+                })?;
+                let until = due.max(Local::now().date_naive()) + Duration::days(days as i64);
+                set_next_attempt_date(&pool, id, Some(until)).await?;
+                say!("Problem {} will come up for review on {}.", id, until);
+            }
+            Commands::Suspend { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                set_suspended(&pool, id, true, Local::now().date_naive()).await?;
+                say!("Suspended problem {}. `track next` will skip it.", id);
+            }
+            Commands::Unsuspend { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                set_suspended(&pool, id, false, Local::now().date_naive()).await?;
+                say!("Unsuspended problem {}.", id);
+            }
+            Commands::Streak => {
+                let today = Local::now().date_naive();
+                let dates = fetch_attempt_dates(&pool).await?;
+                let streaks = compute_streaks(&dates, today);
+                if cli.json {
+                    return print_json(&streaks);
+                }
+                say!("\n--- Streaks ---");
+                println!("Current Streak: {} day(s)", streaks.current);
+                println!("Longest Streak: {} day(s)", streaks.longest);
+                if dates.contains(&today) {
+                    say!("\nYou've practiced today. See you tomorrow!");
+                } else if streaks.current == 0 {
+                    say!("\nLog an attempt today to start a new streak.");
+                }
+                print_streak_warning(&dates, today);
+            }
+            Commands::Tui => {
+                let term = Term::buffered_stdout();
+                anyhow::ensure!(
+                    term.is_term(),
+                    "track tui needs a terminal. `track dashboard` prints the same overview."
+                );
+                // Draw on the alternate screen, so quitting gives the terminal
+                // back as it was.
+                term.write_str("\x1b[?1049h")?;
+                term.hide_cursor()?;
+                let result = run_tui(&term, &config, &pool, deck).await;
+                term.show_cursor()?;
+                term.write_str("\x1b[?1049l")?;
+                term.flush()?;
+                result?;
+            }
+            Commands::Dashboard { limit } => {
+                let today = Local::now().date_naive();
+                let due = fetch_due_problems(&pool, today, deck).await?;
+                say!("\n--- Due Reviews ({}) ---", due.len());
+                if due.is_empty() {
+                    println!("Nothing is due.");
+                }
+                for item in due.iter().take(limit) {
+                    let days = item.days_overdue(today);
+                    let when = match days {
+                        0 => "due today".to_string(),
+                        1 => "1 day overdue".to_string(),
+                        _ => format!("{} days overdue", days),
+                    };
+                    println!("  - #{:<5} {:<40} {}", item.problem_id, item.name, when);
+                }
+                if due.len() > limit {
+                    println!("  ...and {} more.", due.len() - limit);
+                }
+
+                say!("\n--- Next Up by Week ---");
+                let next_by_week = fetch_next_unattempted_by_week(&pool, deck).await?;
+                if next_by_week.is_empty() {
+                    println!("Every problem has been attempted.");
+                }
+                for problem in &next_by_week {
+                    let label = match problem.week {
+                        Some(week_num) => format!("Week {}", week_num),
+                        None => "Unassigned".to_string(),
+                    };
+                    println!(
+                        "  {:<12} #{:<5} {}",
+                        format!("{}:", label),
+                        problem.id,
+                        problem.name
+                    );
+                }
+
+                say!("\n--- Latest Ratings ---");
+                let progress_list = fetch_all_progress(&pool, None, deck).await?;
+                let dates = fetch_attempt_dates(&pool).await?;
+                let stats = compute_stats(&progress_list, &dates, today);
+                for rating in AttemptRating::ALL {
+                    let count = stats.by_rating.get(&rating).copied().unwrap_or(0);
+                    println!(
+                        "  {:<10} {:>4} {}",
+                        rating,
+                        count,
+                        progress_bar(count as u64, stats.total_attempted as u64, 30)
+                    );
+                }
+            }
+            Commands::Weeks => {
+                say!("\n--- Completion by Week ---");
+                let weeks = fetch_week_completion(&pool, deck).await?;
+                if weeks.is_empty() {
+                    println!(
+                        "No problems found in the database. Use the --build command to populate it."
+                    );
+                }
+                for row in &weeks {
+                    let label = match row.week {
+                        Some(week_num) => format!("Week {}", week_num),
+                        None => "Unassigned".to_string(),
+                    };
+                    println!(
+                        "{:<12} {:>3}/{:<3} ({:>3.0}%) {}",
+                        format!("{}:", label),
+                        row.attempted,
+                        row.total,
+                        row.percent(),
+                        progress_bar(row.attempted as u64, row.total as u64, 20)
+                    );
+                }
+            }
+            Commands::Decks => {
+                say!("\n--- Decks ---");
+                let decks = fetch_deck_summaries(&pool).await?;
+                if decks.is_empty() {
+                    println!(
+                        "No problems found in the database. Use the --build command to populate it."
+                    );
+                }
+                for row in &decks {
+                    println!(
+                        "{:<20} {:>3}/{:<3} attempted {}",
+                        row.deck,
+                        row.attempted,
+                        row.total,
+                        progress_bar(row.attempted as u64, row.total as u64, 20)
+                    );
+                }
+            }
+            Commands::Master { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                master_problem(&pool, id).await?;
+                say!(
+                    "Marked problem {} mastered. It won't come up for review again.",
+                    id
+                );
+            }
+            Commands::Snooze { id, until, clear } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let until = if clear { None } else { until };
+                set_next_attempt_date(&pool, id, until).await?;
+                match until {
+                    Some(date) => say!("Problem {} will come up for review on {}.", id, date),
+                    None => say!("Cleared the review date for problem {}.", id),
+                }
+            }
+            Commands::Reschedule => {
+                let changed = reschedule_all_progress(&pool).await?;
+                say!("Rescheduled reviews: {} date(s) changed.", changed);
+            }
+            Commands::Merge { other_db } => {
+                let summary = merge_database(&pool, Path::new(&other_db)).await?;
+                print_merge_summary(&other_db, &summary);
+            }
+            Commands::Import { path } => {
+                let json = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read snapshot '{}'", path.display()))?;
+                let snapshot = Snapshot::from_json(&json)?;
+                let summary = import_snapshot(&pool, &snapshot).await?;
+                print_merge_summary(&path.display().to_string(), &summary);
+            }
+            Commands::CompleteIds => {
+                for problem in fetch_all_problems(&pool, deck).await? {
+                    println!("{}\t{}", problem.id, problem.name);
+                }
+            }
+            Commands::Daily { rating } => {
+                let today = Local::now().date_naive();
+                let daily = fetch_daily_challenge().await?;
+                let id = daily.meta.question_id;
+                if add_daily_problem(&pool, &daily, deck.unwrap_or(DAILY_DECK), today).await? {
+                    say!("Added #{} to the database.", id);
+                }
+
+                say!("\n--- Daily Challenge for {} ---", daily.date);
+                say!(
+                    "#{}: {} ({:?})",
+                    id,
+                    daily.meta.title,
+                    daily.meta.difficulty
+                );
+                say!("{}", daily.url());
+                match fetch_progress(&pool, id).await? {
+                    Some(progress) if progress.last_attempted == today => {
+                        say!("Already logged today: {}.", progress.attempt_rating);
+                        return Ok(());
+                    }
+                    Some(progress) => say!(
+                        "Last:  {} on {} ({} attempt(s))",
+                        progress.attempt_rating,
+                        progress.last_attempted,
+                        progress.number_of_attempts
+                    ),
+                    None => say!("Last:  not yet attempted"),
+                }
+
+                let rating = match rating {
+                    Some(rating) => Some(map_rating(rating)),
+                    None if io::stdin().is_terminal() => prompt_for_rating()?,
+                    None => None,
+                };
+                if let Some(rating) = rating {
+                    log_attempt(&pool, id, rating, Some(today), AttemptDetails::default()).await?;
+                    say!("Logged {} for #{}.", rating, id);
+                    after_attempt(&config, &pool, id).await;
+                }
+            }
+            Commands::RefreshMeta {
+                missing,
+                concurrency,
+            } => {
+                let mut problems = fetch_all_problems(&pool, deck).await?;
+                if missing {
+                    let fetched = fetch_problem_meta_ids(&pool).await?;
+                    problems.retain(|p| !fetched.contains(&p.id));
+                }
+                say!(
+                    "Fetching metadata for {} problem(s) from LeetCode...",
+                    problems.len()
+                );
+                let refresh =
+                    refresh_meta(&pool, &problems, concurrency, Local::now().date_naive()).await?;
+                if !cli.quiet || !refresh.failed.is_empty() {
+                    refresh.print_report();
+                }
+            }
+            Commands::Prune { bank, dry_run, yes } => {
+                let bank_problems = load_problems(&cli.static_dir, &bank)?;
+                let deck_name = deck
+                    .map(str::to_string)
+                    .unwrap_or_else(|| default_deck_name(&bank));
+                let stale = fetch_stale_problems(&pool, &deck_name, &bank_problems, &[]).await?;
+
+                if stale.is_empty() {
+                    say!(
+                        "Every problem in deck '{}' is still in {}.",
+                        deck_name,
+                        bank
+                    );
+                    return Ok(());
+                }
+                println!(
+                    "{} problem(s) in deck '{}' are no longer in {}:",
+                    stale.len(),
+                    deck_name,
+                    bank
+                );
+                for problem in &stale {
+                    println!("  - #{:<5} {}", problem.id, problem.name);
+                }
+                if dry_run {
+                    say!("Dry run: nothing was removed.");
+                    return Ok(());
+                }
+                if !yes {
+                    print!("Remove them (their progress and history are kept)? [y/N] ");
+                    io::stdout().flush()?;
+                    let mut answer = String::new();
+                    io::stdin().lock().read_line(&mut answer)?;
+                    if !answer.trim().eq_ignore_ascii_case("y") {
+                        println!("Nothing was removed.");
+                        return Ok(());
+                    }
+                }
+                let ids: Vec<i64> = stale.iter().map(|p| p.id).collect();
+                let archived =
+                    remove_from_deck(&pool, &deck_name, &ids, Local::now().date_naive()).await?;
+                say!(
+                    "Removed {} problem(s) from deck '{}'; {} in no other deck were archived.",
+                    ids.len(),
+                    deck_name,
+                    archived
+                );
+            }
+            Commands::Duplicates { bank, by } => {
+                let candidates: Vec<Candidate> = match &bank {
+                    Some(name) => load_problems_allowing_duplicates(&cli.static_dir, name)?
+                        .into_iter()
+                        .map(|p| Candidate {
+                            id: p.id,
+                            order: p.order,
+                            name: p.name,
+                        })
+                        .collect(),
+                    None => fetch_all_problems(&pool, deck)
+                        .await?
+                        .into_iter()
+                        .map(|p| Candidate {
+                            id: p.id,
+                            order: p.order,
+                            name: p.name,
+                        })
+                        .collect(),
+                };
+                let clusters = match by {
+                    DuplicateKey::Id => find_id_duplicates(&candidates),
+                    DuplicateKey::Name => find_name_duplicates(&candidates),
+                };
+
+                say!("\n--- Suspected Duplicates ---");
+                if clusters.is_empty() {
+                    println!("No duplicates found among {} problems.", candidates.len());
+                }
+                for (number, cluster) in clusters.iter().enumerate() {
+                    println!("Cluster {}:", number + 1);
+                    for candidate in cluster {
+                        println!(
+                            "  - #{:<5} order {:<4} {}",
+                            candidate.id, candidate.order, candidate.name
+                        );
+                    }
+                }
+            }
+            Commands::Overdue => {
+                say!("\n--- Overdue Reviews ---");
+                let today = Local::now().date_naive();
+                let overdue = fetch_overdue_problems(&pool, today, deck).await?;
+                if overdue.is_empty() {
+                    println!("Nothing is overdue. Nice work!");
+                } else {
+                    for item in &overdue {
+                        let days = item.days_overdue(today);
+                        println!(
+                            "  - #{:<5} {:<40} {} day{} overdue",
+                            item.problem_id,
+                            item.name,
+                            days,
+                            if days == 1 { "" } else { "s" }
+                        );
+                    }
+                }
+                println!("\nTotal overdue: {}", overdue.len());
+            }
+            Commands::Search { query, limit } => {
+                let progress: HashMap<i64, ProgressView> = fetch_all_progress(&pool, None, deck)
+                    .await?
+                    .into_iter()
+                    .map(|progress| (progress.problem_id, progress))
+                    .collect();
+                let mut notes: HashMap<i64, Vec<String>> = HashMap::new();
+                for (problem_id, note) in fetch_all_notes(&pool).await? {
+                    notes.entry(problem_id).or_default().push(note);
+                }
+
+                let mut matches: Vec<(MatchKind, Problem)> = fetch_all_problems(&pool, deck)
+                    .await?
+                    .into_iter()
+                    .filter_map(|problem| {
+                        let problem_notes: Vec<&str> = notes
+                            .get(&problem.id)
+                            .map(|n| n.iter().map(String::as_str).collect())
+                            .unwrap_or_default();
+                        match_problem(&query, &problem.name, &problem_notes)
+                            .map(|kind| (kind, problem))
+                    })
+                    .collect();
+                // Shorter names first within a kind: they match more of the query.
+                matches.sort_by_key(|(kind, problem)| (*kind, problem.name.len()));
+
+                say!("\n--- Search: {} ---", query);
+                if matches.is_empty() {
+                    println!("No problems match '{}'.", query);
+                }
+                for (kind, problem) in matches.iter().take(limit) {
+                    let difficulty = problem
+                        .difficulty
+                        .map_or("-".to_string(), |d| format!("{:?}", d));
+                    let status = progress
+                        .get(&problem.id)
+                        .map_or("Not attempted".to_string(), |p| p.status().to_string());
+                    let via_note = if *kind == MatchKind::Note {
+                        "  (note)"
+                    } else {
+                        ""
+                    };
+                    println!(
+                        "  - #{:<5} {:<40} {:<7} {}{}",
+                        problem.id, problem.name, difficulty, status, via_note
+                    );
+                }
+                if matches.len() > limit {
+                    println!(
+                        "  ...and {} more. Use --limit to see them.",
+                        matches.len() - limit
+                    );
+                }
+            }
+            Commands::Note { id, text } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let text = match text {
+                    Some(text) => text,
+                    None => {
+                        let current = fetch_problem_note(&pool, id).await?.unwrap_or_default();
+                        edit_in_editor(&current)?
+                    }
+                };
+                set_problem_note(&pool, id, Some(&text), Local::now().date_naive()).await?;
+                if text.trim().is_empty() {
+                    say!("Removed the note for problem {}.", id);
+                } else {
+                    say!("Saved the note for problem {}.", id);
+                }
+            }
+            Commands::Notes { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let problem = fetch_problem(&pool, id).await?.with_context(|| {
+                    format!("No problem with id {} exists in the database.", id)
+                })?;
+                say!("\n--- Notes for #{}: {} ---", problem.id, problem.name);
+                match fetch_problem_note(&pool, id).await? {
+                    Some(note) => println!("{}", note),
+                    None => println!("No problem note. Add one with `track note {}`.", id),
+                }
+                let attempt_notes: Vec<HistoryEntry> = fetch_attempt_history(&pool, id)
+                    .await?
+                    .into_iter()
+                    .filter(|entry| entry.note.is_some())
+                    .collect();
+                if !attempt_notes.is_empty() {
+                    println!("\nFrom attempts:");
+                    for entry in &attempt_notes {
+                        println!(
+                            "  {} ({}): {}",
+                            entry.attempted_on,
+                            entry.rating,
+                            entry.note.as_deref().unwrap_or_default()
+                        );
+                    }
+                }
+            }
+            Commands::DeleteAttempt { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                match delete_latest_attempt(&pool, Some(id)).await? {
+                    Some((_, removed)) => print_removed_attempt(&pool, id, &removed).await?,
+                    None => println!("Problem {} has no attempts to delete.", id),
+                }
+            }
+            Commands::Undo => match delete_latest_attempt(&pool, None).await? {
+                Some((id, removed)) => print_removed_attempt(&pool, id, &removed).await?,
+                None => say!("There are no attempts to undo."),
+            },
+            Commands::History { id } => {
+                let id = resolve_problem(&pool, &id).await?;
+                let problem = fetch_problem(&pool, id).await?.with_context(|| {
+                    format!("No problem with id {} exists in the database.", id)
+                })?;
+                let history = fetch_attempt_history(&pool, id).await?;
+                say!("\n--- History for #{}: {} ---", problem.id, problem.name);
+                if history.is_empty() {
+                    println!("No attempts logged yet.");
+                }
+                print_attempt_history(&history);
+            }
+            Commands::Due { count, check } => {
+                let today = Local::now().date_naive();
+                let due = fetch_due_problems(&pool, today, deck).await?;
+                run_due_hook(&config.hooks, &due).await;
+                if count || check {
+                    if count {
+                        // A bare number is already valid JSON.
+                        println!("{}", due.len());
+                    }
+                    if check && !due.is_empty() {
+                        std::process::exit(EXIT_REVIEWS_DUE);
+                    }
+                    return Ok(());
+                }
+                if cli.json {
+                    return print_json(&due);
+                }
+                say!("\n--- Due Reviews ---");
+                if due.is_empty() {
+                    println!("Nothing is due today.");
+                } else {
+                    for item in &due {
+                        let days = item.days_overdue(today);
+                        let when = if days == 0 {
+                            "due today".to_string()
+                        } else {
+                            format!("{} day{} overdue", days, if days == 1 { "" } else { "s" })
+                        };
+                        println!("  - #{:<5} {:<40} {}", item.problem_id, item.name, when);
+                    }
+                }
+                println!("\nTotal due: {}", due.len());
+                print_streak_warning(&fetch_attempt_dates(&pool).await?, today);
+            }
+            Commands::Notify => {
+                let due = fetch_due_problems(&pool, Local::now().date_naive(), deck).await?;
+                if due.is_empty() {
+                    say!("Nothing is due today.");
+                    return Ok(());
+                }
+                run_due_hook(&config.hooks, &due).await;
+                let names: Vec<&str> = due.iter().map(|item| item.name.as_str()).collect();
+                let (summary, body) = due_notification(&names);
+                notify_desktop(&summary, &body)?;
+                say!("{}", summary);
+            }
+            Commands::Export {
+                format: ExportFormat::Csv,
+                history,
+                all,
+                anki,
+                ics,
+                days,
+                out,
+            } => {
+                let (contents, written) = if all {
+                    let snapshot = fetch_snapshot(&pool, Local::now().date_naive()).await?;
+                    let mut json = serde_json::to_string_pretty(&snapshot)?;
+                    json.push('\n');
+                    let written = format!("a snapshot of {} problem(s)", snapshot.problems.len());
+                    (json, written)
+                } else if anki {
+                    let progress_list = fetch_all_progress(&pool, None, deck).await?;
+                    let attempts = fetch_all_attempts(&pool, deck).await?;
+                    let notes = fetch_problem_notes(&pool).await?;
+                    let contents = render_due_anki_deck(
+                        &progress_list,
+                        &attempts,
+                        &notes,
+                        Local::now().date_naive(),
+                    );
+                    // Header lines start with '#'; cards never do.
+                    let cards = contents.lines().filter(|l| !l.starts_with('#')).count();
+                    (contents, format!("{} card(s)", cards))
+                } else if ics {
+                    let reviews = match days {
+                        Some(days) => {
+                            fetch_reviews_due_within(&pool, Local::now().date_naive(), days, deck)
+                                .await?
+                        }
+                        None => fetch_scheduled_reviews(&pool, deck).await?,
+                    };
+                    let written = format!("{} scheduled review(s)", reviews.len());
+                    (render_calendar(&reviews, Utc::now().naive_utc()), written)
+                } else if history {
+                    let attempts = fetch_all_attempts(&pool, deck).await?;
+                    let written = format!("{} row(s)", attempts.len());
+                    (render_history_csv(&attempts), written)
+                } else {
+                    let problems = fetch_all_problems(&pool, deck).await?;
+                    let progress_list = fetch_all_progress(&pool, None, deck).await?;
+                    let written = format!("{} row(s)", problems.len());
+                    (render_problems_csv(&problems, &progress_list), written)
+                };
+                match out {
+                    Some(path) => {
+                        fs::write(&path, &contents).with_context(|| {
+                            format!("Failed to write export to '{}'", path.display())
+                        })?;
+                        say!("Wrote {} to {}.", written, path.display());
+                    }
+                    None => print!("{}", contents),
+                }
+            }
+            Commands::Anki { path } => {
+                let progress_list = fetch_all_progress(&pool, None, deck).await?;
+                let deck = render_anki_deck(&progress_list);
+                fs::write(&path, &deck).with_context(|| {
+                    format!("Failed to write Anki deck to '{}'", path.display())
+                })?;
+                say!(
+                    "Wrote {} card(s) to {}",
+                    deck.lines().count(),
+                    path.display()
+                );
+            }
+            Commands::Calendar { path } => {
+                let reviews = fetch_scheduled_reviews(&pool, deck).await?;
+                let calendar = render_calendar(&reviews, Utc::now().naive_utc());
+                fs::write(&path, calendar)
+                    .with_context(|| format!("Failed to write calendar to '{}'", path.display()))?;
+                say!(
+                    "Wrote {} scheduled review(s) to {}",
+                    reviews.len(),
+                    path.display()
+                );
+            }
+            Commands::Goal { target } => {
+                let Some(goal) = target.or(config.weekly_goal) else {
+                    println!(
+                        "No weekly goal set. Pass --target <N> or add `weekly_goal = N` to {}.",
+                        CONFIG_FILE
+                    );
+                    return Ok(());
+                };
+
+                let today = Local::now().date_naive();
+                let week = today.iso_week();
+                let done = count_problems_attempted_in_week(&pool, today).await?;
+
+                println!(
+                    "\n--- Weekly Goal (ISO week {}-W{:02}) ---",
+                    week.year(),
+                    week.week()
+                );
+                println!(
+                    "{} {} / {} this week",
+                    progress_bar(done as u64, goal as u64, 20),
+                    done,
+                    goal
+                );
+                if done as u64 >= goal as u64 {
+                    println!("Goal reached! 🎉");
+                } else {
+                    println!("{} to go.", goal as i64 - done);
+                }
+            }
+            Commands::Today { weekly_new } => {
+                let today = Local::now().date_naive();
+                let weekly_new = weekly_new
+                    .or(config.weekly_new)
+                    .unwrap_or(DEFAULT_WEEKLY_NEW);
+                let monday = week_start(today);
+                let started_before =
+                    count_problems_started_between(&pool, monday, today - Duration::days(1))
+                        .await?;
+                let started_today = count_problems_started_between(&pool, today, today).await?;
+                let quota = new_quota_for_today(weekly_new, started_before as u32, today);
+                let left_today = quota.saturating_sub(started_today as u32);
+
+                let reviews = fetch_due_problems(&pool, today, deck).await?;
+                let new = fetch_unattempted_problems(
+                    &pool,
+                    deck,
+                    NextFilter::default(),
+                    NextStrategy::Order,
+                    left_today,
+                )
+                .await?;
+                if cli.json {
+                    return print_json(&serde_json::json!({
+                        "date": today,
+                        "reviews": reviews,
+                        "new": new,
+                        "weekly_new": weekly_new,
+                        "started_this_week": started_before + started_today,
+                    }));
+                }
+
+                say!("\n--- Today's Plan ({}) ---", today);
+                println!("{} review(s) + {} new problem(s)", reviews.len(), new.len());
+                if !reviews.is_empty() {
+                    println!("\nReviews:");
+                    for item in &reviews {
+                        println!("  - #{:<5} {}", item.problem_id, item.name);
+                    }
+                }
+                if !new.is_empty() {
+                    println!("\nNew:");
+                    for problem in &new {
+                        println!("  - #{:<5} {}", problem.id, problem.name);
+                    }
+                }
+                if (new.len() as u32) < left_today {
+                    println!("\nEvery problem has been attempted, so there's nothing new left.");
+                }
+                println!(
+                    "\nThis week: {} / {} new problem(s) started.",
+                    started_before + started_today,
+                    weekly_new
+                );
+            }
+            Commands::Stats => {
+                let report = fetch_stats_report(&pool, deck, Local::now().date_naive()).await?;
+                if cli.json {
+                    print_json(&report)?;
+                } else {
+                    say!("\n--- Statistics ---");
+                    print_stats(&report.summary);
+                    print_stats_breakdowns(&report);
+                }
+            }
+            Commands::Bank { command } => {
+                let open_bank = |file: &Option<PathBuf>| {
+                    file.as_deref()
+                        .map(|file| BankFile::open(&resolve_bank_path(file, &cli.static_dir)))
+                        .transpose()
+                };
+                let file_deck = |file: &Option<PathBuf>| {
+                    file.as_deref()
+                        .map(|file| default_deck_name(&file.to_string_lossy()))
+                };
+                match command {
+                    BankCommand::Validate { .. }
+                    | BankCommand::ImportUrl { .. }
+                    | BankCommand::Reorder { .. }
+                    | BankCommand::Diff { .. }
+                    | BankCommand::Merge { .. }
+                    | BankCommand::Autoweek { .. } => {
+                        unreachable!("handled before the database is opened")
+                    }
+                    BankCommand::Add {
+                        name,
+                        url,
+                        week,
+                        difficulty,
+                        id,
+                        tags,
+                        file,
+                    } => {
+                        let mut bank = open_bank(&file)?;
+                        let deck_name = deck.map(str::to_string).or(file_deck(&file)).context(
+                            "Pass --deck or --file to say which deck to add the problem to.",
+                        )?;
+                        let order = match &bank {
+                            Some(bank) => bank.next_order(),
+                            None => next_deck_order(&pool, &deck_name).await?,
+                        };
+                        let entry = ProblemBankProblem {
+                            id: id.unwrap_or_default(),
+                            order,
+                            name,
+                            difficulty,
+                            week,
+                            url,
+                            tags,
+                        };
+                        let cached_id = fetch_resolved_ids(&pool).await?.get(entry.slug()).copied();
+                        let problem_id = match id.or(cached_id) {
+                            Some(id) => id,
+                            None => {
+                                say!("Looking up the id of '{}'...", entry.name);
+                                entry.get_id(DEFAULT_RESOLVE_ATTEMPTS).await?
+                            }
+                        };
+                        if let Some(bank) = &bank
+                            && bank.find(problem_id, Some(&entry.url)).is_some()
+                        {
+                            anyhow::bail!(
+                                "'{}' is already in {}.",
+                                entry.name,
+                                bank.path().display()
+                            );
+                        }
+
+                        let mut problem = entry.to_problem_with_id(&deck_name, problem_id);
+                        let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+                        problem.upsert(&mut *tx).await?;
+                        cache_resolved_id(&mut *tx, entry.slug(), problem_id).await?;
+                        if !entry.tags.is_empty() {
+                            set_problem_tags(&mut tx, problem_id, &entry.tags).await?;
+                        }
+                        tx.commit()
+                            .await
+                            .with_context(|| format!("Failed to add problem: {}", entry.name))?;
+                        say!(
+                            "Added #{} {} to deck '{}'.",
+                            problem_id,
+                            entry.name,
+                            deck_name
+                        );
+
+                        if let Some(bank) = &mut bank {
+                            bank.push(&problem, &entry.tags);
+                            bank.save()?;
+                            say!("Added it to {}.", bank.path().display());
+                        }
+                    }
+                    BankCommand::SetWeek { id, week, file } => {
+                        let problem_id = resolve_problem(&pool, &id).await?;
+                        let problem =
+                            fetch_problem(&pool, problem_id).await?.with_context(|| {
+                                format!("No problem with id {} exists in the database.", problem_id)
+                            })?;
+                        let mut bank = open_bank(&file)?;
+                        let index = bank
+                            .as_ref()
+                            .map(|bank| {
+                                bank.find(problem_id, problem.url.as_deref())
+                                    .with_context(|| {
+                                        format!(
+                                            "{} isn't in {}.",
+                                            problem.name,
+                                            bank.path().display()
+                                        )
+                                    })
+                            })
+                            .transpose()?;
+
+                        set_problem_week(&pool, problem_id, week).await?;
+                        say!("Moved #{} {} to week {}.", problem_id, problem.name, week);
+                        if let (Some(bank), Some(index)) = (&mut bank, index) {
+                            bank.set_week(index, week);
+                            bank.save()?;
+                            say!("Moved it in {} too.", bank.path().display());
+                        }
+                    }
+                    BankCommand::Remove { id, file } => {
+                        let problem_id = resolve_problem(&pool, &id).await?;
+                        let problem =
+                            fetch_problem(&pool, problem_id).await?.with_context(|| {
+                                format!("No problem with id {} exists in the database.", problem_id)
+                            })?;
+                        let decks = fetch_problem_decks(&pool, problem_id).await?;
+                        let deck_name = match deck.map(str::to_string).or(file_deck(&file)) {
+                            Some(deck_name) => deck_name,
+                            None => match decks.as_slice() {
+                                [only] => only.clone(),
+                                _ => anyhow::bail!(
+                                    "#{} {} is in {} decks ({}). Pass --deck to say which to remove it from.",
+                                    problem_id,
+                                    problem.name,
+                                    decks.len(),
+                                    decks.join(", ")
+                                ),
+                            },
+                        };
+                        if !decks.contains(&deck_name) {
+                            anyhow::bail!(
+                                "#{} {} isn't in deck '{}'.",
+                                problem_id,
+                                problem.name,
+                                deck_name
+                            );
+                        }
+                        let mut bank = open_bank(&file)?;
+                        let index = bank
+                            .as_ref()
+                            .map(|bank| {
+                                bank.find(problem_id, problem.url.as_deref())
+                                    .with_context(|| {
+                                        format!(
+                                            "{} isn't in {}.",
+                                            problem.name,
+                                            bank.path().display()
+                                        )
+                                    })
+                            })
+                            .transpose()?;
+
+                        let archived = remove_from_deck(
+                            &pool,
+                            &deck_name,
+                            &[problem_id],
+                            Local::now().date_naive(),
+                        )
+                        .await?;
+                        say!(
+                            "Removed #{} {} from deck '{}'.",
+                            problem_id,
+                            problem.name,
+                            deck_name
+                        );
+                        if archived > 0 {
+                            say!(
+                                "It was in no other deck, so it was archived with its progress and history."
+                            );
+                        }
+                        if let (Some(bank), Some(index)) = (&mut bank, index) {
+                            bank.remove(index);
+                            bank.save()?;
+                            say!("Removed it from {} too.", bank.path().display());
+                        }
+                    }
+                }
+            }
+            Commands::Completions { .. } => unreachable!("handled before the database is opened"),
+        }
+    } else {
+        // If no command or flag was given, print help.
+        Cli::command().color(color_choice).print_help()?;
+    }
+
+    Ok(())
+}
+
+use anyhow::Context;
+use chrono::{Datelike, Duration};
+use clap::ArgGroup;
+use clap::ColorChoice;
+use clap::CommandFactory;
+use clap::Parser;
+use clap::Subcommand;
+use clap_complete::Shell;
+use console::Term;
+use sqlx::sqlite::SqlitePool;
+use sqlx::types::chrono::{Local, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use track::anki::{render_anki_deck, render_due_anki_deck};
+use track::bank_file::{BankFile, diff_banks, merge_banks, resolve_bank_path};
+use track::bank_import::{
+    DEFAULT_PROBLEMS_PER_WEEK, assign_weeks, bank_file_name, fetch_page, problems_from_csv,
+    problems_from_html, problems_from_markdown,
+};
+use track::bulk::{ParsedBulkFile, parse_bulk_attempts};
+use track::config::{CONFIG_FILE, Config};
+use track::dates::parse_date_arg;
+use track::db::*;
+use track::duplicates::{Candidate, DuplicateKey, find_id_duplicates, find_name_duplicates};
+use track::export::{ExportFormat, render_history_csv, render_problems_csv};
+use track::heatmap::{ASCII_SHADES, SHADES, render_heatmap};
+use track::hooks::{HookEvent, Hooks, announce_attempt};
+use track::ical::render_calendar;
+use track::leetcode::Session;
+use track::mcp::McpServer;
+use track::meta::{DAILY_DECK, fetch_daily_challenge, refresh_meta};
+use track::plan::{DEFAULT_WEEKLY_NEW, new_quota_for_today, week_start};
+use track::pomodoro::{Phase, PhaseKind, format_countdown, plan_phases};
+use track::presets::find_preset;
+use track::problem_attempts::AttemptRating;
+use track::problem_attempts::ProgressStatus;
+use track::problem_attempts::{ProblemAttempt, preview_next_attempt_date};
+use track::problem_bank::{
+    DEFAULT_STATIC_DIR, ProblemBankProblem, check_bank, load_problems,
+    load_problems_allowing_duplicates,
+};
+use track::problem_bank_populator::{
+    DEFAULT_CONCURRENCY, DEFAULT_RESOLVE_ATTEMPTS, PopulateOptions, default_deck_name,
+    fetch_stale_problems, populate_imported_bank, populate_preset, populate_problem_bank,
+};
+use track::problems::{LeetCodeDifficulty, PriorityLevel, Problem};
+use track::schedule::{self, ReviewState};
+use track::scoring::compute_score;
+use track::search::{MatchKind, match_problem};
+use track::serve::{DEFAULT_PORT, Server};
+use track::snapshot::Snapshot;
+use track::stats::{Stats, StatsReport, compute_stats};
+use track::streaks::{compute_streaks, streak_at_risk};
+use track::sync::{fetch_accepted_submissions, plan_sync};
+use track::table::{Cell, Table, difficulty_color, rating_color};
+use track::tui::{Action, Overview, Screen};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_completions_for_every_shell() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut out = Vec::new();
+            print_completions(shell, &mut out).unwrap();
+            let script = String::from_utf8(out).unwrap();
+            assert!(
+                script.contains("track"),
+                "{} script is missing the binary name",
+                shell
+            );
+        }
+    }
+
+    #[test]
+    fn environment_seeds_global_flags_unless_given_explicitly() {
+        // SAFETY: no other test reads or writes these variables.
+        unsafe {
+            std::env::set_var("TRACK_DB", "from-env.db");
+            std::env::set_var("TRACK_DECK", "env-deck");
+            std::env::set_var("TRACK_COLOR", "never");
+        }
+
+        let from_env = Cli::try_parse_from(["track", "weeks"]).unwrap();
+        let explicit = Cli::try_parse_from(["track", "weeks", "--db", "flag.db"]).unwrap();
+
+        unsafe {
+            std::env::remove_var("TRACK_DB");
+            std::env::remove_var("TRACK_DECK");
+            std::env::remove_var("TRACK_COLOR");
+        }
+
+        assert_eq!(from_env.db, Some(PathBuf::from("from-env.db")));
+        assert_eq!(from_env.deck.as_deref(), Some("env-deck"));
+        assert_eq!(from_env.color, ColorChoice::Never);
+        assert_eq!(explicit.db, Some(PathBuf::from("flag.db")));
+    }
+
+    #[test]
+    fn json_is_accepted_anywhere_on_the_command_line() {
+        for args in [["track", "--json", "due"], ["track", "due", "--json"]] {
+            assert!(Cli::try_parse_from(args).unwrap().json);
+        }
+    }
+
+    #[test]
+    fn quiet_is_global_and_check_combines_with_count() {
+        let cli = Cli::try_parse_from(["track", "due", "--check", "--count", "-q"]).unwrap();
+        assert!(cli.quiet);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Due {
+                count: true,
+                check: true
+            })
+        ));
+    }
+
+    #[test]
+    fn notifications_name_the_first_few_due_problems() {
+        assert_eq!(
+            due_notification(&["Two Sum"]),
+            ("1 review due".to_string(), "Two Sum".to_string())
+        );
+        let (summary, body) = due_notification(&[
+            "Two Sum",
+            "LRU Cache",
+            "Course Schedule",
+            "Word Ladder",
+            "Jump Game",
+        ]);
+        assert_eq!(summary, "5 reviews due");
+        assert_eq!(body, "Two Sum, LRU Cache, Course Schedule and 2 more");
+    }
+
+    #[test]
+    fn completion_scripts_complete_problem_ids() {
+        let commands = id_subcommands(&Cli::command());
+        assert!(commands.contains(&"show".to_string()));
+        assert!(commands.contains(&"attempt".to_string()));
+        assert!(!commands.contains(&"due".to_string()));
+
+        let script = |shell| {
+            let mut out = Vec::new();
+            print_completions(shell, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert!(script(Shell::Bash).contains("complete -F _track_with_ids "));
+
+        let zsh = script(Shell::Zsh);
+        let hook = zsh
+            .find("_track_ids() {")
+            .expect("zsh script has no id hook");
+        assert!(hook < zsh.find(r#"if [ "$funcstack[1]" = "_track" ]"#).unwrap());
+        let id_arguments: Vec<_> = zsh.lines().filter(|l| l.starts_with("':id -- ")).collect();
+        assert!(!id_arguments.is_empty());
+        assert!(id_arguments.iter().all(|l| l.ends_with(":_track_ids' \\")));
+
+        assert!(script(Shell::Fish).contains(
+            "__fish_track_using_subcommand show\" -f -a \"(track __complete-ids 2>/dev/null)\""
+        ));
+    }
+}
@@ -0,0 +1,357 @@
+// src/mcp.rs
+
+/// The Model Context Protocol revision `track mcp` speaks.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC's error code for a message that isn't valid JSON-RPC.
+const PARSE_ERROR: i64 = -32700;
+
+/// JSON-RPC's error code for a method the server doesn't have.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC's error code for a call with the wrong arguments.
+const INVALID_PARAMS: i64 = -32602;
+
+/// A JSON-RPC request or notification from the client.
+#[derive(Debug, serde::Deserialize)]
+struct Message {
+    /// Set on requests and missing on notifications, which get no answer.
+    #[serde(default)]
+    id: Option<Value>,
+    /// The method called, e.g. `tools/call`.
+    method: String,
+    /// The method's arguments.
+    #[serde(default)]
+    params: Value,
+}
+
+/// An MCP server offering track's tools over a database.
+#[derive(Debug, Clone)]
+pub struct McpServer {
+    /// The database the tools work on.
+    pub pool: SqlitePool,
+    /// Only offer this deck's problems.
+    pub deck: Option<String>,
+    /// The hooks whose on_attempt runs for each attempt logged.
+    pub hooks: Hooks,
+    /// Where each attempt logged is POSTed, if anywhere.
+    pub webhook_url: Option<String>,
+}
+
+impl McpServer {
+    /// Answers messages from `input`, one JSON object per line, writing each
+    /// answer to `output` as a line, until `input` closes.
+    pub async fn run<R, W>(&self, input: R, mut output: W) -> anyhow::Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = input.lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(answer) = self.handle_line(&line).await {
+                let mut text = serde_json::to_string(&answer)?;
+                text.push('\n');
+                output.write_all(text.as_bytes()).await?;
+                output.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers one message, or returns `None` for a notification.
+    pub async fn handle_line(&self, line: &str) -> Option<Value> {
+        let message: Message = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                let error = format!("Invalid message: {}", e);
+                return Some(error_answer(Value::Null, PARSE_ERROR, &error));
+            }
+        };
+        let id = message.id?;
+        Some(match self.call(&message.method, &message.params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, error)) => error_answer(id, code, &error),
+        })
+    }
+
+    async fn call(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "track", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools() })),
+            "tools/call" => {
+                let Some(name) = params["name"].as_str() else {
+                    return Err((INVALID_PARAMS, "Missing the tool's name.".to_string()));
+                };
+                let arguments = match &params["arguments"] {
+                    Value::Null => json!({}),
+                    arguments => arguments.clone(),
+                };
+                // Failed tools are reported in the result, so the model sees why.
+                let (text, is_error) = match self.call_tool(name, arguments).await {
+                    Some(Ok(value)) => (
+                        serde_json::to_string_pretty(&value).expect("tool results serialize"),
+                        false,
+                    ),
+                    Some(Err(e)) => (format!("{:#}", e), true),
+                    None => return Err((INVALID_PARAMS, format!("Unknown tool '{}'.", name))),
+                };
+                Ok(json!({
+                    "content": [{ "type": "text", "text": text }],
+                    "isError": is_error,
+                }))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'.", method))),
+        }
+    }
+
+    /// Runs the tool `name`, or returns `None` if there's no such tool.
+    async fn call_tool(&self, name: &str, arguments: Value) -> Option<anyhow::Result<Value>> {
+        let today = Local::now().date_naive();
+        let deck = self.deck.as_deref();
+        let result = match name {
+            "get_next_problem" => self.next_problem(&arguments, today).await,
+            "log_attempt" => self.log_attempt(arguments).await,
+            "get_due_reviews" => fetch_due_problems(&self.pool, today, deck)
+                .await
+                .and_then(|due| Ok(serde_json::to_value(due)?)),
+            "get_progress_stats" => fetch_stats_report(&self.pool, deck, today)
+                .await
+                .and_then(|report| Ok(serde_json::to_value(report)?)),
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// The problem `track next --review` would pick, with how many times it's
+    /// been attempted. `null` when there's nothing left.
+    async fn next_problem(&self, arguments: &Value, today: NaiveDate) -> anyhow::Result<Value> {
+        let deck = self.deck.as_deref();
+        let tag = arguments["tag"].as_str();
+        let next = if arguments["new_only"].as_bool().unwrap_or(false) {
+            fetch_next_unattempted_problem(
+                &self.pool,
+                deck,
+                NextFilter::tagged(tag),
+                NextStrategy::default(),
+            )
+            .await?
+        } else {
+            fetch_next_problem(
+                &self.pool,
+                today,
+                deck,
+                NextFilter::tagged(tag),
+                NextStrategy::default(),
+            )
+            .await?
+        };
+        let Some(problem) = next else {
+            return Ok(Value::Null);
+        };
+        let attempts = fetch_progress(&self.pool, problem.id)
+            .await?
+            .map_or(0, |progress| progress.number_of_attempts);
+        Ok(json!({ "problem": problem, "attempts_so_far": attempts }))
+    }
+
+    async fn log_attempt(&self, arguments: Value) -> anyhow::Result<Value> {
+        let attempt: AttemptRequest =
+            serde_json::from_value(arguments).context("Invalid arguments")?;
+        match log_requested_attempt(
+            &self.pool,
+            &attempt,
+            &self.hooks,
+            self.webhook_url.as_deref(),
+        )
+        .await?
+        {
+            Ok(logged) => Ok(serde_json::to_value(logged)?),
+            Err(message) => Err(anyhow::anyhow!(message)),
+        }
+    }
+}
+
+/// The tools `tools/list` offers, with JSON schemas for their arguments.
+fn tools() -> Value {
+    json!([
+        {
+            "name": "get_next_problem",
+            "description": "Picks the LeetCode problem to work on next: the most pressing review due today, or else the next new problem in the study plan. Returns null when everything is done.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "tag": { "type": "string", "description": "Only pick problems with this tag, e.g. graph." },
+                    "new_only": { "type": "boolean", "description": "Skip reviews and pick a problem never attempted." },
+                },
+            },
+        },
+        {
+            "name": "log_attempt",
+            "description": "Records an attempt at a problem and schedules its next review.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "problem": {
+                        "type": ["integer", "string"],
+                        "description": "The problem's LeetCode ID, name, URL slug or URL.",
+                    },
+                    "rating": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": 5,
+                        "description": "How it went: 1 ShortFail (not solved, given up on quickly), 2 LongFail (not solved after a long try), 3 Messy (solved, but the solution was messy), 4 Hard (solved, but it was a struggle), 5 Easy (solved without trouble).",
+                    },
+                    "date": { "type": "string", "format": "date", "description": "The day of the attempt, YYYY-MM-DD. Defaults to today." },
+                    "lang": { "type": "string", "description": "The language it was solved in." },
+                    "minutes": { "type": "integer", "minimum": 1, "description": "How long it took." },
+                    "note": { "type": "string", "description": "Anything worth remembering for next time." },
+                },
+                "required": ["problem", "rating"],
+            },
+        },
+        {
+            "name": "get_due_reviews",
+            "description": "Lists the problems due for review today or earlier, most overdue first.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "get_progress_stats",
+            "description": "Summarizes progress: problems attempted, ratings, streaks, and breakdowns by week, difficulty and tag.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ])
+}
+
+/// A JSON-RPC error answer.
+fn error_answer(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+use crate::db::{
+    NextFilter, NextStrategy, fetch_due_problems, fetch_next_problem,
+    fetch_next_unattempted_problem, fetch_progress, fetch_stats_report,
+};
+use crate::hooks::Hooks;
+use crate::serve::{AttemptRequest, log_requested_attempt};
+use anyhow::Context;
+use chrono::{Local, NaiveDate};
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_pool;
+    use crate::problems::{LeetCodeDifficulty, Problem};
+
+    async fn server() -> McpServer {
+        let pool = init_pool("sqlite::memory:").await.unwrap();
+        Problem {
+            id: 1,
+            order: 1,
+            name: "Two Sum".to_string(),
+            difficulty: Some(LeetCodeDifficulty::Easy),
+            week: Some(1),
+            priority: 0,
+            deck: "grind-75".to_string(),
+            url: None,
+        }
+        .insert(&pool)
+        .await
+        .unwrap();
+        McpServer {
+            pool,
+            deck: None,
+            hooks: Hooks::default(),
+            webhook_url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn answers_requests_but_not_notifications() {
+        let server = server().await;
+
+        let init = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
+            .await
+            .unwrap();
+        assert_eq!(init["id"], 1);
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+        let notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert_eq!(server.handle_line(notification).await, None);
+
+        let list = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let names: Vec<&str> = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"log_attempt"));
+
+        let unknown = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"resources/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+        let garbage = server.handle_line("not json").await.unwrap();
+        assert_eq!(garbage["error"]["code"], PARSE_ERROR);
+    }
+
+    #[tokio::test]
+    async fn tools_pick_and_log_problems() {
+        let server = server().await;
+        let call = |id: i64, name: &str, arguments: Value| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": arguments },
+            })
+            .to_string()
+        };
+
+        let next = server
+            .handle_line(&call(1, "get_next_problem", json!({})))
+            .await
+            .unwrap();
+        let text = next["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("Two Sum"));
+
+        let logged = server
+            .handle_line(&call(
+                2,
+                "log_attempt",
+                json!({ "problem": "two sum", "rating": 5 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(logged["result"]["isError"], false);
+        let history = crate::db::fetch_attempt_history(&server.pool, 1)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+
+        let invalid = server
+            .handle_line(&call(
+                3,
+                "log_attempt",
+                json!({ "problem": 1, "rating": 0 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(invalid["result"]["isError"], true);
+    }
+}
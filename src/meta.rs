@@ -0,0 +1,257 @@
+// src/meta.rs
+
+/// Looks a problem's metadata up by its slug.
+const META_QUERY: &str = "query questionMeta($titleSlug: String!) { question(titleSlug: $titleSlug) { questionFrontendId title difficulty acRate topicTags { slug } } }";
+
+/// Looks up today's Daily Challenge.
+const DAILY_QUERY: &str = "query questionOfToday { activeDailyCodingChallengeQuestion { date question { questionFrontendId titleSlug title difficulty acRate topicTags { slug } } } }";
+
+/// The deck `track daily` files daily challenges under.
+pub const DAILY_DECK: &str = "daily";
+
+/// A problem's metadata on LeetCode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProblemMeta {
+    /// The number LeetCode shows for the problem (its `questionFrontendId`),
+    /// the same id builds resolve to.
+    pub question_id: i64,
+    /// The problem's canonical title.
+    pub title: String,
+    /// The problem's difficulty on LeetCode.
+    pub difficulty: LeetCodeDifficulty,
+    /// The percentage of submissions that are accepted.
+    pub ac_rate: Option<f64>,
+    /// LeetCode's topic tags, as slugs such as `hash-table`.
+    pub topic_tags: Vec<String>,
+}
+
+/// A LeetCode Daily Challenge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyChallenge {
+    /// The day it's the challenge for.
+    pub date: NaiveDate,
+    /// The problem's URL slug, e.g. `two-sum`.
+    pub title_slug: String,
+    /// The problem's metadata.
+    pub meta: ProblemMeta,
+}
+
+impl DailyChallenge {
+    /// The problem's page on LeetCode.
+    pub fn url(&self) -> String {
+        format!("https://leetcode.com/problems/{}/", self.title_slug)
+    }
+}
+
+/// What `refresh_meta` did.
+#[derive(Debug, Default)]
+pub struct MetaRefresh {
+    /// How many problems were updated.
+    pub updated: usize,
+    /// The problems whose lookup failed, by name, with why.
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+impl MetaRefresh {
+    /// Prints how many problems were updated and why the rest failed.
+    pub fn print_report(&self) {
+        println!("Updated metadata for {} problem(s).", self.updated);
+        if !self.failed.is_empty() {
+            println!("Couldn't update {} problem(s):", self.failed.len());
+            for (name, e) in &self.failed {
+                // curl's errors carry its stderr, which can run over lines.
+                let reason = format!("{:#}", e);
+                println!("  - {}: {}", name, reason.trim().replace('\n', " "));
+            }
+        }
+    }
+}
+
+/// A question as LeetCode's GraphQL API sends it.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Question {
+    /// LeetCode sends the number as a string.
+    question_frontend_id: String,
+    title: String,
+    difficulty: LeetCodeDifficulty,
+    #[serde(default)]
+    ac_rate: Option<f64>,
+    #[serde(default)]
+    topic_tags: Vec<TopicTag>,
+}
+
+/// One of a question's `topicTags`.
+#[derive(Debug, serde::Deserialize)]
+struct TopicTag {
+    slug: String,
+}
+
+/// Reads the metadata out of a `question` object from LeetCode's API.
+pub fn parse_question(question: &Value) -> anyhow::Result<ProblemMeta> {
+    let question = Question::deserialize(question)
+        .with_context(|| format!("Failed to parse metadata '{}'", question))?;
+    let question_id = question.question_frontend_id.parse().with_context(|| {
+        format!(
+            "LeetCode's questionFrontendId '{}' isn't a number.",
+            question.question_frontend_id
+        )
+    })?;
+    Ok(ProblemMeta {
+        question_id,
+        title: question.title,
+        difficulty: question.difficulty,
+        ac_rate: question.ac_rate,
+        topic_tags: question
+            .topic_tags
+            .into_iter()
+            .map(|tag| tag.slug)
+            .collect(),
+    })
+}
+
+/// Looks up the metadata for the problem at `url` on LeetCode.
+///
+/// # Errors
+/// Returns an error if `url` has no valid problem slug, which is checked
+/// before anything is sent, if LeetCode has no problem with that slug, or
+/// if the lookup fails.
+pub async fn fetch_problem_meta(url: &str) -> anyhow::Result<ProblemMeta> {
+    let slug = url_slug(url)
+        .filter(|slug| is_valid_slug(slug))
+        .with_context(|| format!("'{}' isn't a LeetCode problem URL.", url))?;
+    let data = graphql(META_QUERY, serde_json::json!({ "titleSlug": slug })).await?;
+    let question = &data["question"];
+    anyhow::ensure!(!question.is_null(), "LeetCode has no problem '{}'.", slug);
+    parse_question(question)
+}
+
+/// Looks up today's Daily Challenge on LeetCode.
+pub async fn fetch_daily_challenge() -> anyhow::Result<DailyChallenge> {
+    let data = graphql(DAILY_QUERY, serde_json::json!({})).await?;
+    parse_daily(&data["activeDailyCodingChallengeQuestion"])
+}
+
+/// Reads an `activeDailyCodingChallengeQuestion` from LeetCode's API.
+pub fn parse_daily(daily: &Value) -> anyhow::Result<DailyChallenge> {
+    let date = daily["date"]
+        .as_str()
+        .and_then(|date| date.parse().ok())
+        .with_context(|| format!("LeetCode sent no date for the daily challenge: {}", daily))?;
+    let question = &daily["question"];
+    let title_slug = question["titleSlug"]
+        .as_str()
+        .with_context(|| {
+            format!(
+                "LeetCode sent no titleSlug for the daily challenge: {}",
+                daily
+            )
+        })?
+        .to_string();
+    Ok(DailyChallenge {
+        date,
+        title_slug,
+        meta: parse_question(question)?,
+    })
+}
+
+/// Fetches and stores the metadata of every problem in `problems`, running
+/// up to `concurrency` lookups at once.
+///
+/// A failed lookup doesn't stop the rest; it's reported in the result. So is
+/// a problem whose URL turns out to belong to a different LeetCode ID, which
+/// is left untouched. Everything that was fetched is saved in one
+/// transaction.
+pub async fn refresh_meta(
+    pool: &SqlitePool,
+    problems: &[Problem],
+    concurrency: usize,
+    today: NaiveDate,
+) -> anyhow::Result<MetaRefresh> {
+    let fetched: Vec<(&Problem, anyhow::Result<ProblemMeta>)> = stream::iter(problems)
+        .map(|problem| async move {
+            let meta = fetch_problem_meta(&problem.link()).await;
+            (problem, meta)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut refresh = MetaRefresh::default();
+    let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+    for (problem, meta) in fetched {
+        match meta {
+            Ok(meta) if meta.question_id != problem.id => refresh.failed.push((
+                problem.name.clone(),
+                anyhow::anyhow!(
+                    "its URL is LeetCode problem #{}, not #{}",
+                    meta.question_id,
+                    problem.id
+                ),
+            )),
+            Ok(meta) => {
+                save_problem_meta(&mut tx, problem.id, &meta, today).await?;
+                refresh.updated += 1;
+            }
+            Err(e) => refresh.failed.push((problem.name.clone(), e)),
+        }
+    }
+    tx.commit()
+        .await
+        .context("Failed to commit problem metadata.")?;
+    refresh.failed.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(refresh)
+}
+
+use crate::db::{save_problem_meta, url_slug};
+use crate::leetcode::{graphql, is_valid_slug};
+use crate::problems::{LeetCodeDifficulty, Problem};
+use anyhow::Context;
+use chrono::NaiveDate;
+use futures::stream::{self, StreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_question() {
+        let meta = parse_question(&serde_json::json!({
+            "questionFrontendId": "1",
+            "title": "Two Sum",
+            "difficulty": "Easy",
+            "acRate": 55.2,
+            "topicTags": [{"slug": "array"}, {"slug": "hash-table"}]
+        }))
+        .unwrap();
+
+        assert_eq!(meta.question_id, 1);
+        assert_eq!(meta.difficulty, LeetCodeDifficulty::Easy);
+        assert_eq!(meta.ac_rate, Some(55.2));
+        assert_eq!(meta.topic_tags, ["array", "hash-table"]);
+        assert!(parse_question(&Value::Null).is_err());
+    }
+
+    #[test]
+    fn parses_the_daily_challenge() {
+        let daily = parse_daily(&serde_json::json!({
+            "date": "2024-03-01",
+            "question": {
+                "questionFrontendId": "1",
+                "titleSlug": "two-sum",
+                "title": "Two Sum",
+                "difficulty": "Easy",
+                "acRate": 55.2,
+                "topicTags": [{"slug": "array"}]
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(daily.date, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+        assert_eq!(daily.meta.title, "Two Sum");
+        assert_eq!(daily.url(), "https://leetcode.com/problems/two-sum/");
+    }
+}
@@ -0,0 +1,45 @@
+// src/plan.rs
+
+/// How many new problems `track today` plans per ISO week when `weekly_new`
+/// isn't set in `track.toml`: one a day.
+pub const DEFAULT_WEEKLY_NEW: u32 = 7;
+
+/// The Monday of the ISO week containing `day`.
+pub fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// How many new problems to start on `today` to reach `weekly_new` by the end
+/// of its ISO week, given how many were started earlier in the week.
+///
+/// What's left is spread evenly over the days left, today included, rounding
+/// up so the quota is met by Sunday. Only problems started before today
+/// count, so the number stays the same while you work through it.
+pub fn new_quota_for_today(weekly_new: u32, started_before_today: u32, today: NaiveDate) -> u32 {
+    let days_left = 7 - today.weekday().num_days_from_monday();
+    weekly_new
+        .saturating_sub(started_before_today)
+        .div_ceil(days_left)
+}
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn spreads_the_rest_of_the_week_over_the_days_left() {
+        // 2024-03-04 is a Monday and 2024-03-10 a Sunday.
+        assert_eq!(week_start(date("2024-03-07")), date("2024-03-04"));
+        assert_eq!(new_quota_for_today(7, 0, date("2024-03-04")), 1);
+        assert_eq!(new_quota_for_today(10, 0, date("2024-03-04")), 2);
+        assert_eq!(new_quota_for_today(7, 2, date("2024-03-09")), 3);
+        assert_eq!(new_quota_for_today(7, 5, date("2024-03-10")), 2);
+        assert_eq!(new_quota_for_today(7, 9, date("2024-03-06")), 0);
+    }
+}
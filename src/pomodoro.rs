@@ -0,0 +1,80 @@
+// src/pomodoro.rs
+
+/// What a pomodoro phase is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhaseKind {
+    /// Focused work on the problem. Only this time is logged.
+    Work,
+    /// A break between work phases.
+    Break,
+}
+
+/// One timed stretch of a pomodoro session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Phase {
+    /// Whether it's work or a break.
+    pub kind: PhaseKind,
+    /// Which work cycle it is or follows, starting at 1.
+    pub cycle: u32,
+    /// How long it lasts.
+    pub duration: Duration,
+}
+
+/// The phases of `cycles` work stretches of `work` each, with a `rest` break
+/// after every one but the last.
+pub fn plan_phases(cycles: u32, work: Duration, rest: Duration) -> Vec<Phase> {
+    (1..=cycles)
+        .flat_map(|cycle| {
+            let work = Phase {
+                kind: PhaseKind::Work,
+                cycle,
+                duration: work,
+            };
+            let rest = (cycle < cycles).then_some(Phase {
+                kind: PhaseKind::Break,
+                cycle,
+                duration: rest,
+            });
+            std::iter::once(work).chain(rest)
+        })
+        .collect()
+}
+
+/// Renders the time left in a phase as `MM:SS`.
+pub fn format_countdown(left: Duration) -> String {
+    let seconds = left.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternates_work_and_breaks_without_a_trailing_break() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        let phases = plan_phases(3, minutes(25), minutes(5));
+
+        let kinds: Vec<(PhaseKind, u32)> = phases.iter().map(|p| (p.kind, p.cycle)).collect();
+        assert_eq!(
+            kinds,
+            [
+                (PhaseKind::Work, 1),
+                (PhaseKind::Break, 1),
+                (PhaseKind::Work, 2),
+                (PhaseKind::Break, 2),
+                (PhaseKind::Work, 3),
+            ]
+        );
+        assert_eq!(phases[1].duration, minutes(5));
+        assert!(plan_phases(0, minutes(25), minutes(5)).is_empty());
+    }
+
+    #[test]
+    fn counts_down_in_minutes_and_seconds() {
+        assert_eq!(format_countdown(Duration::from_secs(25 * 60)), "25:00");
+        assert_eq!(format_countdown(Duration::from_secs(61)), "01:01");
+    }
+}
@@ -0,0 +1,109 @@
+// src/presets.rs
+
+/// A problem bank bundled into the binary, built with `track --preset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    /// The name `--preset` takes, e.g. `blind75`.
+    pub name: &'static str,
+    /// The deck the preset is built into unless --deck says otherwise.
+    pub deck: &'static str,
+    /// What the list is, for `--preset`'s error on an unknown name.
+    pub description: &'static str,
+    /// The bank's JSON, in the same format as a bank file. Its ids are the
+    /// numbers LeetCode shows (`questionFrontendId`), the ones id lookups
+    /// resolve to, so building it needs no lookups.
+    pub contents: &'static str,
+}
+
+/// Every bundled bank.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "blind75",
+        deck: "blind-75",
+        description: "the Blind 75, in 8 weeks by topic",
+        contents: include_str!("../static/presets/blind-75.json"),
+    },
+    Preset {
+        name: "neetcode150",
+        deck: "neetcode-150",
+        description: "the NeetCode 150, in 14 weeks following its roadmap",
+        contents: include_str!("../static/presets/neetcode-150.json"),
+    },
+];
+
+/// The preset called `name`, ignoring case, dashes and underscores, so
+/// `blind-75` and `Blind75` both find `blind75`.
+///
+/// # Errors
+/// Returns an error listing the presets if none is called `name`.
+pub fn find_preset(name: &str) -> anyhow::Result<&'static Preset> {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|c| !matches!(c, '-' | '_'))
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let wanted = normalize(name);
+    PRESETS
+        .iter()
+        .find(|preset| normalize(preset.name) == wanted)
+        .with_context(|| {
+            let known: Vec<String> = PRESETS
+                .iter()
+                .map(|preset| format!("{} ({})", preset.name, preset.description))
+                .collect();
+            format!(
+                "No preset called '{}'. The presets are: {}.",
+                name,
+                known.join(", ")
+            )
+        })
+}
+
+use anyhow::Context;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem_bank::parse_problems;
+
+    #[test]
+    fn finds_presets_by_loose_name() {
+        assert_eq!(find_preset("blind75").unwrap().deck, "blind-75");
+        assert_eq!(find_preset("Blind-75").unwrap().deck, "blind-75");
+        assert_eq!(find_preset("neetcode_150").unwrap().deck, "neetcode-150");
+
+        let message = find_preset("grind169").unwrap_err().to_string();
+        assert!(message.contains("blind75"));
+        assert!(message.contains("neetcode150"));
+    }
+
+    #[test]
+    fn presets_are_valid_banks_in_week_order() {
+        for (preset, size) in PRESETS.iter().zip([75, 150]) {
+            let problems = parse_problems(preset.contents).unwrap();
+            assert_eq!(problems.len(), size, "{}", preset.name);
+            for (index, problem) in problems.iter().enumerate() {
+                assert_eq!(problem.order, index as i64 + 1, "{}", problem.name);
+                assert!(problem.difficulty.is_some(), "{}", problem.name);
+                assert!(!problem.tags.is_empty(), "{}", problem.name);
+                assert!(problem.url.starts_with("https://leetcode.com/problems/"));
+            }
+            assert!(problems.windows(2).all(|pair| pair[0].week <= pair[1].week));
+        }
+    }
+
+    #[test]
+    fn presets_use_the_ids_leetcode_shows() {
+        // Each of these has a different internal questionId.
+        let problems = parse_problems(find_preset("neetcode150").unwrap().contents).unwrap();
+        for (name, id) in [
+            ("Koko Eating Bananas", 875),
+            ("Partition Labels", 763),
+            ("Time Based Key-Value Store", 981),
+        ] {
+            let problem = problems.iter().find(|p| p.name == name).unwrap();
+            assert_eq!(problem.id, id, "{}", name);
+        }
+    }
+}
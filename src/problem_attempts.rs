@@ -1,23 +1,184 @@
-#[derive(Debug, FromRow)]
+/// A problem's progress: its latest attempt and when to review it next.
+#[derive(Debug, FromRow, serde::Serialize, serde::Deserialize)]
 pub struct ProblemAttempt {
+    /// The LeetCode ID of the problem.
     pub problem_id: i64,
+    /// When the problem was last attempted.
     pub last_attempted: NaiveDate,
+    /// The rating of the latest attempt.
     pub attempt_rating: AttemptRating,
+    /// When the problem is due for review. `None` if it's not scheduled.
     pub next_attempt_date: Option<NaiveDate>,
+    /// How many times the problem has been attempted.
     pub number_of_attempts: i64,
+    /// The language of the latest attempt, if recorded.
+    pub language: Option<String>,
+    /// Set by `track master`. Mastered problems are never scheduled again.
+    pub mastered: bool,
+    /// Successful attempts in a row, counting back from the latest. A failed
+    /// attempt resets it, so the review intervals climb from a day again.
+    #[serde(default)]
+    pub repetitions: i64,
+    /// The problem's ease factor, which successes adjust. See
+    /// `schedule::Schedule`.
+    #[serde(default = "default_ease")]
+    pub ease: f64,
 }
 
-#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+/// The ease a progress row without one (say, in an older snapshot) gets.
+fn default_ease() -> f64 {
+    schedule::DEFAULT_EASE
+}
+
+/// How an attempt went, from best to worst.
+#[derive(
+    Hash, Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize,
+)]
 #[sqlx(type_name = "TEXT")]
 pub enum AttemptRating {
+    /// Solved without trouble.
     Easy,
+    /// Solved, but it was a struggle.
     Hard,
+    /// Solved, but the solution was messy.
     Messy,
+    /// Not solved after a long try.
     LongFail,
+    /// Not solved, and given up on quickly.
     ShortFail,
 }
 
+impl AttemptRating {
+    /// Every rating, from best to worst.
+    pub const ALL: [AttemptRating; 5] = [
+        AttemptRating::Easy,
+        AttemptRating::Hard,
+        AttemptRating::Messy,
+        AttemptRating::LongFail,
+        AttemptRating::ShortFail,
+    ];
+
+    /// The rating's position on the CLI's 1-5 scale, where 5 is best.
+    pub fn score(&self) -> u8 {
+        match self {
+            AttemptRating::ShortFail => 1,
+            AttemptRating::LongFail => 2,
+            AttemptRating::Messy => 3,
+            AttemptRating::Hard => 4,
+            AttemptRating::Easy => 5,
+        }
+    }
+
+    /// Whether the rating is a failure (LongFail or ShortFail), which
+    /// restarts the problem's run of successes.
+    pub fn is_fail(&self) -> bool {
+        matches!(self, AttemptRating::LongFail | AttemptRating::ShortFail)
+    }
+
+    /// The inverse of `score`. Returns `None` outside of 1-5.
+    pub fn from_score(score: u8) -> Option<AttemptRating> {
+        AttemptRating::ALL
+            .into_iter()
+            .find(|rating| rating.score() == score)
+    }
+
+    /// The rating's name, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttemptRating::Easy => "Easy",
+            AttemptRating::Hard => "Hard",
+            AttemptRating::Messy => "Messy",
+            AttemptRating::LongFail => "LongFail",
+            AttemptRating::ShortFail => "ShortFail",
+        }
+    }
+}
+
+/// A coarse summary of where a problem stands, derived from its progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Serialize)]
+pub enum ProgressStatus {
+    /// Solved easily, more than once.
+    Mastered,
+    /// Solved, but not yet cleanly and repeatedly.
+    #[value(name = "inprogress", alias = "in-progress")]
+    InProgress,
+    /// The most recent attempt failed.
+    Struggling,
+}
+
+impl ProgressStatus {
+    /// The minimum number of attempts before an Easy rating counts as mastered.
+    pub const MASTERED_MIN_ATTEMPTS: i64 = 2;
+
+    /// Classifies a problem from its latest rating and how often it's been tried.
+    pub fn classify(latest_rating: AttemptRating, number_of_attempts: i64) -> ProgressStatus {
+        match latest_rating {
+            AttemptRating::ShortFail | AttemptRating::LongFail => ProgressStatus::Struggling,
+            AttemptRating::Easy if number_of_attempts >= Self::MASTERED_MIN_ATTEMPTS => {
+                ProgressStatus::Mastered
+            }
+            AttemptRating::Easy | AttemptRating::Hard | AttemptRating::Messy => {
+                ProgressStatus::InProgress
+            }
+        }
+    }
+}
+
+impl fmt::Display for ProgressStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            ProgressStatus::Mastered => "Mastered",
+            ProgressStatus::InProgress => "In progress",
+            ProgressStatus::Struggling => "Struggling",
+        })
+    }
+}
+
+impl Ord for AttemptRating {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+impl PartialOrd for AttemptRating {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for AttemptRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `pad` rather than `write_str` so width/alignment flags are honored.
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for AttemptRating {
+    type Err = anyhow::Error;
+
+    /// Parses a rating name (case-insensitively) or its 1-5 score.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(score) = s.parse::<u8>() {
+            return AttemptRating::from_score(score)
+                .with_context(|| format!("Rating score must be between 1 and 5, got {}", score));
+        }
+
+        AttemptRating::ALL
+            .into_iter()
+            .find(|rating| rating.as_str().eq_ignore_ascii_case(s))
+            .with_context(|| {
+                format!(
+                    "Unknown rating '{}'. Expected one of Easy, Hard, Messy, LongFail, ShortFail or 1-5.",
+                    s
+                )
+            })
+    }
+}
+
 impl ProblemAttempt {
+    /// Progress for a first attempt on `attempt_date` (today if `None`),
+    /// scheduled for its first review.
     pub fn new_attempt(
         problem_id: i64,
         attempt_rating: AttemptRating,
@@ -28,15 +189,24 @@ impl ProblemAttempt {
             None => Local::now().date_naive(),
         };
 
-        ProblemAttempt {
+        let mut attempt = ProblemAttempt {
             problem_id,
             last_attempted,
             attempt_rating,
-            next_attempt_date: next_interval(attempt_rating, 0).map(|days| last_attempted + days),
+            next_attempt_date: None,
             number_of_attempts: 1,
-        }
+            language: None,
+            mastered: false,
+            repetitions: 0,
+            ease: schedule::DEFAULT_EASE,
+        };
+        attempt.set_review_state(schedule::current().review(ReviewState::NEW, attempt_rating));
+        attempt.reschedule();
+        attempt
     }
 
+    /// Records another attempt on `attempt_date` (today if `None`) and
+    /// reschedules the review.
     pub fn update_attempt(
         &mut self,
         latest_rating: AttemptRating,
@@ -44,24 +214,281 @@ impl ProblemAttempt {
     ) {
         self.attempt_rating = latest_rating;
         self.number_of_attempts += 1;
+        self.set_review_state(schedule::current().review(self.review_state(), latest_rating));
+        // Practicing a mastered problem again puts it back into rotation.
+        self.mastered = false;
 
         self.last_attempted = match attempt_date {
             Some(date) => date,
             None => Local::now().date_naive(),
         };
 
-        self.next_attempt_date = next_interval(latest_rating, self.number_of_attempts)
-            .map(|days| self.last_attempted + days);
+        self.reschedule();
+    }
+
+    /// Where the problem stands in the schedule.
+    pub fn review_state(&self) -> ReviewState {
+        ReviewState {
+            repetitions: self.repetitions,
+            ease: self.ease,
+        }
+    }
+
+    /// Stores `state` as where the problem stands in the schedule.
+    pub fn set_review_state(&mut self, state: ReviewState) {
+        self.repetitions = state.repetitions;
+        self.ease = state.ease;
+    }
+
+    /// Recomputes `next_attempt_date` from the stored rating, review state and
+    /// last attempt date. Mastered problems get no date at all.
+    pub fn reschedule(&mut self) {
+        self.next_attempt_date = if self.mastered {
+            None
+        } else {
+            next_interval(self.attempt_rating, self.review_state())
+                .map(|days| self.last_attempted + days)
+        };
+    }
+
+    /// Where the problem stands. Problems marked mastered are always Mastered.
+    pub fn status(&self) -> ProgressStatus {
+        if self.mastered {
+            return ProgressStatus::Mastered;
+        }
+        ProgressStatus::classify(self.attempt_rating, self.number_of_attempts)
+    }
+
+    /// Marks the problem mastered, taking it out of the review schedule.
+    pub fn master(&mut self) {
+        self.mastered = true;
+        self.reschedule();
     }
 }
 
-fn next_interval(
+/// The `next_attempt_date` that logging `rating` on `attempt_date` would
+/// produce, for a problem in `state` beforehand (`ReviewState::NEW` if it's
+/// never been tried). Nothing is modified.
+pub fn preview_next_attempt_date(
+    rating: AttemptRating,
+    state: ReviewState,
+    attempt_date: NaiveDate,
+) -> Option<NaiveDate> {
+    let after = schedule::current().review(state, rating);
+    next_interval(rating, after).map(|days| attempt_date + days)
+}
+
+/// How long to wait before the next attempt, using the installed
+/// `schedule::Schedule` (SM-2 style by default, or `[schedule]` in track.toml),
+/// after an attempt rated `most_recent_attempt_rating` that left the problem
+/// in `state`.
+pub fn next_interval(
     most_recent_attempt_rating: AttemptRating,
-    total_number_of_attempts: i64,
+    state: ReviewState,
 ) -> Option<Duration> {
-    let very_clever_calculation_for_days = 1;
-    Some(Duration::days(very_clever_calculation_for_days))
+    Some(schedule::current().interval(most_recent_attempt_rating, state))
 }
 
+use crate::schedule::{self, ReviewState};
+use anyhow::Context;
 use chrono::{Duration, Local, NaiveDate};
 use sqlx::FromRow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_match_the_cli_scale() {
+        assert_eq!(AttemptRating::ShortFail.score(), 1);
+        assert_eq!(AttemptRating::Easy.score(), 5);
+        for rating in AttemptRating::ALL {
+            assert_eq!(AttemptRating::from_score(rating.score()), Some(rating));
+        }
+        assert_eq!(AttemptRating::from_score(0), None);
+        assert_eq!(AttemptRating::from_score(6), None);
+    }
+
+    #[test]
+    fn ratings_order_from_worst_to_best() {
+        let mut ratings = AttemptRating::ALL.to_vec();
+        ratings.sort();
+        assert_eq!(
+            ratings,
+            vec![
+                AttemptRating::ShortFail,
+                AttemptRating::LongFail,
+                AttemptRating::Messy,
+                AttemptRating::Hard,
+                AttemptRating::Easy,
+            ]
+        );
+        assert!(AttemptRating::Easy > AttemptRating::Hard);
+    }
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        for rating in AttemptRating::ALL {
+            assert_eq!(rating.to_string().parse::<AttemptRating>().unwrap(), rating);
+        }
+    }
+
+    #[test]
+    fn from_str_accepts_scores_and_any_case() {
+        assert_eq!(
+            "longfail".parse::<AttemptRating>().unwrap(),
+            AttemptRating::LongFail
+        );
+        assert_eq!("4".parse::<AttemptRating>().unwrap(), AttemptRating::Hard);
+        assert!("7".parse::<AttemptRating>().is_err());
+        assert!("great".parse::<AttemptRating>().is_err());
+    }
+
+    #[test]
+    fn easy_twice_is_mastered() {
+        assert_eq!(
+            ProgressStatus::classify(AttemptRating::Easy, 2),
+            ProgressStatus::Mastered
+        );
+        assert_eq!(
+            ProgressStatus::classify(AttemptRating::Easy, 5),
+            ProgressStatus::Mastered
+        );
+    }
+
+    #[test]
+    fn a_single_easy_attempt_is_still_in_progress() {
+        assert_eq!(
+            ProgressStatus::classify(AttemptRating::Easy, 1),
+            ProgressStatus::InProgress
+        );
+    }
+
+    #[test]
+    fn hard_and_messy_are_in_progress() {
+        for rating in [AttemptRating::Hard, AttemptRating::Messy] {
+            assert_eq!(
+                ProgressStatus::classify(rating, 1),
+                ProgressStatus::InProgress
+            );
+            assert_eq!(
+                ProgressStatus::classify(rating, 4),
+                ProgressStatus::InProgress
+            );
+        }
+    }
+
+    #[test]
+    fn failures_are_struggling_regardless_of_attempts() {
+        for rating in [AttemptRating::ShortFail, AttemptRating::LongFail] {
+            assert_eq!(
+                ProgressStatus::classify(rating, 1),
+                ProgressStatus::Struggling
+            );
+            assert_eq!(
+                ProgressStatus::classify(rating, 6),
+                ProgressStatus::Struggling
+            );
+        }
+    }
+
+    #[test]
+    fn display_honors_padding() {
+        assert_eq!(format!("{:<6}|", AttemptRating::Easy), "Easy  |");
+    }
+
+    #[test]
+    fn mastered_problems_are_never_rescheduled_until_attempted_again() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let mut attempt = ProblemAttempt::new_attempt(1, AttemptRating::Easy, Some(day));
+        assert!(attempt.next_attempt_date.is_some());
+
+        attempt.master();
+        assert_eq!(attempt.next_attempt_date, None);
+        attempt.reschedule();
+        assert_eq!(attempt.next_attempt_date, None);
+
+        attempt.update_attempt(AttemptRating::Messy, Some(day));
+        assert!(!attempt.mastered);
+        assert!(attempt.next_attempt_date.is_some());
+    }
+
+    #[test]
+    fn preview_matches_what_logging_the_attempt_would_schedule() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        for rating in AttemptRating::ALL {
+            let first = ProblemAttempt::new_attempt(1, rating, Some(day));
+            assert_eq!(
+                preview_next_attempt_date(rating, ReviewState::NEW, day),
+                first.next_attempt_date
+            );
+
+            let before = first.review_state();
+            let mut second = first;
+            second.update_attempt(rating, Some(day));
+            assert_eq!(
+                preview_next_attempt_date(rating, before, day),
+                second.next_attempt_date
+            );
+        }
+    }
+
+    /// The gap after the last of `ratings`, logged in order on a new problem.
+    fn days_after(ratings: &[AttemptRating]) -> i64 {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let mut attempt = ProblemAttempt::new_attempt(1, ratings[0], Some(day));
+        for &rating in &ratings[1..] {
+            attempt.update_attempt(rating, Some(day));
+        }
+        (attempt.next_attempt_date.unwrap() - day).num_days()
+    }
+
+    #[test]
+    fn intervals_follow_sm2() {
+        use AttemptRating::*;
+        assert_eq!(days_after(&[Easy]), 1);
+        assert_eq!(days_after(&[Easy, Easy]), 6);
+        // Each Easy raises the ease by 0.1 (to 2.8 here), Hard keeps it at
+        // 2.5 and Messy lowers it by 0.14 (to 2.08).
+        assert_eq!(days_after(&[Easy, Easy, Easy]), 17);
+        assert_eq!(days_after(&[Hard, Hard, Hard]), 15);
+        assert_eq!(days_after(&[Messy, Messy, Messy]), 12);
+        assert!(days_after(&[Easy; 5]) > days_after(&[Easy; 4]));
+    }
+
+    #[test]
+    fn failures_restart_the_schedule() {
+        use AttemptRating::*;
+        for rating in [ShortFail, LongFail] {
+            assert_eq!(days_after(&[rating]), 1);
+            assert_eq!(days_after(&[Easy, Easy, Easy, rating]), 1);
+        }
+        // A success after failing climbs from a day again, however many
+        // attempts came before.
+        assert_eq!(
+            days_after(&[ShortFail, ShortFail, ShortFail, ShortFail, ShortFail, Easy]),
+            1
+        );
+        assert_eq!(days_after(&[Easy, Easy, Easy, LongFail, Easy, Easy]), 6);
+        // The ease earned before the failure is kept.
+        assert_eq!(
+            days_after(&[Easy, Easy, Easy, LongFail, Easy, Easy, Easy]),
+            19
+        );
+    }
+
+    #[test]
+    fn intervals_are_capped() {
+        let long_run = ReviewState {
+            repetitions: 1_000,
+            ease: schedule::DEFAULT_EASE,
+        };
+        assert_eq!(
+            next_interval(AttemptRating::Easy, long_run),
+            Some(Duration::days(schedule::MAX_INTERVAL_DAYS))
+        );
+    }
+}
@@ -1,78 +1,416 @@
+/// One entry in a problem bank JSON file.
 #[derive(Debug, serde::Deserialize)]
 pub struct ProblemBankProblem {
+    /// The id as written in the bank, or 0 when it isn't known. Builds look
+    /// the real one up by name.
     pub id: i64,
+    /// Where the problem sits in the bank.
     pub order: i64,
+    /// The problem's name.
     pub name: String,
+    /// The problem's difficulty, if known.
     pub difficulty: Option<LeetCodeDifficulty>,
+    /// The study-plan week the problem belongs to.
     pub week: Option<i64>,
+    /// The problem's page.
     pub url: String,
+    /// Topics such as "dp" or "graph". Optional in the bank file.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl ProblemBankProblem {
-    pub fn get_id(&self) -> anyhow::Result<i64> {
-        let script_path = "./static/scripts/get_lc_id.sh";
-
-        // 1. Set up the command to run the shell script.
-        let output = Command::new(script_path)
-            .arg(&self.url) // Pass the problem's URL as the first argument
-            .output()
-            .with_context(|| format!("Failed to execute script at '{}'. Is it executable (`chmod +x`) and in the correct path?", script_path))?;
-
-        // 2. Check if the script itself exited with an error.
-        if !output.status.success() {
-            // If the script failed, its error messages are usually on stderr.
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!(
-                "Script execution failed with status {}:\n{}",
-                output.status,
-                stderr
-            );
-        }
+    /// Resolves this problem's LeetCode id (the number LeetCode shows) from
+    /// its URL's slug, asking LeetCode's GraphQL API.
+    ///
+    /// Failed requests are retried up to `max_attempts` times with exponential
+    /// backoff, since those are usually transient. A slug LeetCode doesn't
+    /// know fails immediately, and so does a lookup when curl isn't installed.
+    pub async fn get_id(&self, max_attempts: u32) -> anyhow::Result<i64> {
+        let max_attempts = max_attempts.max(1);
+        let mut attempt = 1;
 
-        // 3. Process the successful output (stdout).
-        let stdout_str = String::from_utf8(output.stdout)
-            .context("Failed to read script output as UTF-8 string.")?;
+        let found = loop {
+            match fetch_frontend_id(self.slug()).await {
+                Ok(found) => break found,
+                Err(e)
+                    if attempt < max_attempts
+                        && is_valid_slug(self.slug())
+                        && !is_curl_missing(&e) =>
+                {
+                    let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                    eprintln!(
+                        "Id lookup for '{}' failed (attempt {}/{}), retrying in {:?}: {:#}",
+                        self.name, attempt, max_attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "Id lookup for '{}' failed after {} attempt(s)",
+                        self.name, attempt
+                    )));
+                }
+            }
+        };
 
-        // 4. Trim whitespace (like newlines) and parse the string into an i64.
-        let parsed_id = stdout_str.trim().parse::<i64>().with_context(|| {
+        found.with_context(|| {
             format!(
-                "Failed to parse script output '{}' as a number.",
-                stdout_str.trim()
+                "LeetCode has no problem at '{}'. Is the URL right?",
+                self.url
             )
-        })?;
+        })
+    }
 
-        Ok(parsed_id)
+    /// Converts the entry into a `Problem` in `deck`, looking its id up on
+    /// LeetCode.
+    pub async fn to_problem(&self, deck: &str, max_attempts: u32) -> anyhow::Result<Problem> {
+        let id = self.get_id(max_attempts).await?;
+        Ok(self.to_problem_with_id(deck, id))
     }
 
-    pub fn to_problem(&self) -> anyhow::Result<Problem> {
-        Ok(Problem {
-            id: self.get_id()?,
+    /// Builds the `Problem` for an id that's already known, e.g. from the
+    /// resolved id cache.
+    pub fn to_problem_with_id(&self, deck: &str, id: i64) -> Problem {
+        Problem {
+            id,
             order: self.order,
             name: self.name.clone(),
             difficulty: self.difficulty,
             week: self.week,
-        })
+            priority: 0,
+            deck: deck.to_string(),
+            url: Some(self.url.clone()),
+        }
+    }
+
+    /// The problem's slug: the last path segment of its URL, e.g. `two-sum`
+    /// for `https://leetcode.com/problems/two-sum/`.
+    pub fn slug(&self) -> &str {
+        self.url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+/// The delay before the first retry of a failed id lookup. Each further retry
+/// doubles it.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Where bank files live unless configured otherwise.
+pub const DEFAULT_STATIC_DIR: &str = "./static";
+
+/// Loads and validates the bank file `name` from `static_dir`.
+pub fn load_problems(static_dir: &Path, name: &str) -> anyhow::Result<Vec<ProblemBankProblem>> {
+    parse_problems(&read_bank(static_dir, name)?)
+}
+
+/// Like `load_problems`, but keeps entries that share an id or `order`, for
+/// tools that look for those duplicates.
+pub fn load_problems_allowing_duplicates(
+    static_dir: &Path,
+    name: &str,
+) -> anyhow::Result<Vec<ProblemBankProblem>> {
+    parse_entries(&read_bank(static_dir, name)?, false)
+}
+
+/// Reads a bank file from `static_dir`.
+fn read_bank(static_dir: &Path, name: &str) -> anyhow::Result<String> {
+    let path = static_dir.join(name);
+
+    fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read bank file {}", path.display()))
+}
+
+/// Parses and validates the contents of a bank file.
+///
+/// Each entry is deserialized on its own so a bad field is reported with the
+/// entry's position and the field's path. Duplicate `order` values and ids are
+/// rejected too. Every failing entry is collected into a single error rather
+/// than stopping at the first.
+pub fn parse_problems(contents: &str) -> anyhow::Result<Vec<ProblemBankProblem>> {
+    parse_entries(contents, true)
+}
+
+fn parse_entries(
+    contents: &str,
+    check_duplicates: bool,
+) -> anyhow::Result<Vec<ProblemBankProblem>> {
+    let Entries {
+        problems, errors, ..
+    } = collect_entries(contents, check_duplicates)?;
+    if !errors.is_empty() {
+        anyhow::bail!(
+            "{} problem(s) in the bank are invalid:\n  - {}",
+            errors.len(),
+            errors.join("\n  - ")
+        );
     }
+
+    Ok(problems.into_iter().map(|(_, problem)| problem).collect())
 }
 
-pub fn load_problems(name: &str) -> anyhow::Result<Vec<ProblemBankProblem>> {
-    let mut path = PathBuf::from(".");
-    path.push("static");
-    path.push(name);
+/// A bank file's entries, as far as they could be read.
+struct Entries {
+    /// How many entries there are, valid or not.
+    count: usize,
+    /// The valid entries, with their index in the file.
+    problems: Vec<(usize, ProblemBankProblem)>,
+    /// What's wrong with the rest.
+    errors: Vec<String>,
+}
 
-    let file = File::open(path)?;
+/// Deserializes every entry it can, and describes what's wrong with the rest.
+/// Only fails if the contents aren't a JSON array at all.
+fn collect_entries(contents: &str, check_duplicates: bool) -> anyhow::Result<Entries> {
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(contents).context("Bank file is not a JSON array")?;
+    let count = entries.len();
 
-    let reader = BufReader::new(file);
+    let mut problems = Vec::with_capacity(entries.len());
+    let mut errors = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        match serde_path_to_error::deserialize::<_, ProblemBankProblem>(entry) {
+            Ok(problem) if problem.url.trim().is_empty() => errors.push(format!(
+                "entry #{}: '{}' has no `url`; builds look its id up from it",
+                index + 1,
+                problem.name
+            )),
+            Ok(problem) => problems.push((index, problem)),
+            // Missing fields are reported against the entry itself, whose
+            // path is just ".".
+            Err(e) if e.path().iter().next().is_none() => {
+                errors.push(format!("entry #{}: {}", index + 1, e.inner()))
+            }
+            Err(e) => errors.push(format!(
+                "entry #{}: field `{}`: {}",
+                index + 1,
+                e.path(),
+                e.inner()
+            )),
+        }
+    }
 
-    let problems = serde_json::from_reader(reader)?;
+    let mut orders = HashMap::new();
+    let mut ids = HashMap::new();
+    let to_check = if check_duplicates { &problems[..] } else { &[] };
+    for (index, problem) in to_check {
+        if let Some(first) = orders.insert(problem.order, *index) {
+            errors.push(format!(
+                "entry #{}: duplicate order {} (first used by entry #{})",
+                index + 1,
+                problem.order,
+                first + 1
+            ));
+        }
+        // Imported banks leave every id at 0 for the build to look up.
+        if problem.id == 0 {
+            continue;
+        }
+        if let Some(first) = ids.insert(problem.id, *index) {
+            errors.push(format!(
+                "entry #{}: duplicate id {} (first used by entry #{})",
+                index + 1,
+                problem.id,
+                first + 1
+            ));
+        }
+    }
+
+    Ok(Entries {
+        count,
+        problems,
+        errors,
+    })
+}
+
+/// What `check_bank` found in a bank file.
+#[derive(Debug, Default)]
+pub struct BankCheck {
+    /// How many entries the bank has, valid or not.
+    pub entries: usize,
+    /// Problems that would stop the bank from building.
+    pub errors: Vec<String>,
+    /// Things that build, but are probably mistakes.
+    pub warnings: Vec<String>,
+}
 
-    Ok(problems)
+impl BankCheck {
+    /// Whether the bank would build.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
 }
 
+/// Checks a bank file's contents without building it: everything a build
+/// would reject, i.e. bad fields, missing URLs and duplicate ids or orders,
+/// plus warnings for gaps in the weeks and entries without one.
+///
+/// # Errors
+/// Returns an error only if the contents aren't a JSON array.
+pub fn check_bank(contents: &str) -> anyhow::Result<BankCheck> {
+    let Entries {
+        count,
+        problems,
+        errors,
+    } = collect_entries(contents, true)?;
+    let mut warnings = Vec::new();
+
+    for (index, problem) in &problems {
+        if !problem.url.trim().is_empty() && !problem.url.contains("leetcode.com/problems/") {
+            warnings.push(format!(
+                "entry #{}: '{}' has a URL that isn't a LeetCode problem page: {}",
+                index + 1,
+                problem.name,
+                problem.url
+            ));
+        }
+    }
+
+    let without_week: Vec<&str> = problems
+        .iter()
+        .filter(|(_, p)| p.week.is_none())
+        .map(|(_, p)| p.name.as_str())
+        .collect();
+    if !without_week.is_empty() && without_week.len() < problems.len() {
+        warnings.push(format!(
+            "{} problem(s) have no `week` while the rest do: {}",
+            without_week.len(),
+            without_week.join(", ")
+        ));
+    }
+
+    let weeks: BTreeSet<i64> = problems.iter().filter_map(|(_, p)| p.week).collect();
+    for gap in week_gaps(&weeks) {
+        warnings.push(match gap {
+            (first, last) if first == last => format!("week {} has no problems", first),
+            (first, last) => format!("weeks {}-{} have no problems", first, last),
+        });
+    }
+
+    Ok(BankCheck {
+        entries: count,
+        errors,
+        warnings,
+    })
+}
+
+/// The runs of weeks missing between the first and last week in `weeks`, as
+/// inclusive `(first, last)` pairs.
+fn week_gaps(weeks: &BTreeSet<i64>) -> Vec<(i64, i64)> {
+    weeks
+        .iter()
+        .zip(weeks.iter().skip(1))
+        .filter(|(week, next)| *next - *week > 1)
+        .map(|(week, next)| (week + 1, next - 1))
+        .collect()
+}
+
+use crate::leetcode::{fetch_frontend_id, is_curl_missing, is_valid_slug};
 use crate::problems::*;
 use anyhow::Context;
-use std::fs::File;
-use std::io::BufReader;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
 use std::path::Path;
-use std::path::PathBuf;
-use std::process::Command;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_valid_bank() {
+        let contents = r#"[
+            {"id": 1, "order": 1, "name": "Two Sum", "difficulty": "Easy", "week": 1, "url": "u"},
+            {"id": 20, "order": 2, "name": "Valid Parentheses", "difficulty": null, "week": null, "url": "u"}
+        ]"#;
+
+        let problems = parse_problems(contents).unwrap();
+
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[1].name, "Valid Parentheses");
+    }
+
+    #[test]
+    fn reports_every_invalid_entry_with_its_field() {
+        let contents = r#"[
+            {"id": 1, "order": "one", "name": "Two Sum", "url": "u"},
+            {"id": 20, "order": 2, "name": "Valid Parentheses", "difficulty": "Trivial", "url": "u"},
+            {"id": 21, "order": 3, "name": "Merge Two Sorted Lists"}
+        ]"#;
+
+        let message = format!("{:#}", parse_problems(contents).unwrap_err());
+
+        assert!(message.contains("3 problem(s)"));
+        assert!(message.contains("entry #1: field `order`"));
+        assert!(message.contains("entry #2: field `difficulty`"));
+        assert!(message.contains("entry #3: missing field `url`"));
+    }
+
+    #[test]
+    fn rejects_duplicate_orders_and_ids() {
+        let contents = r#"[
+            {"id": 1, "order": 1, "name": "Two Sum", "url": "u"},
+            {"id": 1, "order": 1, "name": "Two Sum Again", "url": "u"}
+        ]"#;
+
+        let message = format!("{:#}", parse_problems(contents).unwrap_err());
+
+        assert!(message.contains("entry #2: duplicate order 1 (first used by entry #1)"));
+        assert!(message.contains("entry #2: duplicate id 1 (first used by entry #1)"));
+
+        let unknown_ids = r#"[
+            {"id": 0, "order": 1, "name": "Two Sum", "url": "u"},
+            {"id": 0, "order": 2, "name": "Valid Parentheses", "url": "v"}
+        ]"#;
+        assert_eq!(parse_problems(unknown_ids).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn checks_for_missing_urls_and_week_gaps() {
+        let check = check_bank(
+            r#"[
+            {"id": 1, "order": 1, "name": "Two Sum", "week": 1, "url": "https://leetcode.com/problems/two-sum/"},
+            {"id": 20, "order": 2, "name": "Valid Parentheses", "week": 2, "url": " "},
+            {"id": 21, "order": 3, "name": "Merge Two Sorted Lists", "week": 3, "url": "https://example.com/merge"},
+            {"id": 70, "order": 4, "name": "Climbing Stairs", "url": "https://leetcode.com/problems/climbing-stairs/"},
+            {"id": 121, "order": 5, "name": "Best Time to Buy and Sell Stock", "week": 6, "url": "https://leetcode.com/problems/best-time-to-buy-and-sell-stock/"}
+        ]"#,
+        )
+        .unwrap();
+
+        assert!(!check.is_valid());
+        assert_eq!(check.entries, 5);
+        assert_eq!(
+            check.errors,
+            ["entry #2: 'Valid Parentheses' has no `url`; builds look its id up from it"]
+        );
+        assert_eq!(
+            check.warnings,
+            [
+                "entry #3: 'Merge Two Sorted Lists' has a URL that isn't a LeetCode problem page: https://example.com/merge",
+                "1 problem(s) have no `week` while the rest do: Climbing Stairs",
+                "week 2 has no problems",
+                "weeks 4-5 have no problems",
+            ]
+        );
+        assert!(check_bank("{}").is_err());
+    }
+
+    #[test]
+    fn slug_is_the_last_path_segment() {
+        let problems = parse_problems(
+            r#"[
+            {"id": 1, "order": 1, "name": "Two Sum", "url": "https://leetcode.com/problems/two-sum/"},
+            {"id": 2, "order": 2, "name": "Add Two Numbers", "url": "https://leetcode.com/problems/add-two-numbers"}
+        ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(problems[0].slug(), "two-sum");
+        assert_eq!(problems[1].slug(), "add-two-numbers");
+    }
+}
@@ -1,36 +1,619 @@
 // src/problem_bank_populator.rs
 
+use crate::db::{
+    cache_resolved_id, fetch_all_problems, fetch_problem, fetch_problem_id_by_name,
+    fetch_resolved_ids, rekey_problem, remove_from_deck, set_problem_tags, url_slug,
+};
+use crate::leetcode::is_curl_missing;
+use crate::meta::refresh_meta;
+use crate::presets::Preset;
 use crate::problem_bank::*;
 use crate::problems::*;
 use anyhow::Context;
+use chrono::Local;
+use futures::stream::{self, StreamExt};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
-pub async fn populate_problem_bank(pool: &SqlitePool, bank_name: &str) -> anyhow::Result<()> {
-    println!("Attempting to load problem bank: '{}'...", bank_name);
+/// The number of id lookups `populate_problem_bank` runs at once by default.
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// How many times a single id lookup is tried before giving up, by default.
+pub const DEFAULT_RESOLVE_ATTEMPTS: u32 = 3;
+
+/// `println!` unless the options passed first ask for quiet.
+macro_rules! say {
+    ($options:expr, $($arg:tt)*) => {
+        if !$options.quiet {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Tuning knobs for `populate_problem_bank`.
+#[derive(Debug, Clone)]
+pub struct PopulateOptions {
+    /// How many id lookups run at once.
+    pub concurrency: usize,
+    /// How many times each id lookup is tried before the build fails.
+    pub resolve_attempts: u32,
+    /// Load and resolve the bank, report what would change, but write nothing.
+    pub dry_run: bool,
+    /// The deck to file the problems under. Defaults to the bank's file stem.
+    pub deck: Option<String>,
+    /// Draw a progress bar on stdout while resolving and inserting.
+    pub show_progress: bool,
+    /// The directory holding bank files.
+    pub static_dir: PathBuf,
+    /// Delete problems in the deck that are no longer in the bank, archiving
+    /// their progress. Without it they're only reported.
+    pub prune: bool,
+    /// After syncing, fetch each problem's difficulty, topic tags and
+    /// acceptance rate from LeetCode.
+    pub fetch_meta: bool,
+    /// Only print the dry-run report and problems needing attention, not
+    /// progress messages and summaries.
+    pub quiet: bool,
+    /// Use each entry's id as written instead of looking it up. Only for
+    /// banks whose ids are known to be the ones lookups resolve to, like the
+    /// bundled presets.
+    pub trust_ids: bool,
+}
+
+impl Default for PopulateOptions {
+    fn default() -> Self {
+        PopulateOptions {
+            concurrency: DEFAULT_CONCURRENCY,
+            resolve_attempts: DEFAULT_RESOLVE_ATTEMPTS,
+            dry_run: false,
+            deck: None,
+            show_progress: false,
+            static_dir: PathBuf::from(DEFAULT_STATIC_DIR),
+            prune: false,
+            fetch_meta: false,
+            quiet: false,
+            trust_ids: false,
+        }
+    }
+}
+
+/// Builds the bank `bank_name` from the static directory into the database,
+/// upserting each problem and reporting what changed.
+pub async fn populate_problem_bank(
+    pool: &SqlitePool,
+    bank_name: &str,
+    options: &PopulateOptions,
+) -> anyhow::Result<()> {
+    let deck = options
+        .deck
+        .clone()
+        .unwrap_or_else(|| default_deck_name(bank_name));
+    say!(
+        options,
+        "Attempting to load problem bank: '{}' into deck '{}'...",
+        bank_name,
+        deck
+    );
 
     // Step 1: Load the raw problem data from the JSON file.
-    let problems_from_json = load_problems(bank_name)
+    let problems_from_json = load_problems(&options.static_dir, bank_name)
         .with_context(|| format!("Could not load data for bank '{}'", bank_name))?;
 
-    println!(
-        "Successfully loaded {} problems from JSON. Syncing with database...",
+    populate_problems(pool, bank_name, &deck, &problems_from_json, options).await
+}
+
+/// Builds the bundled `preset` into the database like `populate_problem_bank`,
+/// using its ids as they are instead of looking them up.
+pub async fn populate_preset(
+    pool: &SqlitePool,
+    preset: &Preset,
+    options: &PopulateOptions,
+) -> anyhow::Result<()> {
+    let deck = options
+        .deck
+        .clone()
+        .unwrap_or_else(|| preset.deck.to_string());
+    say!(
+        options,
+        "Loading the bundled '{}' preset into deck '{}'...",
+        preset.name,
+        deck
+    );
+    let problems = parse_problems(preset.contents)
+        .with_context(|| format!("The bundled '{}' preset is invalid", preset.name))?;
+    let options = PopulateOptions {
+        trust_ids: true,
+        ..options.clone()
+    };
+    populate_problems(pool, preset.name, &deck, &problems, &options).await
+}
+
+/// Builds `problems`, read from somewhere other than a bank file, into the
+/// database like `populate_problem_bank`. `bank_name` names them in messages
+/// and, unless `options.deck` is set, is where the deck's name comes from.
+pub async fn populate_imported_bank(
+    pool: &SqlitePool,
+    bank_name: &str,
+    problems: &[ProblemBankProblem],
+    options: &PopulateOptions,
+) -> anyhow::Result<()> {
+    let deck = options
+        .deck
+        .clone()
+        .unwrap_or_else(|| default_deck_name(bank_name));
+    anyhow::ensure!(
+        !problems.is_empty(),
+        "No LeetCode problems were found in '{}'.",
+        bank_name
+    );
+    populate_problems(pool, bank_name, &deck, problems, options).await
+}
+
+/// Resolves the ids of `problems_from_json` and syncs them into `deck`.
+async fn populate_problems(
+    pool: &SqlitePool,
+    bank_name: &str,
+    deck: &str,
+    problems_from_json: &[ProblemBankProblem],
+    options: &PopulateOptions,
+) -> anyhow::Result<()> {
+    say!(
+        options,
+        "Successfully loaded {} problems from JSON. Resolving ids...",
         problems_from_json.len()
     );
 
-    // Step 2: Iterate through the loaded problems and insert them.
-    for pbp in &problems_from_json {
-        let problem_to_insert = Problem {
-            id: pbp.id,
-            order: pbp.order,
-            name: pbp.name.clone(),
-            difficulty: pbp.difficulty,
-            week: pbp.week,
-        };
+    // Step 2: Resolve every problem's id concurrently, skipping the lookup for
+    // slugs resolved by an earlier build. The results come back in completion
+    // order, so each one carries its index in the bank.
+    let cached_ids = fetch_resolved_ids(pool).await?;
+    let cache_hits = problems_from_json
+        .iter()
+        .filter(|pbp| cached_ids.contains_key(pbp.slug()))
+        .count();
+    if cache_hits > 0 && !options.trust_ids {
+        say!(
+            options,
+            "{} id(s) found in the cache from earlier builds.",
+            cache_hits
+        );
+    }
+    let bar = progress_bar(options, problems_from_json.len(), "Resolving");
+    let bar_ref = &bar;
+    let cached_ids = &cached_ids;
+    let mut resolved: Vec<(usize, anyhow::Result<Problem>, bool)> =
+        stream::iter(problems_from_json.iter().enumerate())
+            .map(|(index, pbp)| async move {
+                let known_id = if options.trust_ids {
+                    Some(pbp.id)
+                } else {
+                    cached_ids.get(pbp.slug()).copied()
+                };
+                let (problem, checked) = match known_id {
+                    Some(id) => (Ok(pbp.to_problem_with_id(deck, id)), true),
+                    None => match pbp.to_problem(deck, options.resolve_attempts).await {
+                        // Without curl nothing can be looked up, so an entry
+                        // that writes its id is stored under it until a build
+                        // with curl checks it.
+                        Err(e) if is_curl_missing(&e) && pbp.id != 0 => {
+                            (Ok(pbp.to_problem_with_id(deck, pbp.id)), false)
+                        }
+                        result => (
+                            result.with_context(|| {
+                                format!("Failed to resolve id for problem '{}'", pbp.name)
+                            }),
+                            true,
+                        ),
+                    },
+                };
+                bar_ref.inc(1);
+                (index, problem, checked)
+            })
+            .buffer_unordered(options.concurrency.max(1))
+            .collect()
+            .await;
+    bar.finish_and_clear();
+
+    // Step 3: Restore bank order so the first error reported is the first
+    // failing problem in the file, and inserts happen in a stable order.
+    resolved.sort_by_key(|(index, _, _)| *index);
+    let unchecked: HashSet<usize> = resolved
+        .iter()
+        .filter(|(_, _, checked)| !checked)
+        .map(|(index, _, _)| *index)
+        .collect();
+    let resolved: Vec<(usize, anyhow::Result<Problem>)> = resolved
+        .into_iter()
+        .map(|(index, problem, _)| (index, problem))
+        .collect();
+
+    let resolved_ids: Vec<i64> = resolved
+        .iter()
+        .filter_map(|(_, problem)| problem.as_ref().ok().map(|problem| problem.id))
+        .collect();
+    let stale = fetch_stale_problems(pool, deck, problems_from_json, &resolved_ids).await?;
+
+    if options.dry_run {
+        return report_dry_run(pool, bank_name, resolved, &stale).await;
+    }
+
+    let mut problems_to_insert = resolved
+        .into_iter()
+        .map(|(_, problem)| problem)
+        .collect::<anyhow::Result<Vec<Problem>>>()?;
+
+    say!(options, "Resolved all ids. Syncing with database...");
 
-        // Step 3: Call the insert method on the newly created `Problem` instance.
-        problem_to_insert.insert(pool).await?;
+    // Step 4: Upsert everything inside one transaction, so a failure part way
+    // through leaves the database as it was.
+    let bar = progress_bar(options, problems_to_insert.len(), "Syncing");
+    let mut counts = SyncCounts::default();
+    let mut kept_ids = Vec::new();
+    let mut rekeyed = Vec::new();
+    let mut tx = pool.begin().await.context("Failed to start transaction.")?;
+    for (index, (problem, pbp)) in problems_to_insert
+        .iter_mut()
+        .zip(problems_from_json)
+        .enumerate()
+    {
+        let resolved_id = problem.id;
+        let checked = !unchecked.contains(&index);
+        // A problem stored under an older id, such as LeetCode's internal
+        // one, moves to the id it resolves to now, unless that's taken.
+        if checked
+            && let Some(stored_id) = fetch_problem_id_by_name(&mut *tx, &problem.name).await?
+            && stored_id != resolved_id
+            && fetch_problem(&mut *tx, resolved_id).await?.is_none()
+        {
+            rekey_problem(&mut tx, stored_id, resolved_id).await?;
+            rekeyed.push((problem.name.clone(), stored_id, resolved_id));
+        }
+        match problem.upsert(&mut *tx).await? {
+            UpsertOutcome::Inserted => counts.inserted += 1,
+            UpsertOutcome::Updated => counts.updated += 1,
+            UpsertOutcome::Unchanged => counts.unchanged += 1,
+        }
+        if problem.id != resolved_id {
+            kept_ids.push((problem.name.clone(), resolved_id, problem.id));
+        }
+        if checked {
+            cache_resolved_id(&mut *tx, pbp.slug(), problem.id).await?;
+        }
+        // Banks without tags leave the ones another bank gave the problem.
+        if !pbp.tags.is_empty() {
+            set_problem_tags(&mut tx, problem.id, &pbp.tags).await?;
+        }
+        bar.inc(1);
     }
+    tx.commit()
+        .await
+        .context("Failed to commit problem bank.")?;
+    bar.finish_and_clear();
 
-    println!("Database sync complete for bank '{}'.", bank_name);
+    say!(options, "Database sync complete for bank '{}'.", bank_name);
+    say!(
+        options,
+        "Inserted: {}  Updated: {}  Unchanged: {}",
+        counts.inserted,
+        counts.updated,
+        counts.unchanged
+    );
+    if !unchecked.is_empty() {
+        println!(
+            "curl isn't installed, so {} id(s) couldn't be looked up and were taken from the bank as written. A build with curl checks them.",
+            unchecked.len()
+        );
+    }
+    if !rekeyed.is_empty() {
+        println!(
+            "{} problem(s) were stored under another id and moved to this bank's, with their progress and history:",
+            rekeyed.len()
+        );
+        for (name, stored_id, resolved_id) in &rekeyed {
+            println!("  - {}: #{} -> #{}", name, stored_id, resolved_id);
+        }
+    }
+    if !kept_ids.is_empty() {
+        println!(
+            "{} problem(s) were already stored under another id and kept it:",
+            kept_ids.len()
+        );
+        for (name, resolved_id, stored_id) in &kept_ids {
+            println!(
+                "  - {}: #{} in this bank, stored as #{}",
+                name, resolved_id, stored_id
+            );
+        }
+    }
+
+    if options.fetch_meta {
+        say!(options, "Fetching metadata from LeetCode...");
+        let refresh = refresh_meta(
+            pool,
+            &problems_to_insert,
+            options.concurrency,
+            Local::now().date_naive(),
+        )
+        .await?;
+        if !options.quiet || !refresh.failed.is_empty() {
+            refresh.print_report();
+        }
+    }
+
+    if !stale.is_empty() {
+        println!(
+            "{} problem(s) in deck '{}' are no longer in the bank:",
+            stale.len(),
+            deck
+        );
+        for problem in &stale {
+            println!("  - #{:<5} {}", problem.id, problem.name);
+        }
+        if options.prune {
+            let ids: Vec<i64> = stale.iter().map(|p| p.id).collect();
+            let archived = remove_from_deck(pool, deck, &ids, Local::now().date_naive()).await?;
+            say!(
+                options,
+                "Pruned {} problem(s) from the deck; {} in no other deck were archived.",
+                ids.len(),
+                archived
+            );
+        } else {
+            println!("Re-run with --prune to remove them (their progress and history are kept).");
+        }
+    }
     Ok(())
 }
+
+/// Problems stored in `deck` that aren't in `bank` any more.
+///
+/// Ids alone can't tell: an entry's written id may be 0 (imported banks) or
+/// differ from the one it was stored under. So a stored problem counts as
+/// still in the bank if its id is one the bank writes, one its entries
+/// resolved to (`resolved_ids`, or the resolved id cache from earlier
+/// builds), or if its URL's slug or its name matches an entry.
+pub async fn fetch_stale_problems(
+    pool: &SqlitePool,
+    deck: &str,
+    bank: &[ProblemBankProblem],
+    resolved_ids: &[i64],
+) -> anyhow::Result<Vec<Problem>> {
+    let cached_ids = fetch_resolved_ids(pool).await?;
+    let bank_ids: HashSet<i64> = bank
+        .iter()
+        .map(|pbp| pbp.id)
+        .filter(|&id| id != 0)
+        .chain(resolved_ids.iter().copied())
+        .chain(
+            bank.iter()
+                .filter_map(|pbp| cached_ids.get(pbp.slug()).copied()),
+        )
+        .collect();
+    let slugs: HashSet<&str> = bank.iter().map(ProblemBankProblem::slug).collect();
+    let names: HashSet<&str> = bank.iter().map(|pbp| pbp.name.as_str()).collect();
+    let stale = fetch_all_problems(pool, Some(deck))
+        .await?
+        .into_iter()
+        .filter(|problem| {
+            let link = problem.link();
+            !bank_ids.contains(&problem.id)
+                && !url_slug(&link).is_some_and(|slug| slugs.contains(slug))
+                && !names.contains(problem.name.as_str())
+        })
+        .collect();
+
+    Ok(stale)
+}
+
+/// How many bank entries a build inserted, updated or left alone.
+#[derive(Debug, Default)]
+struct SyncCounts {
+    inserted: usize,
+    updated: usize,
+    unchanged: usize,
+}
+
+/// A bar counting `len` steps, or a hidden one unless `show_progress` is set.
+fn progress_bar(options: &PopulateOptions, len: usize, label: &'static str) -> ProgressBar {
+    if !options.show_progress {
+        return ProgressBar::hidden();
+    }
+
+    let bar = ProgressBar::with_draw_target(Some(len as u64), ProgressDrawTarget::stdout());
+    bar.set_style(
+        ProgressStyle::with_template("{msg:>9} [{bar:40}] {pos}/{len} (ETA {eta})")
+            .expect("progress template is valid")
+            .progress_chars("#>-"),
+    );
+    bar.set_message(label);
+    bar
+}
+
+/// Prints what a build would do without touching the database.
+///
+/// Every resolution failure is listed, not just the first, so a bank file can
+/// be fixed in one pass. Returns an error if any problem failed.
+async fn report_dry_run(
+    pool: &SqlitePool,
+    bank_name: &str,
+    resolved: Vec<(usize, anyhow::Result<Problem>)>,
+    stale: &[Problem],
+) -> anyhow::Result<()> {
+    let existing: HashMap<i64, Problem> = fetch_all_problems(pool, None)
+        .await?
+        .into_iter()
+        .map(|problem| (problem.id, problem))
+        .collect();
+    // A build files a name that's stored under another id under that row.
+    let ids_by_name: HashMap<&str, i64> = existing
+        .values()
+        .map(|problem| (problem.name.as_str(), problem.id))
+        .collect();
+
+    let mut counts = SyncCounts::default();
+    let mut failures = Vec::new();
+    for (index, result) in resolved {
+        match result {
+            Ok(problem) => match ids_by_name
+                .get(problem.name.as_str())
+                .or(Some(&problem.id))
+                .and_then(|id| existing.get(id))
+            {
+                None => counts.inserted += 1,
+                Some(stored) if problem.bank_fields_differ(stored) => counts.updated += 1,
+                Some(_) => counts.unchanged += 1,
+            },
+            Err(e) => failures.push((index, e)),
+        }
+    }
+
+    println!("\n--- Dry Run for bank '{}' ---", bank_name);
+    println!("New problems:     {}", counts.inserted);
+    println!("Would update:     {}", counts.updated);
+    println!("Unchanged:        {}", counts.unchanged);
+    println!("Not in the bank:  {}", stale.len());
+    println!("Failed:           {}", failures.len());
+
+    if failures.is_empty() {
+        println!("No changes were written.");
+        return Ok(());
+    }
+
+    eprintln!("\nThe following problems failed validation:");
+    for (index, e) in &failures {
+        eprintln!("  - entry #{}: {:#}", index + 1, e);
+    }
+    anyhow::bail!(
+        "{} problem(s) in bank '{}' failed validation",
+        failures.len(),
+        bank_name
+    )
+}
+
+/// Derives a deck name from a bank file name, e.g. `grind-75.json` -> `grind-75`.
+pub fn default_deck_name(bank_name: &str) -> String {
+    std::path::Path::new(bank_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| bank_name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AttemptRating;
+    use crate::db::init_pool;
+
+    fn entry(id: i64, name: &str, slug: &str) -> ProblemBankProblem {
+        ProblemBankProblem {
+            id,
+            order: 1,
+            name: name.to_string(),
+            difficulty: None,
+            week: Some(1),
+            url: format!("https://leetcode.com/problems/{}/", slug),
+            tags: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn builds_move_problems_stored_under_an_old_id_to_the_resolved_one() {
+        let pool = init_pool("sqlite::memory:").await.unwrap();
+        // 907 is Koko Eating Bananas' internal id; LeetCode shows 875.
+        let old = entry(907, "Koko Eating Bananas", "koko-eating-bananas");
+        old.to_problem_with_id("grind-75", 907)
+            .insert(&pool)
+            .await
+            .unwrap();
+        crate::db::log_attempt(
+            &pool,
+            907,
+            AttemptRating::Messy,
+            None,
+            crate::db::AttemptDetails::default(),
+        )
+        .await
+        .unwrap();
+        let today = Local::now().date_naive();
+        crate::db::set_problem_note(&pool, 907, Some("binary search on the speed"), today)
+            .await
+            .unwrap();
+
+        let options = PopulateOptions {
+            trust_ids: true,
+            quiet: true,
+            ..PopulateOptions::default()
+        };
+        let bank = [entry(875, "Koko Eating Bananas", "koko-eating-bananas")];
+        populate_imported_bank(&pool, "grind-75", &bank, &options)
+            .await
+            .unwrap();
+
+        assert!(fetch_problem(&pool, 907).await.unwrap().is_none());
+        assert!(fetch_problem(&pool, 875).await.unwrap().is_some());
+        let history = crate::db::fetch_attempt_history(&pool, 875).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(
+            crate::db::fetch_progress(&pool, 875)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            crate::db::fetch_problem_note(&pool, 875)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("binary search on the speed")
+        );
+        assert_eq!(
+            fetch_all_problems(&pool, Some("grind-75")).await.unwrap()[0].id,
+            875
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_problems_match_entries_by_slug_and_name_not_just_id() {
+        let pool = init_pool("sqlite::memory:").await.unwrap();
+        for bank_entry in [
+            entry(
+                1023,
+                "Time Based Key-Value Store",
+                "time-based-key-value-store",
+            ),
+            entry(1, "Two Sum", "two-sum"),
+            entry(146, "LRU Cache", "lru-cache"),
+        ] {
+            bank_entry
+                .to_problem_with_id("grind-75", bank_entry.id)
+                .insert(&pool)
+                .await
+                .unwrap();
+        }
+
+        // The bank writes 981 for the problem stored as 1023, and an
+        // imported bank writes 0 for every id.
+        let bank = [
+            entry(
+                981,
+                "Time Based Key-Value Store",
+                "time-based-key-value-store",
+            ),
+            entry(0, "Two Sum", "two-sum"),
+        ];
+        let stale = fetch_stale_problems(&pool, "grind-75", &bank, &[])
+            .await
+            .unwrap();
+        let stale_ids: Vec<i64> = stale.iter().map(|problem| problem.id).collect();
+        assert_eq!(stale_ids, [146]);
+
+        assert!(
+            fetch_stale_problems(&pool, "other-deck", &bank, &[])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
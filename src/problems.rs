@@ -1,41 +1,270 @@
-#[derive(Debug, FromRow)]
+/// A problem from a problem bank.
+#[derive(Debug, FromRow, serde::Serialize, serde::Deserialize)]
 pub struct Problem {
-    pub id: i64, // LeetCode ID
+    /// The LeetCode ID.
+    pub id: i64,
+    /// Where the problem sits in its bank.
     pub order: i64,
+    /// The problem's name.
     pub name: String,
+    /// The problem's difficulty, if the bank gave one.
     pub difficulty: Option<LeetCodeDifficulty>,
+    /// The study-plan week the problem belongs to.
     pub week: Option<i64>,
+    /// Pinned problems (`track pin`) are offered before the rest, and
+    /// skipped ones (`track skip`) after.
+    pub priority: i64,
+    /// The deck (study plan) this problem belongs to.
+    pub deck: String,
+    /// The problem's page, from the bank file. `None` for problems built
+    /// before URLs were stored.
+    pub url: Option<String>,
 }
 
 impl Problem {
-    pub async fn insert(&self, pool: &SqlitePool) -> anyhow::Result<()> {
+    /// The priority `track pin` assigns. Unpinned problems have priority 0.
+    pub const PINNED_PRIORITY: i64 = 1;
+
+    /// The priority `track skip` assigns, which puts the problem behind
+    /// every other one.
+    pub const SKIPPED_PRIORITY: i64 = -1;
+
+    /// Whether the problem was pinned with `track pin`.
+    pub fn is_pinned(&self) -> bool {
+        self.priority >= Self::PINNED_PRIORITY
+    }
+
+    /// Whether the problem was skipped with `track skip`.
+    pub fn is_skipped(&self) -> bool {
+        self.priority <= Self::SKIPPED_PRIORITY
+    }
+
+    /// The stored URL, or one guessed from the name if none was stored.
+    pub fn link(&self) -> String {
+        self.url.clone().unwrap_or_else(|| leetcode_url(&self.name))
+    }
+
+    /// Whether `other` (the stored row for the same id) holds different
+    /// bank-file data. Priority and deck are managed by track, so they're
+    /// not compared, and neither is a difficulty the bank leaves out.
+    pub fn bank_fields_differ(&self, other: &Problem) -> bool {
+        self.order != other.order
+            || self.name != other.name
+            || (self.difficulty.is_some() && self.difficulty != other.difficulty)
+            || self.week != other.week
+            || self.url != other.url
+    }
+
+    /// Inserts the problem, or updates its bank-file fields (order, name,
+    /// difficulty, week and URL) if it's already stored. Either way the
+    /// problem joins `self.deck`; priority and the deck it was first filed
+    /// under are left as they are, and so is a stored difficulty (say, one
+    /// fetched from LeetCode) when the bank doesn't give one.
+    ///
+    /// Names are unique, so if the name is already stored under another id
+    /// the problem is filed under that row instead, and `self.id` changes to
+    /// match. That happens when two sources resolved the same problem to
+    /// different ids, and it keeps the stored row's history attached.
+    pub async fn upsert<'a, A>(&mut self, db: A) -> anyhow::Result<UpsertOutcome>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        let mut conn = db
+            .acquire()
+            .await
+            .context("Failed to acquire a connection.")?;
+
+        if let Some(stored_id) = fetch_problem_id_by_name(&mut *conn, &self.name).await? {
+            self.id = stored_id;
+        }
+        let outcome = match fetch_problem(&mut *conn, self.id).await? {
+            None => UpsertOutcome::Inserted,
+            Some(stored) if self.bank_fields_differ(&stored) => UpsertOutcome::Updated,
+            Some(_) => {
+                self.join_deck(&mut conn).await?;
+                return Ok(UpsertOutcome::Unchanged);
+            }
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO problems (id, "order", name, difficulty, week, deck, url)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(id) DO UPDATE SET
+                "order" = excluded."order",
+                name = excluded.name,
+                difficulty = COALESCE(excluded.difficulty, problems.difficulty),
+                week = excluded.week,
+                url = excluded.url
+            "#,
+        )
+        .bind(self.id)
+        .bind(self.order)
+        .bind(&self.name)
+        .bind(self.difficulty)
+        .bind(self.week)
+        .bind(&self.deck)
+        .bind(&self.url)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to save problem: {}", self.name))?;
+        self.join_deck(&mut conn).await?;
+
+        Ok(outcome)
+    }
+
+    /// Inserts the problem and files it under `self.deck`. An existing problem is
+    /// left alone, except to fill in a URL it was built without.
+    pub async fn insert<'a, A>(&self, db: A) -> anyhow::Result<()>
+    where
+        A: Acquire<'a, Database = Sqlite>,
+    {
+        let mut conn = db
+            .acquire()
+            .await
+            .context("Failed to acquire a connection.")?;
+
+        // Rebuilding a bank leaves existing problems alone, except to fill in
+        // a URL they were built without.
         sqlx::query!(
             r#"
-            INSERT OR IGNORE INTO problems (id, "order", name, difficulty, week)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT OR IGNORE INTO problems (id, "order", name, difficulty, week, deck, url)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET url = COALESCE(problems.url, excluded.url)
             "#,
             self.id,
             self.order,
             self.name,
             self.difficulty,
-            self.week
+            self.week,
+            self.deck,
+            self.url
         )
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .with_context(|| format!("Failed to insert problem: {}", self.name))?;
+        self.join_deck(&mut conn).await?;
+
+        Ok(())
+    }
+
+    /// Files the problem under `self.deck`, alongside any decks it's already in,
+    /// restoring it if it was archived.
+    async fn join_deck(&self, conn: &mut SqliteConnection) -> anyhow::Result<()> {
+        sqlx::query("INSERT OR IGNORE INTO deck_problems (deck, problem_id) VALUES (?1, ?2)")
+            .bind(&self.deck)
+            .bind(self.id)
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("Failed to add {} to deck '{}'", self.name, self.deck))?;
+        sqlx::query("UPDATE problems SET archived_on = NULL WHERE id = ?")
+            .bind(self.id)
+            .execute(conn)
+            .await
+            .with_context(|| format!("Failed to restore {}", self.name))?;
 
         Ok(())
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Deserialize)]
+/// Builds a LeetCode URL from a problem name, following LeetCode's slug rules
+/// (lowercase, punctuation dropped, words joined by hyphens).
+pub fn leetcode_url(name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("https://leetcode.com/problems/{}/", slug)
+}
+
+/// What `Problem::upsert` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    /// The problem wasn't stored yet.
+    Inserted,
+    /// One of its bank-file fields changed.
+    Updated,
+    /// It was already stored as it is.
+    Unchanged,
+}
+
+/// How much a problem matters to you, set with `track set-priority`. Within
+/// a week, `next` and `today` suggest high before normal before low.
+///
+/// This is separate from pinning: a pinned problem jumps every week, while a
+/// high-priority one only moves up its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriorityLevel {
+    /// Interview-critical: suggested first in its week.
+    High,
+    /// The default.
+    #[default]
+    Normal,
+    /// Suggested last in its week.
+    Low,
+}
+
+impl PriorityLevel {
+    /// The level as stored in `problem_priorities`: 1, 0 or -1.
+    pub fn value(self) -> i64 {
+        match self {
+            PriorityLevel::High => 1,
+            PriorityLevel::Normal => 0,
+            PriorityLevel::Low => -1,
+        }
+    }
+
+    /// The level stored as `value`. Anything positive is high and anything
+    /// negative low.
+    pub fn from_value(value: i64) -> PriorityLevel {
+        match value.signum() {
+            1 => PriorityLevel::High,
+            -1 => PriorityLevel::Low,
+            _ => PriorityLevel::Normal,
+        }
+    }
+}
+
+impl fmt::Display for PriorityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            PriorityLevel::High => "high",
+            PriorityLevel::Normal => "normal",
+            PriorityLevel::Low => "low",
+        })
+    }
+}
+
+/// A problem's difficulty on LeetCode.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    sqlx::Type,
+    serde::Deserialize,
+    serde::Serialize,
+    clap::ValueEnum,
+)]
 #[sqlx(type_name = "TEXT")]
 pub enum LeetCodeDifficulty {
+    /// Easy.
     Easy,
+    /// Medium.
     Medium,
+    /// Hard.
     Hard,
 }
 
+use crate::db::{fetch_problem, fetch_problem_id_by_name};
 use anyhow::Context;
+use sqlx::Acquire;
 use sqlx::FromRow;
+use sqlx::Sqlite;
+use sqlx::SqliteConnection;
 use sqlx::SqlitePool;
+use std::fmt;
@@ -0,0 +1,343 @@
+// src/schedule.rs
+
+/// The longest gap the default schedule ever leaves between reviews.
+pub const MAX_INTERVAL_DAYS: i64 = 365;
+
+/// The ease factor a problem starts with, as in SM-2.
+pub const DEFAULT_EASE: f64 = 2.5;
+
+/// The lowest a problem's ease factor goes, as in SM-2.
+pub const MIN_EASE: f64 = 1.3;
+
+/// Where a problem stands in the schedule, as of its latest attempt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewState {
+    /// Successful attempts in a row, counting back from the latest. A failed
+    /// attempt resets it to 0.
+    pub repetitions: i64,
+    /// What the gap is multiplied by for each success past the second.
+    /// Successes move it by their rating's adjustment; failures leave it.
+    pub ease: f64,
+}
+
+impl ReviewState {
+    /// A problem that's never been attempted.
+    pub const NEW: ReviewState = ReviewState {
+        repetitions: 0,
+        ease: DEFAULT_EASE,
+    };
+}
+
+/// How the gap before a rating's next review grows. Set per rating under
+/// `[schedule.<rating>]` in track.toml; a table given there must set all
+/// three fields.
+///
+/// A failure (LongFail or ShortFail) restarts the run of successes, so only
+/// its `first` is used, and its `growth` is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RatingInterval {
+    /// Days until the next review after the first success in a row.
+    pub first: i64,
+    /// Days until the next review after the second success in a row.
+    pub second: i64,
+    /// The ease this rating gives a problem that's at `DEFAULT_EASE`. Each
+    /// success moves the problem's ease by `growth - DEFAULT_EASE`, so Easy
+    /// (2.6) raises it by 0.1 and Messy (2.36) lowers it by 0.14.
+    pub growth: f64,
+}
+
+impl RatingInterval {
+    /// A rating that always comes back after `days`, however often it's been tried.
+    const fn fixed(days: i64) -> RatingInterval {
+        RatingInterval {
+            first: days,
+            second: days,
+            growth: 1.0,
+        }
+    }
+}
+
+/// The review schedule, loaded from `[schedule]` in track.toml.
+///
+/// The defaults follow SM-2. Each problem has an ease factor, starting at
+/// 2.5 and never below 1.3, that successes adjust: Easy raises it by 0.1,
+/// Hard leaves it and Messy lowers it by 0.14. A run of successes comes back
+/// after 1 day, then 6, then 6 times the ease for each further success. A
+/// failure ends the run: the problem comes back the next day and climbs from
+/// 1 day again.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schedule {
+    /// No gap is ever longer than this, so long-running problems still come back.
+    pub max_interval_days: i64,
+    /// The gaps after an Easy attempt.
+    pub easy: RatingInterval,
+    /// The gaps after a Hard attempt.
+    pub hard: RatingInterval,
+    /// The gaps after a Messy attempt.
+    pub messy: RatingInterval,
+    /// The gaps after a LongFail attempt.
+    pub long_fail: RatingInterval,
+    /// The gaps after a ShortFail attempt.
+    pub short_fail: RatingInterval,
+    /// The most reviews any one day should get. When a review would land on
+    /// a full day, it's moved to the nearest day with room, by up to a
+    /// quarter of its interval either way. Unset, days are never capped.
+    pub daily_review_cap: Option<u32>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        let sm2 = |growth| RatingInterval {
+            first: 1,
+            second: 6,
+            growth,
+        };
+        Schedule {
+            max_interval_days: MAX_INTERVAL_DAYS,
+            easy: sm2(2.6),
+            hard: sm2(2.5),
+            messy: sm2(2.36),
+            long_fail: RatingInterval::fixed(1),
+            short_fail: RatingInterval::fixed(1),
+            daily_review_cap: None,
+        }
+    }
+}
+
+impl Schedule {
+    /// The settings for one rating.
+    pub fn for_rating(&self, rating: AttemptRating) -> &RatingInterval {
+        match rating {
+            AttemptRating::Easy => &self.easy,
+            AttemptRating::Hard => &self.hard,
+            AttemptRating::Messy => &self.messy,
+            AttemptRating::LongFail => &self.long_fail,
+            AttemptRating::ShortFail => &self.short_fail,
+        }
+    }
+
+    /// The state after an attempt rated `rating` on a problem in `state`.
+    /// Failures restart the run and leave the ease alone; successes extend
+    /// the run and move the ease by the rating's adjustment.
+    pub fn review(&self, state: ReviewState, rating: AttemptRating) -> ReviewState {
+        if rating.is_fail() {
+            return ReviewState {
+                repetitions: 0,
+                ..state
+            };
+        }
+        let adjustment = self.for_rating(rating).growth - DEFAULT_EASE;
+        ReviewState {
+            repetitions: state.repetitions + 1,
+            ease: (state.ease + adjustment).max(MIN_EASE),
+        }
+    }
+
+    /// The state of a problem attempted with `ratings`, oldest first.
+    pub fn replay(&self, ratings: impl IntoIterator<Item = AttemptRating>) -> ReviewState {
+        ratings
+            .into_iter()
+            .fold(ReviewState::NEW, |state, rating| self.review(state, rating))
+    }
+
+    /// How long to wait after an attempt rated `rating` that left the
+    /// problem in `state`.
+    pub fn interval(&self, rating: AttemptRating, state: ReviewState) -> Duration {
+        let settings = self.for_rating(rating);
+        let days = match state.repetitions {
+            ..=1 => settings.first,
+            2 => settings.second,
+            n => {
+                // Capping the exponent keeps the float finite for long runs.
+                let exponent = (n - 2).min(64) as i32;
+                (settings.second as f64 * state.ease.powi(exponent)).round() as i64
+            }
+        };
+        Duration::days(days.min(self.max_interval_days))
+    }
+
+    /// The days a review due on `due`, after an attempt on `attempted`, may
+    /// be moved between to respect `daily_review_cap`: a quarter of the
+    /// interval either side, so one-day intervals always stay put.
+    pub fn smoothing_window(&self, attempted: NaiveDate, due: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let slack = Duration::days((due - attempted).num_days().max(0) / 4);
+        (due - slack, due + slack)
+    }
+
+    /// Where to put a review due on `due` so no day goes over
+    /// `daily_review_cap`, given how many reviews `load` already has on each
+    /// day. Days closest to `due` are tried first, later before earlier. If
+    /// every day in the window is full, or there's no cap, it stays on `due`.
+    pub fn smooth(
+        &self,
+        attempted: NaiveDate,
+        due: NaiveDate,
+        load: &HashMap<NaiveDate, u32>,
+    ) -> NaiveDate {
+        let Some(cap) = self.daily_review_cap else {
+            return due;
+        };
+        let (earliest, latest) = self.smoothing_window(attempted, due);
+        let has_room = |day: &NaiveDate| load.get(day).copied().unwrap_or(0) < cap;
+        (0..=(latest - due).num_days())
+            .flat_map(|offset| [due + Duration::days(offset), due - Duration::days(offset)])
+            .filter(|day| (earliest..=latest).contains(day))
+            .find(has_room)
+            .unwrap_or(due)
+    }
+
+    /// Checks that every gap is at least a day and never shrinks.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.max_interval_days < 1 {
+            anyhow::bail!("schedule.max_interval_days must be at least 1.");
+        }
+        if self.daily_review_cap == Some(0) {
+            anyhow::bail!("schedule.daily_review_cap must be at least 1.");
+        }
+        for rating in AttemptRating::ALL {
+            let settings = self.for_rating(rating);
+            if settings.first < 1 || settings.second < 1 {
+                anyhow::bail!("schedule for {}: intervals must be at least 1 day.", rating);
+            }
+            if settings.growth.is_nan() || settings.growth < 1.0 {
+                anyhow::bail!("schedule for {}: growth must be at least 1.0.", rating);
+            }
+        }
+        Ok(())
+    }
+}
+
+static SCHEDULE: OnceLock<Schedule> = OnceLock::new();
+
+/// Makes `schedule` the one used by `next_interval` for the rest of the
+/// process. Only the first call has any effect.
+pub fn install(schedule: Schedule) {
+    let _ = SCHEDULE.set(schedule);
+}
+
+/// The installed schedule, or the default if none was installed.
+pub fn current() -> &'static Schedule {
+    SCHEDULE.get_or_init(Schedule::default)
+}
+
+use crate::problem_attempts::AttemptRating;
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn reads_partial_overrides_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [schedule]
+            max_interval_days = 30
+
+            [schedule.messy]
+            first = 2
+            second = 3
+            growth = 1.5
+            "#,
+        )
+        .unwrap();
+        let schedule = config.schedule;
+        schedule.validate().unwrap();
+
+        assert_eq!(schedule.easy, Schedule::default().easy);
+        let run = |repetitions| ReviewState {
+            repetitions,
+            ease: 1.5,
+        };
+        assert_eq!(
+            schedule.interval(AttemptRating::Messy, run(1)).num_days(),
+            2
+        );
+        assert_eq!(
+            schedule.interval(AttemptRating::Messy, run(2)).num_days(),
+            3
+        );
+        assert_eq!(
+            schedule.interval(AttemptRating::Messy, run(3)).num_days(),
+            5
+        );
+        assert_eq!(
+            schedule.interval(AttemptRating::Easy, run(10)).num_days(),
+            30
+        );
+        // Messy's growth of 1.5 lowers the ease by 1.0, down to the floor.
+        let messy = schedule.replay([AttemptRating::Messy; 2]);
+        assert_eq!(messy.repetitions, 2);
+        assert_eq!(messy.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn successes_adjust_the_ease_and_failures_reset_the_run() {
+        let schedule = Schedule::default();
+        let easy = schedule.replay([AttemptRating::Easy; 3]);
+        assert_eq!(easy.repetitions, 3);
+        assert!((easy.ease - 2.8).abs() < 1e-9);
+        let hard = schedule.replay([AttemptRating::Hard; 3]);
+        assert_eq!(hard.ease, DEFAULT_EASE);
+
+        let failed = schedule.review(easy, AttemptRating::LongFail);
+        assert_eq!(failed.repetitions, 0);
+        assert_eq!(failed.ease, easy.ease);
+        let recovered = schedule.review(failed, AttemptRating::Easy);
+        assert_eq!(recovered.repetitions, 1);
+        assert_eq!(
+            schedule.interval(AttemptRating::Easy, recovered).num_days(),
+            1
+        );
+    }
+
+    #[test]
+    fn rejects_shrinking_or_empty_intervals() {
+        let mut schedule = Schedule::default();
+        schedule.hard.growth = 0.5;
+        assert!(schedule.validate().is_err());
+
+        let mut schedule = Schedule::default();
+        schedule.short_fail.first = 0;
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn moves_reviews_off_full_days() {
+        let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+        let schedule = Schedule {
+            daily_review_cap: Some(2),
+            ..Schedule::default()
+        };
+        let attempted = date("2024-03-01");
+        let due = date("2024-03-13");
+        let mut load = HashMap::from([(due, 2)]);
+
+        assert_eq!(schedule.smooth(attempted, due, &HashMap::new()), due);
+        assert_eq!(schedule.smooth(attempted, due, &load), date("2024-03-14"));
+        load.insert(date("2024-03-14"), 5);
+        assert_eq!(schedule.smooth(attempted, due, &load), date("2024-03-12"));
+
+        // A 12-day interval may move 3 days; past that it stays put.
+        for day in [
+            "2024-03-10",
+            "2024-03-11",
+            "2024-03-12",
+            "2024-03-15",
+            "2024-03-16",
+        ] {
+            load.insert(date(day), 2);
+        }
+        assert_eq!(schedule.smooth(attempted, due, &load), due);
+        // Failures come back the next day whatever the load.
+        let tomorrow = date("2024-03-02");
+        load.insert(tomorrow, 9);
+        assert_eq!(schedule.smooth(attempted, tomorrow, &load), tomorrow);
+        assert_eq!(Schedule::default().smooth(attempted, due, &load), due);
+    }
+}
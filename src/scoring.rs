@@ -0,0 +1,141 @@
+// src/scoring.rs
+
+/// Points awarded per difficulty. Set under `[scoring]` in track.toml.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+    /// Points for an Easy problem.
+    pub easy: u32,
+    /// Points for a Medium problem.
+    pub medium: u32,
+    /// Points for a Hard problem.
+    pub hard: u32,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            easy: 1,
+            medium: 2,
+            hard: 3,
+        }
+    }
+}
+
+impl ScoreWeights {
+    /// The points a problem of this difficulty is worth. Problems without a
+    /// difficulty are worth nothing, since there's no way to weigh them.
+    pub fn weight(&self, difficulty: Option<LeetCodeDifficulty>) -> u32 {
+        match difficulty {
+            Some(LeetCodeDifficulty::Easy) => self.easy,
+            Some(LeetCodeDifficulty::Medium) => self.medium,
+            Some(LeetCodeDifficulty::Hard) => self.hard,
+            None => 0,
+        }
+    }
+}
+
+/// Points earned out of the points available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score {
+    /// Points for the problems attempted.
+    pub earned: u32,
+    /// Points for every problem.
+    pub available: u32,
+}
+
+/// Scores progress against every problem in the bank.
+///
+/// A problem's points are earned once its latest rating is Easy; every
+/// problem in `problems` counts toward what's available.
+pub fn compute_score(
+    progress: &[ProgressView],
+    problems: &[Problem],
+    weights: &ScoreWeights,
+) -> Score {
+    let earned = progress
+        .iter()
+        .filter(|item| item.attempt_rating == AttemptRating::Easy)
+        .map(|item| weights.weight(item.difficulty))
+        .sum();
+    let available = problems
+        .iter()
+        .map(|problem| weights.weight(problem.difficulty))
+        .sum();
+
+    Score { earned, available }
+}
+
+use crate::db::ProgressView;
+use crate::problem_attempts::AttemptRating;
+use crate::problems::{LeetCodeDifficulty, Problem};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(id: i64, difficulty: Option<LeetCodeDifficulty>) -> Problem {
+        Problem {
+            id,
+            order: id,
+            name: format!("Problem {}", id),
+            difficulty,
+            week: None,
+            priority: 0,
+            deck: "default".to_string(),
+            url: None,
+        }
+    }
+
+    fn progress(problem: &Problem, rating: AttemptRating) -> ProgressView {
+        ProgressView {
+            problem_id: problem.id,
+            name: problem.name.clone(),
+            difficulty: problem.difficulty,
+            last_attempted: chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            attempt_rating: rating,
+            number_of_attempts: 1,
+            mastered: false,
+            next_attempt_date: None,
+            duration_minutes: None,
+            note: None,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn only_easy_ratings_earn_points() {
+        let problems = vec![
+            problem(1, Some(LeetCodeDifficulty::Easy)),
+            problem(2, Some(LeetCodeDifficulty::Medium)),
+            problem(3, Some(LeetCodeDifficulty::Hard)),
+            problem(4, None),
+        ];
+        let progress = vec![
+            progress(&problems[0], AttemptRating::Hard),
+            progress(&problems[2], AttemptRating::Easy),
+        ];
+
+        let score = compute_score(&progress, &problems, &ScoreWeights::default());
+
+        assert_eq!(
+            score,
+            Score {
+                earned: 3,
+                available: 6
+            }
+        );
+    }
+
+    #[test]
+    fn weights_can_be_overridden() {
+        let problems = vec![problem(1, Some(LeetCodeDifficulty::Medium))];
+        let progress = vec![progress(&problems[0], AttemptRating::Easy)];
+        let weights = ScoreWeights {
+            medium: 5,
+            ..ScoreWeights::default()
+        };
+
+        assert_eq!(compute_score(&progress, &problems, &weights).earned, 5);
+    }
+}
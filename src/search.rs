@@ -0,0 +1,110 @@
+// src/search.rs
+
+/// How a problem matched a search query, from strongest to weakest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    /// The name is the query, ignoring case, spaces and punctuation.
+    Exact,
+    /// The name contains the query, ignoring case, spaces and punctuation.
+    Substring,
+    /// Every word of the query starts a word of the name, in any order.
+    Words,
+    /// The query's letters appear in the name in order, with gaps.
+    Fuzzy,
+    /// Only one of the problem's notes contains the query.
+    Note,
+}
+
+/// Queries shorter than this (once normalized) don't get fuzzy matching,
+/// since a couple of letters turn up in order in almost every name.
+const MIN_FUZZY_LEN: usize = 3;
+
+/// How well `name`, or failing that one of `notes`, matches `query`. Returns
+/// `None` if nothing matches.
+pub fn match_problem(query: &str, name: &str, notes: &[&str]) -> Option<MatchKind> {
+    let wanted = normalize(query);
+    if wanted.is_empty() {
+        return None;
+    }
+    let normalized_name = normalize(name);
+
+    if normalized_name == wanted {
+        return Some(MatchKind::Exact);
+    }
+    if normalized_name.contains(&wanted) {
+        return Some(MatchKind::Substring);
+    }
+
+    let name_words = words(name);
+    if words(query)
+        .iter()
+        .all(|q| name_words.iter().any(|w| w.starts_with(q.as_str())))
+    {
+        return Some(MatchKind::Words);
+    }
+    if wanted.chars().count() >= MIN_FUZZY_LEN && is_subsequence(&wanted, &normalized_name) {
+        return Some(MatchKind::Fuzzy);
+    }
+
+    let query = query.trim().to_lowercase();
+    notes
+        .iter()
+        .any(|note| note.to_lowercase().contains(&query))
+        .then_some(MatchKind::Note)
+}
+
+/// Lowercased words of `text`, split on anything that isn't alphanumeric.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether every char of `needle` appears in `haystack` in the same order.
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle.chars().all(|c| rest.any(|h| h == c))
+}
+
+use crate::duplicates::normalize;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_closer_matches_first() {
+        let name = "Two Sum II - Input Array Is Sorted";
+        assert_eq!(
+            match_problem("two sum ii input array is sorted", name, &[]),
+            Some(MatchKind::Exact)
+        );
+        assert_eq!(
+            match_problem("two sum II", name, &[]),
+            Some(MatchKind::Substring)
+        );
+        assert_eq!(
+            match_problem("sorted two", name, &[]),
+            Some(MatchKind::Words)
+        );
+        assert_eq!(match_problem("tsmii", name, &[]), Some(MatchKind::Fuzzy));
+        assert!(MatchKind::Substring < MatchKind::Fuzzy);
+    }
+
+    #[test]
+    fn falls_back_to_notes() {
+        let notes = ["Use a monotonic stack"];
+        assert_eq!(
+            match_problem("monotonic", "Daily Temperatures", &notes),
+            Some(MatchKind::Note)
+        );
+        assert_eq!(match_problem("heap", "Daily Temperatures", &notes), None);
+    }
+
+    #[test]
+    fn short_queries_are_not_fuzzy() {
+        assert_eq!(match_problem("tm", "Two Sum", &[]), None);
+        assert_eq!(match_problem("  ", "Two Sum", &[]), None);
+    }
+}
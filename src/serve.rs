@@ -0,0 +1,1090 @@
+// src/serve.rs
+
+/// The port `track serve` listens on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// The deck a problem logged by URL is filed under if it wasn't stored yet
+/// and no deck is being served.
+pub const BY_URL_DECK: &str = "logged";
+
+/// How many weeks of activity the dashboard's heatmap covers.
+const HEATMAP_WEEKS: u32 = 26;
+
+/// The largest request body accepted, which is plenty for a logged attempt.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// The most header lines read before a request is rejected.
+const MAX_HEADERS: usize = 100;
+
+/// The longest request or header line accepted.
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// An HTTP request, as much of it as the dashboard needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// The method, e.g. `GET`.
+    pub method: String,
+    /// The path, without the query string.
+    pub path: String,
+    /// The query string's parameters.
+    pub query: HashMap<String, String>,
+    /// The headers, by lowercased name.
+    pub headers: HashMap<String, String>,
+    /// The body, empty unless a `Content-Length` was sent.
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// The body's `application/x-www-form-urlencoded` fields.
+    pub fn form(&self) -> HashMap<String, String> {
+        form_urlencoded::parse(&self.body).into_owned().collect()
+    }
+}
+
+/// An HTTP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The status code.
+    pub status: u16,
+    /// The body's media type.
+    pub content_type: &'static str,
+    /// Headers beyond the content type and length, such as `Location`.
+    pub headers: Vec<(String, String)>,
+    /// The body.
+    pub body: String,
+}
+
+impl Response {
+    /// A page of HTML.
+    pub fn html(body: String) -> Self {
+        Response {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// A plain-text message, usually explaining an error.
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Response {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// `value` as a JSON document.
+    pub fn json(status: u16, value: &impl serde::Serialize) -> Self {
+        let mut body = serde_json::to_string_pretty(value).expect("responses serialize to JSON");
+        body.push('\n');
+        Response {
+            status,
+            content_type: "application/json",
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// Sends the browser on to `location` after a form post.
+    pub fn redirect(location: &str) -> Self {
+        Response {
+            headers: vec![("Location".to_string(), location.to_string())],
+            ..Response::text(303, "")
+        }
+    }
+
+    /// The value of the header `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The response as sent over the wire. Every connection serves one
+    /// request, so it always asks the client to close it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            reason_phrase(self.status),
+            self.content_type,
+            self.body.len()
+        );
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(self.body.as_bytes());
+        bytes
+    }
+}
+
+/// The reason phrase sent with `status`.
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        303 => "See Other",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Reads one request: the request line, the headers and, if there's a
+/// `Content-Length`, the body.
+///
+/// This is a small HTTP/1.1 reader of our own rather than a framework such as
+/// axum, which isn't a dependency: the server answers one request per
+/// connection and needs nothing beyond this. Lines, headers and bodies are all
+/// capped, so a client can't make it buffer without limit.
+pub async fn read_request<R>(reader: &mut R) -> anyhow::Result<Request>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = String::new();
+    read_line(reader, &mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Malformed request line '{}'", line.trim());
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect(),
+        headers: HashMap::new(),
+        body: Vec::new(),
+    };
+
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        read_line(reader, &mut line).await?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    if let Some(length) = request.headers.get("content-length") {
+        let length: usize = length
+            .parse()
+            .with_context(|| format!("Invalid Content-Length '{}'", length))?;
+        anyhow::ensure!(
+            length <= MAX_BODY_BYTES,
+            "The request body is over {} bytes.",
+            MAX_BODY_BYTES
+        );
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body).await?;
+    }
+    Ok(request)
+}
+
+/// Reads one line into `line`, failing if it's over `MAX_LINE_BYTES`.
+async fn read_line<R>(reader: &mut R, line: &mut String) -> anyhow::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let read = reader
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_line(line)
+        .await?;
+    anyhow::ensure!(
+        read <= MAX_LINE_BYTES,
+        "A request line is over {} bytes.",
+        MAX_LINE_BYTES
+    );
+    Ok(())
+}
+
+/// The dashboard `track serve` runs: HTML pages over a database, or with
+/// `api` set, a JSON API for other clients.
+#[derive(Debug, Clone)]
+pub struct Server {
+    /// The database being served.
+    pub pool: SqlitePool,
+    /// Only show this deck's problems.
+    pub deck: Option<String>,
+    /// Serve the JSON API instead of the HTML dashboard.
+    pub api: bool,
+    /// The bearer token API requests must carry, if any.
+    pub token: Option<String>,
+    /// The hooks whose on_attempt runs for each attempt logged.
+    pub hooks: Hooks,
+    /// Where each attempt logged is POSTed, if anywhere.
+    pub webhook_url: Option<String>,
+}
+
+/// An attempt to log, as posted to the API's `/attempts` or passed to the
+/// MCP server's `log_attempt` tool.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct AttemptRequest {
+    /// The problem: its LeetCode ID, or its name, URL slug or URL.
+    pub problem: ProblemField,
+    /// The rating, from 1 (ShortFail) to 5 (Easy).
+    pub rating: u8,
+    /// The day of the attempt. Defaults to today.
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    /// The language it was solved in.
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// How long it took, in minutes.
+    #[serde(default)]
+    pub minutes: Option<i64>,
+    /// Anything worth remembering about the attempt.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// A problem as named in an `AttemptRequest`: a JSON number or string.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ProblemField {
+    /// Its LeetCode ID.
+    Id(i64),
+    /// Its ID as a string, its name, its URL slug or its URL.
+    Text(String),
+}
+
+impl From<&ProblemField> for ProblemRef {
+    fn from(field: &ProblemField) -> Self {
+        match field {
+            ProblemField::Id(id) => ProblemRef::Id(*id),
+            ProblemField::Text(text) => text.parse().expect("parsing a ProblemRef never fails"),
+        }
+    }
+}
+
+/// An attempt the server logged.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct LoggedAttempt {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The rating logged.
+    pub rating: AttemptRating,
+    /// Whether this was the problem's first logged attempt.
+    pub first_attempt: bool,
+    /// Whether the problem was looked up on LeetCode and added, which only
+    /// happens when logging by URL.
+    pub added_problem: bool,
+}
+
+impl Server {
+    /// Serves connections from `listener` until the process is stopped.
+    ///
+    /// Connections are served concurrently but on this task, since the
+    /// compiler can't prove the database helpers' futures `Send`, which
+    /// spawning them would need.
+    pub async fn run(&self, listener: TcpListener) -> anyhow::Result<()> {
+        let mut connections = FuturesUnordered::new();
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted.context("Failed to accept a connection")?;
+                    connections.push(self.serve_connection(stream));
+                }
+                Some(result) = connections.next(), if !connections.is_empty() => {
+                    if let Err(e) = result {
+                        eprintln!("Connection failed: {:#}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads one request from `stream` and writes back the response.
+    async fn serve_connection(&self, stream: TcpStream) -> anyhow::Result<()> {
+        let (read, mut write) = stream.into_split();
+        let mut reader = BufReader::new(read);
+        let response = match read_request(&mut reader).await {
+            Ok(request) => self.handle(&request).await,
+            Err(e) => self.error(400, format!("{:#}", e)),
+        };
+        write.write_all(&response.to_bytes()).await?;
+        write.shutdown().await?;
+        Ok(())
+    }
+
+    /// Answers one request. Errors become a 500 carrying the message.
+    ///
+    /// With a token set, API responses allow any origin, so scripts running
+    /// on LeetCode's pages can call the API; the token is what keeps it
+    /// private. Without one, other pages can't read the API, and writes are
+    /// only taken from clients that aren't browsers or from this origin.
+    pub async fn handle(&self, request: &Request) -> Response {
+        if !self.api {
+            return self
+                .route(request)
+                .await
+                .unwrap_or_else(|e| self.error(500, format!("{:#}", e)));
+        }
+        let mut response = self
+            .route_api(request)
+            .await
+            .unwrap_or_else(|e| self.error(500, format!("{:#}", e)));
+        if self.token.is_none() {
+            return response;
+        }
+        for (name, value) in [
+            ("Access-Control-Allow-Origin", "*"),
+            (
+                "Access-Control-Allow-Headers",
+                "Authorization, Content-Type",
+            ),
+            ("Access-Control-Allow-Methods", "GET, POST"),
+        ] {
+            response.headers.push((name.to_string(), value.to_string()));
+        }
+        response
+    }
+
+    /// An error response: JSON for the API, plain text for the dashboard.
+    fn error(&self, status: u16, message: impl Into<String>) -> Response {
+        let message = message.into();
+        if self.api {
+            Response::json(status, &serde_json::json!({ "error": message }))
+        } else {
+            Response::text(status, format!("{}\n", message))
+        }
+    }
+
+    async fn route(&self, request: &Request) -> anyhow::Result<Response> {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/") => self.dashboard().await,
+            ("POST", "/attempts") if !is_same_origin(request) => Ok(self.error(
+                403,
+                "Attempts can only be logged from the dashboard's own page.",
+            )),
+            ("POST", "/attempts") => self.log_attempt_from_form(&request.form()).await,
+            (_, "/") | (_, "/attempts") => Ok(self.error(405, "Method not allowed.")),
+            _ => Ok(self.error(404, "Not found.")),
+        }
+    }
+
+    async fn route_api(&self, request: &Request) -> anyhow::Result<Response> {
+        match &self.token {
+            // Browsers ask before a cross-origin POST, without the token.
+            Some(_) if request.method == "OPTIONS" => return Ok(Response::text(204, "")),
+            Some(token) => {
+                let sent = request
+                    .headers
+                    .get("authorization")
+                    .and_then(|value| value.strip_prefix("Bearer "));
+                if sent != Some(token.as_str()) {
+                    let mut response = self.error(401, "Missing or wrong bearer token.");
+                    response
+                        .headers
+                        .push(("WWW-Authenticate".to_string(), "Bearer".to_string()));
+                    return Ok(response);
+                }
+            }
+            // Any page can still send a simple POST without asking first.
+            None if request.method == "POST" && !is_same_origin(request) => {
+                return Ok(self.error(
+                    403,
+                    "Without a token, attempts can't be logged from other sites' pages.",
+                ));
+            }
+            None => {}
+        }
+
+        let today = Local::now().date_naive();
+        let deck = self.deck.as_deref();
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/problems") => {
+                let tag = request.query.get("tag").map(String::as_str);
+                let problems = fetch_problems_page(&self.pool, deck, tag, -1, 0).await?;
+                Ok(Response::json(200, &problems))
+            }
+            ("GET", "/due") => {
+                let due = fetch_due_problems(&self.pool, today, deck).await?;
+                Ok(Response::json(200, &due))
+            }
+            ("GET", "/stats") => {
+                let report = fetch_stats_report(&self.pool, deck, today).await?;
+                Ok(Response::json(200, &report))
+            }
+            ("POST", "/attempts") => {
+                let attempt: AttemptRequest = match serde_json::from_slice(&request.body) {
+                    Ok(attempt) => attempt,
+                    Err(e) => return Ok(self.error(400, format!("Invalid attempt: {}", e))),
+                };
+                Ok(
+                    match log_requested_attempt(
+                        &self.pool,
+                        &attempt,
+                        &self.hooks,
+                        self.webhook_url.as_deref(),
+                    )
+                    .await?
+                    {
+                        Ok(logged) => Response::json(201, &logged),
+                        Err(message) => self.error(400, message),
+                    },
+                )
+            }
+            // Logging by URL can reach LeetCode and add problems, so unlike the
+            // rest of the API it's never open to any page that finds the port.
+            ("POST", "/attempts/by-url") if self.token.is_none() => Ok(self.error(
+                403,
+                "Logging by URL needs a token: restart with `track serve --api --token <TOKEN>`.",
+            )),
+            ("POST", "/attempts/by-url") => self.log_attempt_by_url(&request.body).await,
+            (_, "/problems" | "/due" | "/stats" | "/attempts" | "/attempts/by-url") => {
+                Ok(self.error(405, "Method not allowed."))
+            }
+            _ => Ok(self.error(404, "Not found.")),
+        }
+    }
+
+    async fn dashboard(&self) -> anyhow::Result<Response> {
+        let today = Local::now().date_naive();
+        let deck = self.deck.as_deref();
+        let report = fetch_stats_report(&self.pool, deck, today).await?;
+        let due = fetch_due_problems(&self.pool, today, deck).await?;
+        let dates = fetch_attempt_dates(&self.pool).await?;
+        let heatmap = render_heatmap(&dates, today, HEATMAP_WEEKS, &SHADES);
+        Ok(Response::html(render_dashboard(
+            &report, &due, &heatmap, today,
+        )))
+    }
+
+    /// Logs the attempt posted by the dashboard's form, then sends the
+    /// browser back to the dashboard.
+    async fn log_attempt_from_form(
+        &self,
+        form: &HashMap<String, String>,
+    ) -> anyhow::Result<Response> {
+        let field = |name: &str| {
+            form.get(name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let Some(problem) = field("problem") else {
+            return Ok(self.error(400, "Say which problem you attempted."));
+        };
+        let minutes = match field("minutes").map(str::parse::<i64>) {
+            Some(Ok(minutes)) => Some(minutes),
+            Some(Err(_)) => return Ok(self.error(400, "Minutes must be a whole number above 0.")),
+            None => None,
+        };
+        let attempt = AttemptRequest {
+            problem: ProblemField::Text(problem.to_string()),
+            // Anything that isn't a number is as invalid as 0.
+            rating: field("rating").and_then(|r| r.parse().ok()).unwrap_or(0),
+            date: None,
+            lang: field("lang").map(str::to_string),
+            minutes,
+            note: field("note").map(str::to_string),
+        };
+        Ok(
+            match log_requested_attempt(
+                &self.pool,
+                &attempt,
+                &self.hooks,
+                self.webhook_url.as_deref(),
+            )
+            .await?
+            {
+                Ok(_) => Response::redirect("/"),
+                Err(message) => self.error(400, message),
+            },
+        )
+    }
+
+    /// Logs an attempt posted to `/attempts/by-url`: the same body as
+    /// `/attempts`, but with the problem's LeetCode `url` instead of
+    /// `problem`. A problem that isn't stored yet is looked up on LeetCode
+    /// and added first. Only served when a token is set.
+    async fn log_attempt_by_url(&self, body: &[u8]) -> anyhow::Result<Response> {
+        let mut fields: serde_json::Map<String, serde_json::Value> =
+            match serde_json::from_slice(body) {
+                Ok(fields) => fields,
+                Err(e) => return Ok(self.error(400, format!("Invalid attempt: {}", e))),
+            };
+        let Some(url) = fields
+            .remove("url")
+            .and_then(|url| url.as_str().map(str::to_string))
+        else {
+            return Ok(self.error(400, "Invalid attempt: missing field `url`"));
+        };
+        let Some(slug) = url_slug(&url)
+            .map(str::to_lowercase)
+            .filter(|slug| is_valid_slug(slug))
+        else {
+            return Ok(self.error(400, format!("'{}' isn't a LeetCode problem URL.", url)));
+        };
+
+        let stored = fetch_problem_ids_by_slug(&self.pool, None)
+            .await?
+            .get(&slug)
+            .copied();
+        let (problem_id, added_problem) = match stored {
+            Some(id) => (id, false),
+            None => {
+                let url = format!("https://leetcode.com/problems/{}/", slug);
+                let meta = match fetch_meta_from_leetcode(&url).await {
+                    Ok(meta) => meta,
+                    Err(e) => {
+                        return Ok(self.error(400, format!("Couldn't look up '{}': {:#}", slug, e)));
+                    }
+                };
+                let deck = self.deck.as_deref().unwrap_or(BY_URL_DECK);
+                let today = Local::now().date_naive();
+                let added = add_problem_from_meta(&self.pool, &meta, &url, deck, today).await?;
+                cache_resolved_id(&self.pool, &slug, meta.question_id).await?;
+                (meta.question_id, added)
+            }
+        };
+
+        fields.insert("problem".to_string(), problem_id.into());
+        let attempt: AttemptRequest = match serde_json::from_value(fields.into()) {
+            Ok(attempt) => attempt,
+            Err(e) => return Ok(self.error(400, format!("Invalid attempt: {}", e))),
+        };
+        Ok(
+            match log_requested_attempt(
+                &self.pool,
+                &attempt,
+                &self.hooks,
+                self.webhook_url.as_deref(),
+            )
+            .await?
+            {
+                Ok(logged) => Response::json(
+                    201,
+                    &LoggedAttempt {
+                        added_problem,
+                        ..logged
+                    },
+                ),
+                Err(message) => self.error(400, message),
+            },
+        )
+    }
+}
+
+/// Whether `request` came from a page served by this server, going by its
+/// `Origin` header, or `Referer` if there's none. Browsers send one of them
+/// with posts, so a page on another site can't log attempts; clients
+/// that send neither, such as curl, aren't browsers and are let through.
+fn is_same_origin(request: &Request) -> bool {
+    let Some(source) = request
+        .headers
+        .get("origin")
+        .or_else(|| request.headers.get("referer"))
+    else {
+        return true;
+    };
+    let Some(host) = request.headers.get("host") else {
+        return false;
+    };
+    let authority = source
+        .strip_prefix("http://")
+        .or_else(|| source.strip_prefix("https://"))
+        .and_then(|rest| rest.split('/').next());
+    authority.is_some_and(|authority| authority.eq_ignore_ascii_case(host))
+}
+
+/// Logs `attempt`, then tells the on_attempt hook and the webhook about it.
+/// Problems with the request itself, such as a rating out of range or an
+/// unknown problem, come back as an `Err` message.
+pub async fn log_requested_attempt(
+    pool: &SqlitePool,
+    attempt: &AttemptRequest,
+    hooks: &Hooks,
+    webhook_url: Option<&str>,
+) -> anyhow::Result<Result<LoggedAttempt, String>> {
+    let Some(rating) = AttemptRating::from_score(attempt.rating) else {
+        return Ok(Err("The rating must be from 1 to 5.".to_string()));
+    };
+    if attempt.minutes.is_some_and(|minutes| minutes <= 0) {
+        return Ok(Err("Minutes must be a whole number above 0.".to_string()));
+    }
+    let id = match resolve_problem(pool, &ProblemRef::from(&attempt.problem)).await {
+        Ok(id) => id,
+        Err(e) => return Ok(Err(format!("{:#}", e))),
+    };
+    if fetch_problem(pool, id).await?.is_none() {
+        return Ok(Err(format!(
+            "No problem with id {} exists in the database.",
+            id
+        )));
+    }
+
+    let text = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let details = AttemptDetails {
+        language: text(&attempt.lang).map(|lang| lang.to_lowercase()),
+        duration_minutes: attempt.minutes,
+        note: text(&attempt.note),
+    };
+    let first_attempt = log_attempt(pool, id, rating, attempt.date, details).await?;
+    announce_attempt(hooks, webhook_url, pool, id).await;
+    Ok(Ok(LoggedAttempt {
+        problem_id: id,
+        rating,
+        first_attempt,
+        added_problem: false,
+    }))
+}
+
+/// Renders the dashboard page: the headline stats, a form to log an attempt,
+/// the due queue, the activity heatmap and per-week and per-tag progress.
+pub fn render_dashboard(
+    report: &StatsReport,
+    due: &[ScheduledReview],
+    heatmap: &str,
+    today: NaiveDate,
+) -> String {
+    let stats = &report.summary;
+    let mut page = String::from(concat!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>track</title>\n<style>\n",
+        "body { font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }\n",
+        "table { border-collapse: collapse; }\n",
+        "td, th { padding: 0.2rem 0.8rem 0.2rem 0; text-align: left; }\n",
+        ".bar { background: #eee; width: 12rem; height: 0.8rem; }\n",
+        ".bar div { background: #4a9; height: 100%; }\n",
+        "pre { line-height: 1.1; }\n",
+        "</style>\n</head>\n<body>\n<h1>track</h1>\n",
+    ));
+
+    page.push_str(&format!(
+        "<p>{} problem(s) attempted, {} attempt(s) in all. {} due for review. \
+         Current streak: {} day(s), longest: {}.</p>\n",
+        stats.total_attempted,
+        stats.total_attempts,
+        stats.due,
+        stats.streaks.current,
+        stats.streaks.longest
+    ));
+
+    page.push_str("<h2>Log an attempt</h2>\n<form method=\"post\" action=\"/attempts\">\n");
+    page.push_str("<input name=\"problem\" placeholder=\"ID, name or slug\" required>\n");
+    page.push_str("<select name=\"rating\">\n");
+    for rating in AttemptRating::ALL.iter().rev() {
+        page.push_str(&format!(
+            "<option value=\"{}\">{} - {}</option>\n",
+            rating.score(),
+            rating.score(),
+            rating
+        ));
+    }
+    page.push_str(concat!(
+        "</select>\n",
+        "<input name=\"lang\" placeholder=\"Language\" size=\"8\">\n",
+        "<input name=\"minutes\" placeholder=\"Minutes\" size=\"6\">\n",
+        "<input name=\"note\" placeholder=\"Note\">\n",
+        "<button>Log</button>\n</form>\n",
+    ));
+
+    page.push_str("<h2>Due for review</h2>\n");
+    if due.is_empty() {
+        page.push_str("<p>Nothing is due today.</p>\n");
+    } else {
+        page.push_str(
+            "<table>\n<tr><th>ID</th><th>Name</th><th>Difficulty</th><th>Due</th></tr>\n",
+        );
+        for review in due {
+            let days = review.days_overdue(today);
+            let when = match days {
+                0 => "today".to_string(),
+                1 => "1 day overdue".to_string(),
+                days => format!("{} days overdue", days),
+            };
+            page.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                review.problem_id,
+                escape_html(&review.name),
+                review
+                    .difficulty
+                    .map(|d| format!("{:?}", d))
+                    .unwrap_or_default(),
+                when
+            ));
+        }
+        page.push_str("</table>\n");
+    }
+
+    page.push_str(&format!(
+        "<h2>Activity</h2>\n<pre>{}</pre>\n",
+        escape_html(heatmap)
+    ));
+
+    page.push_str("<h2>Weeks</h2>\n<table>\n");
+    for week in &report.weeks {
+        let label = match week.week {
+            Some(week) => format!("Week {}", week),
+            None => "No week".to_string(),
+        };
+        page.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}/{}</td></tr>\n",
+            label,
+            bar(week.percent()),
+            week.attempted,
+            week.total
+        ));
+    }
+    page.push_str("</table>\n");
+
+    page.push_str("<h2>Tags</h2>\n");
+    if report.tags.is_empty() {
+        page.push_str("<p>No problems are tagged yet.</p>\n");
+    } else {
+        page.push_str(
+            "<table>\n<tr><th>Tag</th><th>Attempted</th><th></th><th>Struggling</th></tr>\n",
+        );
+        for tag in &report.tags {
+            let percent = if tag.total == 0 {
+                0.0
+            } else {
+                tag.attempted as f64 / tag.total as f64 * 100.0
+            };
+            let struggle = tag
+                .struggle_rate()
+                .map(|rate| format!("{:.0}%", rate * 100.0))
+                .unwrap_or_default();
+            page.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}/{}</td><td>{}</td></tr>\n",
+                escape_html(&tag.tag),
+                bar(percent),
+                tag.attempted,
+                tag.total,
+                struggle
+            ));
+        }
+        page.push_str("</table>\n");
+    }
+
+    page.push_str("</body>\n</html>\n");
+    page
+}
+
+/// A horizontal bar filled to `percent`.
+fn bar(percent: f64) -> String {
+    format!(
+        "<div class=\"bar\"><div style=\"width: {:.0}%\"></div></div>",
+        percent.clamp(0.0, 100.0)
+    )
+}
+
+/// Escapes `text` for use in HTML text and attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+use crate::db::{
+    AttemptDetails, ProblemRef, ScheduledReview, add_problem_from_meta, cache_resolved_id,
+    fetch_attempt_dates, fetch_due_problems, fetch_problem, fetch_problem_ids_by_slug,
+    fetch_problems_page, fetch_stats_report, log_attempt, resolve_problem, url_slug,
+};
+use crate::heatmap::{SHADES, render_heatmap};
+use crate::hooks::{Hooks, announce_attempt};
+use crate::leetcode::is_valid_slug;
+use crate::meta::fetch_problem_meta as fetch_meta_from_leetcode;
+use crate::problem_attempts::AttemptRating;
+use crate::stats::StatsReport;
+use anyhow::Context;
+use chrono::{Local, NaiveDate};
+use futures::stream::{FuturesUnordered, StreamExt};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::init_pool;
+    use crate::problems::{LeetCodeDifficulty, Problem};
+
+    fn request(method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: HashMap::new(),
+            headers: HashMap::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_the_request_line_headers_and_body() {
+        let raw = "POST /attempts?from=form HTTP/1.1\r\nHost: localhost\r\nContent-Length: 22\r\n\r\nproblem=two+sum&rating=5";
+        let request = read_request(&mut raw.as_bytes()).await.unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/attempts");
+        assert_eq!(request.query["from"], "form");
+        assert_eq!(request.headers["host"], "localhost");
+        // Only Content-Length bytes of the body are read.
+        assert_eq!(request.form()["problem"], "two sum");
+        assert_eq!(request.form()["rating"], "");
+        assert!(read_request(&mut "\r\n".as_bytes()).await.is_err());
+
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_BYTES));
+        let error = read_request(&mut long.as_bytes()).await.unwrap_err();
+        assert!(error.to_string().contains("over"));
+    }
+
+    async fn seeded_pool() -> SqlitePool {
+        let pool = init_pool("sqlite::memory:").await.unwrap();
+        Problem {
+            id: 1,
+            order: 1,
+            name: "Two Sum".to_string(),
+            difficulty: Some(LeetCodeDifficulty::Easy),
+            week: Some(1),
+            priority: 0,
+            deck: "grind-75".to_string(),
+            url: None,
+        }
+        .insert(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn logs_attempts_posted_from_the_form() {
+        let pool = seeded_pool().await;
+        let server = Server {
+            pool: pool.clone(),
+            deck: None,
+            api: false,
+            token: None,
+            hooks: Hooks::default(),
+            webhook_url: None,
+        };
+
+        let response = server
+            .handle(&request(
+                "POST",
+                "/attempts",
+                "problem=two+sum&rating=4&lang=Rust",
+            ))
+            .await;
+        assert_eq!(response.status, 303);
+        assert_eq!(response.header("location"), Some("/"));
+        let history = crate::db::fetch_attempt_history(&pool, 1).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].language.as_deref(), Some("rust"));
+
+        let bad = server
+            .handle(&request("POST", "/attempts", "problem=1&rating=9"))
+            .await;
+        assert_eq!(bad.status, 400);
+
+        // A form posted from another site is turned away; one from the
+        // dashboard itself isn't.
+        let mut cross_site = request("POST", "/attempts", "problem=1&rating=5");
+        cross_site
+            .headers
+            .insert("host".to_string(), "localhost:7878".to_string());
+        cross_site
+            .headers
+            .insert("origin".to_string(), "https://evil.example".to_string());
+        assert_eq!(server.handle(&cross_site).await.status, 403);
+        cross_site
+            .headers
+            .insert("origin".to_string(), "http://localhost:7878".to_string());
+        assert_eq!(server.handle(&cross_site).await.status, 303);
+        assert_eq!(
+            crate::db::fetch_attempt_history(&pool, 1)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        let page = server.handle(&request("GET", "/", "")).await;
+        assert_eq!(page.status, 200);
+        assert!(page.body.contains("Log an attempt"));
+        assert_eq!(
+            server.handle(&request("GET", "/nope", "")).await.status,
+            404
+        );
+    }
+
+    #[tokio::test]
+    async fn the_api_checks_the_token_and_logs_json_attempts() {
+        let server = Server {
+            pool: seeded_pool().await,
+            deck: None,
+            api: true,
+            token: Some("secret".to_string()),
+            hooks: Hooks::default(),
+            webhook_url: None,
+        };
+        let mut post = request("POST", "/attempts", r#"{"problem": 1, "rating": 5}"#);
+
+        let denied = server.handle(&post).await;
+        assert_eq!(denied.status, 401);
+        assert_eq!(denied.header("WWW-Authenticate"), Some("Bearer"));
+
+        post.headers
+            .insert("authorization".to_string(), "Bearer secret".to_string());
+        let logged = server.handle(&post).await;
+        assert_eq!(logged.status, 201);
+        let body: serde_json::Value = serde_json::from_str(&logged.body).unwrap();
+        assert_eq!(body["problem_id"], 1);
+        assert_eq!(body["first_attempt"], true);
+
+        post.body = br#"{"problem": "two-sum"}"#.to_vec();
+        let invalid = server.handle(&post).await;
+        assert_eq!(invalid.status, 400);
+        assert!(invalid.body.contains("\"error\""));
+    }
+
+    #[tokio::test]
+    async fn without_a_token_the_api_is_closed_to_other_sites() {
+        let server = Server {
+            pool: seeded_pool().await,
+            deck: None,
+            api: true,
+            token: None,
+            hooks: Hooks::default(),
+            webhook_url: None,
+        };
+        let from = |origin: &str| {
+            let mut post = request("POST", "/attempts", r#"{"problem": 1, "rating": 5}"#);
+            post.headers
+                .insert("host".to_string(), "localhost:7878".to_string());
+            post.headers
+                .insert("origin".to_string(), origin.to_string());
+            post
+        };
+
+        let listed = server.handle(&request("GET", "/problems", "")).await;
+        assert_eq!(listed.status, 200);
+        assert_eq!(listed.header("access-control-allow-origin"), None);
+        assert_eq!(
+            server
+                .handle(&request("OPTIONS", "/attempts", ""))
+                .await
+                .status,
+            405
+        );
+        assert_eq!(
+            server.handle(&from("https://evil.example")).await.status,
+            403
+        );
+        assert_eq!(
+            server.handle(&from("http://localhost:7878")).await.status,
+            201
+        );
+        let curl = request("POST", "/attempts", r#"{"problem": 1, "rating": 4}"#);
+        assert_eq!(server.handle(&curl).await.status, 201);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn logged_attempts_run_the_on_attempt_hook() {
+        let out = std::env::temp_dir().join(format!("track-hook-{}.json", std::process::id()));
+        let server = Server {
+            pool: seeded_pool().await,
+            deck: None,
+            api: true,
+            token: None,
+            hooks: Hooks {
+                on_attempt: Some(format!("cat > '{}'", out.display())),
+                ..Hooks::default()
+            },
+            webhook_url: None,
+        };
+
+        let post = request("POST", "/attempts", r#"{"problem": 1, "rating": 4}"#);
+        assert_eq!(server.handle(&post).await.status, 201);
+        let event: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        std::fs::remove_file(&out).unwrap();
+        assert_eq!(event["problem_id"], 1);
+        assert_eq!(event["rating"], "Hard");
+    }
+
+    #[tokio::test]
+    async fn logs_attempts_by_leetcode_url() {
+        let pool = seeded_pool().await;
+        let mut server = Server {
+            pool: pool.clone(),
+            deck: None,
+            api: true,
+            token: None,
+            hooks: Hooks::default(),
+            webhook_url: None,
+        };
+        let body = r#"{"url": "https://leetcode.com/problems/two-sum/description/", "rating": 2}"#;
+        let post = |body: &str| {
+            let mut post = request("POST", "/attempts/by-url", body);
+            post.headers
+                .insert("authorization".to_string(), "Bearer secret".to_string());
+            post
+        };
+
+        // Without a token the endpoint is off.
+        let refused = server
+            .handle(&request("POST", "/attempts/by-url", body))
+            .await;
+        assert_eq!(refused.status, 403);
+
+        server.token = Some("secret".to_string());
+        let logged = server.handle(&post(body)).await;
+        assert_eq!(logged.status, 201);
+        let logged: serde_json::Value = serde_json::from_str(&logged.body).unwrap();
+        assert_eq!(logged["problem_id"], 1);
+        assert_eq!(logged["added_problem"], false);
+
+        let not_leetcode = r#"{"url": "https://example.com/", "rating": 2}"#;
+        let rejected = server.handle(&post(not_leetcode)).await;
+        assert_eq!(rejected.status, 400);
+        assert_eq!(rejected.header("access-control-allow-origin"), Some("*"));
+
+        // A slug that isn't one is never sent to LeetCode.
+        let injected = r#"{"url": "https://leetcode.com/problems/two-sum$(id)/", "rating": 2}"#;
+        let rejected = server.handle(&post(injected)).await;
+        assert_eq!(rejected.status, 400);
+        assert!(rejected.body.contains("isn't a LeetCode problem URL"));
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+}
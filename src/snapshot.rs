@@ -0,0 +1,82 @@
+// src/snapshot.rs
+
+/// The snapshot format `track export --all` writes. Bump it when a change
+/// would stop older versions of track from reading the file correctly.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Everything in a database, as written by `track export --all` and read
+/// back by `track import`.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    /// The format version, `SNAPSHOT_VERSION` when written.
+    pub version: u32,
+    /// When the snapshot was taken.
+    pub exported_on: NaiveDate,
+    /// Every problem, with its decks and tags.
+    pub problems: Vec<SnapshotProblem>,
+    /// Every problem's progress.
+    pub progress: Vec<ProblemAttempt>,
+    /// Every logged attempt.
+    pub history: Vec<SnapshotAttempt>,
+    /// Every problem note set with `track note`.
+    pub notes: Vec<SnapshotNote>,
+}
+
+impl Snapshot {
+    /// Parses a snapshot, checking it's a version this build can read.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let deserializer = &mut serde_json::Deserializer::from_str(json);
+        let snapshot: Snapshot = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+            anyhow::anyhow!("Invalid snapshot: field `{}`: {}", e.path(), e.inner())
+        })?;
+        anyhow::ensure!(
+            snapshot.version <= SNAPSHOT_VERSION,
+            "The snapshot is version {}, but this version of track only reads up to {}.",
+            snapshot.version,
+            SNAPSHOT_VERSION
+        );
+        Ok(snapshot)
+    }
+}
+
+/// A problem in a snapshot.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotProblem {
+    /// The problem itself.
+    #[serde(flatten)]
+    pub problem: Problem,
+    /// Every deck the problem is in.
+    #[serde(default)]
+    pub decks: Vec<String>,
+    /// The problem's tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// A logged attempt in a snapshot.
+#[derive(Debug, FromRow, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotAttempt {
+    /// The LeetCode ID of the problem attempted.
+    pub problem_id: i64,
+    /// The attempt itself.
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub attempt: HistoryEntry,
+}
+
+/// A problem note in a snapshot.
+#[derive(Debug, FromRow, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotNote {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The note.
+    pub note: String,
+    /// When the note was last changed.
+    pub updated_on: NaiveDate,
+}
+
+use crate::db::HistoryEntry;
+use crate::problem_attempts::ProblemAttempt;
+use crate::problems::Problem;
+use chrono::NaiveDate;
+use sqlx::FromRow;
@@ -0,0 +1,182 @@
+// src/stats.rs
+
+/// Summary statistics over a set of attempted problems.
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize)]
+pub struct Stats {
+    /// How many problems have been attempted.
+    pub total_attempted: usize,
+    /// How many attempts they took between them.
+    pub total_attempts: i64,
+    /// Problems per latest rating. Ratings nobody has are left out.
+    pub by_rating: BTreeMap<AttemptRating, usize>,
+    /// Attempted problems per difficulty.
+    pub by_difficulty: DifficultyCounts,
+    /// Problems whose next review is today or earlier.
+    pub due: usize,
+    /// The current and longest daily streaks.
+    pub streaks: Streaks,
+}
+
+impl Stats {
+    /// The mean number of attempts per attempted problem.
+    pub fn average_attempts(&self) -> Option<f64> {
+        (self.total_attempted > 0).then(|| self.total_attempts as f64 / self.total_attempted as f64)
+    }
+}
+
+/// Everything `track stats` reports: the summary plus the per-week and
+/// per-difficulty breakdowns aggregated in the database.
+#[derive(Debug, serde::Serialize)]
+pub struct StatsReport {
+    /// The headline counts.
+    #[serde(flatten)]
+    pub summary: Stats,
+    /// The mean number of attempts per attempted problem.
+    pub average_attempts: Option<f64>,
+    /// Progress through each week of the study plan.
+    pub weeks: Vec<WeekCompletion>,
+    /// Latest ratings per difficulty.
+    pub difficulties: Vec<DifficultyBreakdown>,
+    /// Latest ratings per tag.
+    pub tags: Vec<TagBreakdown>,
+    /// Problems failed four or more times.
+    pub leeches: Vec<Leech>,
+}
+
+/// A count per difficulty, with problems missing one counted as `unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize)]
+pub struct DifficultyCounts {
+    /// Easy problems.
+    pub easy: usize,
+    /// Medium problems.
+    pub medium: usize,
+    /// Hard problems.
+    pub hard: usize,
+    /// Problems without a difficulty.
+    pub unknown: usize,
+}
+
+/// Computes `Stats` from progress rows and the dates of every logged attempt.
+pub fn compute_stats(
+    progress: &[ProgressView],
+    attempt_dates: &[NaiveDate],
+    today: NaiveDate,
+) -> Stats {
+    let mut stats = Stats {
+        total_attempted: progress.len(),
+        streaks: compute_streaks(attempt_dates, today),
+        ..Stats::default()
+    };
+
+    for item in progress {
+        stats.total_attempts += item.number_of_attempts;
+        *stats.by_rating.entry(item.attempt_rating).or_insert(0) += 1;
+        let counts = &mut stats.by_difficulty;
+        match item.difficulty {
+            Some(LeetCodeDifficulty::Easy) => counts.easy += 1,
+            Some(LeetCodeDifficulty::Medium) => counts.medium += 1,
+            Some(LeetCodeDifficulty::Hard) => counts.hard += 1,
+            None => counts.unknown += 1,
+        }
+        if item.next_attempt_date.is_some_and(|next| next <= today) {
+            stats.due += 1;
+        }
+    }
+
+    stats
+}
+
+use crate::db::{DifficultyBreakdown, Leech, ProgressView, TagBreakdown, WeekCompletion};
+use crate::problem_attempts::AttemptRating;
+use crate::problems::LeetCodeDifficulty;
+use crate::streaks::{Streaks, compute_streaks};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn view(
+        problem_id: i64,
+        difficulty: Option<LeetCodeDifficulty>,
+        rating: AttemptRating,
+        next_attempt_date: Option<NaiveDate>,
+    ) -> ProgressView {
+        ProgressView {
+            problem_id,
+            name: format!("Problem {}", problem_id),
+            difficulty,
+            last_attempted: date("2024-03-01"),
+            attempt_rating: rating,
+            number_of_attempts: 1,
+            mastered: false,
+            next_attempt_date,
+            duration_minutes: None,
+            note: None,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn counts_ratings_difficulties_and_due_reviews() {
+        let today = date("2024-03-10");
+        let progress = vec![
+            view(
+                1,
+                Some(LeetCodeDifficulty::Easy),
+                AttemptRating::Easy,
+                Some(date("2024-03-10")),
+            ),
+            view(
+                2,
+                Some(LeetCodeDifficulty::Hard),
+                AttemptRating::ShortFail,
+                Some(date("2024-03-11")),
+            ),
+            view(3, None, AttemptRating::Easy, None),
+        ];
+        let dates = [date("2024-03-09"), date("2024-03-10")];
+
+        let stats = compute_stats(&progress, &dates, today);
+
+        assert_eq!(stats.total_attempted, 3);
+        assert_eq!(stats.by_rating.get(&AttemptRating::Easy), Some(&2));
+        assert_eq!(stats.by_rating.get(&AttemptRating::ShortFail), Some(&1));
+        assert_eq!(stats.by_rating.get(&AttemptRating::Messy), None);
+        assert_eq!(
+            stats.by_difficulty,
+            DifficultyCounts {
+                easy: 1,
+                medium: 0,
+                hard: 1,
+                unknown: 1,
+            }
+        );
+        assert_eq!(stats.due, 1);
+        assert_eq!(stats.total_attempts, 3);
+        assert_eq!(stats.average_attempts(), Some(1.0));
+        assert_eq!(stats.streaks.current, 2);
+    }
+
+    #[test]
+    fn serializes_to_a_flat_json_object() {
+        let progress = vec![view(
+            1,
+            Some(LeetCodeDifficulty::Medium),
+            AttemptRating::Hard,
+            None,
+        )];
+
+        let json = serde_json::to_value(compute_stats(&progress, &[], date("2024-03-10"))).unwrap();
+
+        assert_eq!(json["total_attempted"], 1);
+        assert_eq!(json["by_rating"]["Hard"], 1);
+        assert_eq!(json["by_difficulty"]["medium"], 1);
+        assert_eq!(json["streaks"]["longest"], 0);
+    }
+}
@@ -0,0 +1,142 @@
+// src/streaks.rs
+
+/// Consecutive-day practice streaks derived from attempt dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize)]
+pub struct Streaks {
+    /// The run of consecutive days ending today (or yesterday, if nothing
+    /// has been logged today yet).
+    pub current: u32,
+    /// The longest run of consecutive days ever recorded.
+    pub longest: u32,
+}
+
+/// Computes the current and longest streaks from a list of attempt dates.
+///
+/// A day counts once no matter how many attempts were logged on it, and the
+/// dates don't need to be sorted. A streak that ran through yesterday is still
+/// "current" today, since there's time left to extend it.
+pub fn compute_streaks(dates: &[NaiveDate], today: NaiveDate) -> Streaks {
+    let days: BTreeSet<NaiveDate> = dates.iter().copied().collect();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in &days {
+        run = match previous {
+            Some(prev) if day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let yesterday = today - Duration::days(1);
+    let mut cursor = if days.contains(&today) {
+        today
+    } else {
+        yesterday
+    };
+    let mut current = 0;
+    while days.contains(&cursor) {
+        current += 1;
+        cursor -= Duration::days(1);
+    }
+
+    Streaks { current, longest }
+}
+
+/// The length of the current streak if it will break unless something is
+/// logged today: it ran through yesterday, but nothing is logged for today.
+pub fn streak_at_risk(dates: &[NaiveDate], today: NaiveDate) -> Option<u32> {
+    if dates.contains(&today) {
+        return None;
+    }
+    let current = compute_streaks(dates, today).current;
+    (current > 0).then_some(current)
+}
+
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn empty_history_has_no_streaks() {
+        assert_eq!(compute_streaks(&[], date("2024-03-10")), Streaks::default());
+    }
+
+    #[test]
+    fn current_streak_includes_today() {
+        let dates = [date("2024-03-08"), date("2024-03-09"), date("2024-03-10")];
+        let streaks = compute_streaks(&dates, date("2024-03-10"));
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 3,
+                longest: 3
+            }
+        );
+    }
+
+    #[test]
+    fn streak_through_yesterday_is_still_current() {
+        let dates = [date("2024-03-08"), date("2024-03-09")];
+        let streaks = compute_streaks(&dates, date("2024-03-10"));
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 2,
+                longest: 2
+            }
+        );
+    }
+
+    #[test]
+    fn missing_yesterday_breaks_the_current_streak() {
+        let dates = [date("2024-03-07"), date("2024-03-08")];
+        let streaks = compute_streaks(&dates, date("2024-03-10"));
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 0,
+                longest: 2
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_and_unsorted_dates_count_once() {
+        let dates = [
+            date("2024-03-10"),
+            date("2024-03-01"),
+            date("2024-03-02"),
+            date("2024-03-03"),
+            date("2024-03-03"),
+            date("2024-03-04"),
+            date("2024-03-09"),
+        ];
+        let streaks = compute_streaks(&dates, date("2024-03-10"));
+        assert_eq!(
+            streaks,
+            Streaks {
+                current: 2,
+                longest: 4
+            }
+        );
+    }
+
+    #[test]
+    fn streak_is_at_risk_until_today_is_logged() {
+        let today = date("2024-03-13");
+        let dates = [date("2024-03-11"), date("2024-03-12")];
+        assert_eq!(streak_at_risk(&dates, today), Some(2));
+        assert_eq!(streak_at_risk(&[date("2024-03-12"), today], today), None);
+        assert_eq!(streak_at_risk(&[date("2024-03-10")], today), None);
+    }
+}
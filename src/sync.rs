@@ -0,0 +1,234 @@
+// src/sync.rs
+
+/// Lists a page of the signed-in account's submissions, newest first.
+const SUBMISSIONS_QUERY: &str = "query submissionList($offset: Int!, $limit: Int!, $lastKey: String) { submissionList(offset: $offset, limit: $limit, lastKey: $lastKey) { lastKey hasNext submissions { titleSlug statusDisplay lang timestamp } } }";
+
+/// How many submissions are asked for at once.
+const PAGE_SIZE: usize = 20;
+
+/// The pause between pages, to stay well under LeetCode's rate limit.
+const PAGE_DELAY: Duration = Duration::from_secs(1);
+
+/// An accepted submission on LeetCode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptedSubmission {
+    /// The problem's URL slug, e.g. `two-sum`.
+    pub title_slug: String,
+    /// The language it was submitted in.
+    pub lang: String,
+    /// When it was submitted, in seconds since the Unix epoch.
+    pub timestamp: i64,
+}
+
+impl AcceptedSubmission {
+    /// The local date the submission was made on.
+    pub fn date(&self) -> Option<NaiveDate> {
+        Local
+            .timestamp_opt(self.timestamp, 0)
+            .single()
+            .map(|time| time.date_naive())
+    }
+}
+
+/// An attempt `track sync` will log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncedAttempt {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The day the problem was accepted.
+    pub attempted_on: NaiveDate,
+    /// The language of that day's last accepted submission.
+    pub language: String,
+}
+
+/// One page of `SUBMISSIONS_QUERY`'s answer.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubmissionPage {
+    last_key: Option<String>,
+    has_next: bool,
+    submissions: Vec<Submission>,
+}
+
+/// A submission as LeetCode sends it, accepted or not.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Submission {
+    title_slug: String,
+    status_display: String,
+    lang: String,
+    /// Seconds since the Unix epoch, as a string.
+    timestamp: String,
+}
+
+/// Fetches every accepted submission of `session`'s account, newest first,
+/// a page at a time.
+pub async fn fetch_accepted_submissions(
+    session: Session<'_>,
+) -> anyhow::Result<Vec<AcceptedSubmission>> {
+    let mut accepted = Vec::new();
+    let mut last_key: Option<String> = None;
+    for offset in (0..).step_by(PAGE_SIZE) {
+        let variables = serde_json::json!({
+            "offset": offset,
+            "limit": PAGE_SIZE,
+            "lastKey": last_key,
+        });
+        let data = graphql_signed_in(SUBMISSIONS_QUERY, variables, session).await?;
+        let (submissions, next_key) = parse_submission_page(&data)?;
+        accepted.extend(submissions);
+        if next_key.is_none() {
+            break;
+        }
+        last_key = next_key;
+        tokio::time::sleep(PAGE_DELAY).await;
+    }
+    Ok(accepted)
+}
+
+/// The accepted submissions in the `data` of a `SUBMISSIONS_QUERY` response,
+/// and the key of the next page if there is one.
+fn parse_submission_page(
+    data: &Value,
+) -> anyhow::Result<(Vec<AcceptedSubmission>, Option<String>)> {
+    let list = &data["submissionList"];
+    anyhow::ensure!(
+        !list.is_null(),
+        "LeetCode didn't return any submissions. Is the session cookie still valid?"
+    );
+    let page = SubmissionPage::deserialize(list)
+        .with_context(|| format!("Unexpected submission list from LeetCode: {}", list))?;
+    let accepted = page
+        .submissions
+        .into_iter()
+        .filter(|submission| submission.status_display == "Accepted")
+        .map(|submission| {
+            let timestamp = submission.timestamp.parse().with_context(|| {
+                format!(
+                    "LeetCode's submission timestamp '{}' isn't a number.",
+                    submission.timestamp
+                )
+            })?;
+            Ok(AcceptedSubmission {
+                title_slug: submission.title_slug,
+                lang: submission.lang,
+                timestamp,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    let next_key = page.last_key.filter(|_| page.has_next);
+    Ok((accepted, next_key))
+}
+
+/// Picks the attempts to log: one per day each problem was accepted, oldest
+/// first, for problems in `slugs` (slug to problem ID) that aren't in
+/// `tracked` yet. Problems that already have progress are left alone so
+/// syncing never rewrites what you've logged by hand.
+pub fn plan_sync(
+    submissions: &[AcceptedSubmission],
+    slugs: &HashMap<String, i64>,
+    tracked: &HashSet<i64>,
+) -> Vec<SyncedAttempt> {
+    let mut days: BTreeMap<(i64, NaiveDate), (i64, &str)> = BTreeMap::new();
+    for submission in submissions {
+        let Some(&problem_id) = slugs.get(&submission.title_slug) else {
+            continue;
+        };
+        let Some(day) = submission.date() else {
+            continue;
+        };
+        if tracked.contains(&problem_id) {
+            continue;
+        }
+        let latest = days
+            .entry((problem_id, day))
+            .or_insert((submission.timestamp, &submission.lang));
+        if submission.timestamp > latest.0 {
+            *latest = (submission.timestamp, &submission.lang);
+        }
+    }
+
+    let mut attempts: Vec<SyncedAttempt> = days
+        .into_iter()
+        .map(
+            |((problem_id, attempted_on), (_, language))| SyncedAttempt {
+                problem_id,
+                attempted_on,
+                language: language.to_string(),
+            },
+        )
+        .collect();
+    attempts.sort_by_key(|a| (a.attempted_on, a.problem_id));
+    attempts
+}
+
+use crate::leetcode::{Session, graphql_signed_in};
+use anyhow::Context;
+use chrono::{Local, NaiveDate, TimeZone};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(slug: &str, lang: &str, timestamp: i64) -> AcceptedSubmission {
+        AcceptedSubmission {
+            title_slug: slug.to_string(),
+            lang: lang.to_string(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn keeps_the_accepted_submissions_of_a_page() {
+        let data = serde_json::json!({
+            "submissionList": {
+                "lastKey": "abc",
+                "hasNext": true,
+                "submissions": [
+                    {"titleSlug": "two-sum", "statusDisplay": "Accepted", "lang": "rust", "timestamp": "1700000000"},
+                    {"titleSlug": "two-sum", "statusDisplay": "Wrong Answer", "lang": "rust", "timestamp": "1699999000"},
+                    {"titleSlug": "lru-cache", "statusDisplay": "Accepted", "lang": "python3", "timestamp": "1690000000"}
+                ]
+            }
+        });
+        let (submissions, next_key) = parse_submission_page(&data).unwrap();
+        assert_eq!(submissions[0], submission("two-sum", "rust", 1700000000));
+        assert_eq!(submissions.len(), 2);
+        assert_eq!(next_key.as_deref(), Some("abc"));
+
+        let mut last = data.clone();
+        last["submissionList"]["hasNext"] = false.into();
+        assert_eq!(parse_submission_page(&last).unwrap().1, None);
+        let signed_out = serde_json::json!({ "submissionList": null });
+        assert!(parse_submission_page(&signed_out).is_err());
+    }
+
+    #[test]
+    fn logs_one_attempt_per_day_for_untracked_problems() {
+        let day = 86_400;
+        // Noon UTC falls on the same local day in almost every timezone.
+        let noon = 1_700_000_000 - 1_700_000_000 % day + day / 2;
+        let submissions = [
+            submission("two-sum", "rust", noon + 60),
+            submission("two-sum", "python3", noon),
+            submission("two-sum", "golang", noon - 3 * day),
+            submission("lru-cache", "rust", noon),
+            submission("not-in-any-deck", "rust", noon),
+        ];
+        let slugs = HashMap::from([("two-sum".to_string(), 1), ("lru-cache".to_string(), 146)]);
+        let tracked = HashSet::from([146]);
+
+        let attempts = plan_sync(&submissions, &slugs, &tracked);
+
+        let logged: Vec<(i64, &str)> = attempts
+            .iter()
+            .map(|a| (a.problem_id, a.language.as_str()))
+            .collect();
+        assert_eq!(logged, [(1, "golang"), (1, "rust")]);
+        assert!(attempts[0].attempted_on < attempts[1].attempted_on);
+    }
+}
@@ -0,0 +1,186 @@
+// src/table.rs
+
+/// A color a table cell can be drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// Good: Easy problems and ratings.
+    Green,
+    /// So-so: Medium problems, Hard and Messy ratings.
+    Yellow,
+    /// Bad: Hard problems and failed attempts.
+    Red,
+}
+
+impl Color {
+    /// The ANSI escape sequence that switches to the color.
+    fn code(self) -> &'static str {
+        match self {
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+            Color::Red => "\x1b[31m",
+        }
+    }
+}
+
+/// The ANSI escape sequence that resets colors.
+const RESET: &str = "\x1b[0m";
+
+/// The color a difficulty is drawn in.
+pub fn difficulty_color(difficulty: LeetCodeDifficulty) -> Color {
+    match difficulty {
+        LeetCodeDifficulty::Easy => Color::Green,
+        LeetCodeDifficulty::Medium => Color::Yellow,
+        LeetCodeDifficulty::Hard => Color::Red,
+    }
+}
+
+/// The color a rating is drawn in, by how badly the attempt went.
+pub fn rating_color(rating: AttemptRating) -> Color {
+    match rating {
+        AttemptRating::Easy => Color::Green,
+        AttemptRating::Hard | AttemptRating::Messy => Color::Yellow,
+        AttemptRating::LongFail | AttemptRating::ShortFail => Color::Red,
+    }
+}
+
+/// One cell of a `Table`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    text: String,
+    color: Option<Color>,
+}
+
+impl Cell {
+    /// A cell drawn in `color`.
+    pub fn colored(text: impl ToString, color: Color) -> Self {
+        Cell {
+            text: text.to_string(),
+            color: Some(color),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell { text, color: None }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::from(text.to_string())
+    }
+}
+
+/// Rows of text laid out in columns as wide as their widest cell.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// An empty table with these column headers.
+    pub fn new(headers: &[&str]) -> Self {
+        Table {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row. Missing trailing cells are left blank.
+    pub fn push(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    /// Whether the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Renders the headers, a rule under them and every row, each line
+    /// indented by two spaces. Colors are only written if `color` is set;
+    /// either way they don't count towards a column's width.
+    pub fn render(&self, color: bool) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (column, cell) in row.iter().enumerate() {
+                let width = cell.text.chars().count();
+                match widths.get_mut(column) {
+                    Some(max) => *max = (*max).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+
+        let headers: Vec<Cell> = self
+            .headers
+            .iter()
+            .map(|h| Cell::from(h.as_str()))
+            .collect();
+        let rule: Vec<Cell> = widths.iter().map(|&w| Cell::from("-".repeat(w))).collect();
+        let mut out = String::new();
+        for row in [&headers, &rule].into_iter().chain(&self.rows) {
+            let mut line = String::from(" ");
+            for (column, &width) in widths.iter().enumerate() {
+                let (text, cell_color) = match row.get(column) {
+                    Some(cell) => (cell.text.as_str(), cell.color),
+                    None => ("", None),
+                };
+                line.push(' ');
+                match cell_color.filter(|_| color) {
+                    Some(c) => line.push_str(&format!("{}{}{}", c.code(), text, RESET)),
+                    None => line.push_str(text),
+                }
+                // Only pad between columns, so lines don't end in spaces.
+                if column + 1 < widths.len() {
+                    let padding = width - text.chars().count();
+                    line.push_str(&" ".repeat(padding + 1));
+                }
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+use crate::problem_attempts::AttemptRating;
+use crate::problems::LeetCodeDifficulty;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_columns_to_their_widest_cell() {
+        let mut table = Table::new(&["ID", "Name", "Diff"]);
+        table.push(vec!["1".into(), "Two Sum".into(), "Easy".into()]);
+        table.push(vec!["146".into(), "LRU Cache".into()]);
+
+        assert_eq!(
+            table.render(false),
+            concat!(
+                "  ID   Name       Diff\n",
+                "  ---  ---------  ----\n",
+                "  1    Two Sum    Easy\n",
+                "  146  LRU Cache\n",
+            )
+        );
+    }
+
+    #[test]
+    fn colors_do_not_shift_columns() {
+        let mut table = Table::new(&["Rating", "ID"]);
+        table.push(vec![
+            Cell::colored("Easy", rating_color(AttemptRating::Easy)),
+            "1".into(),
+        ]);
+
+        let plain = table.render(false);
+        let colored = table.render(true);
+        assert!(!plain.contains('\x1b'));
+        assert!(colored.contains("\x1b[32mEasy\x1b[0m    1"));
+        assert_eq!(colored.replace("\x1b[32m", "").replace(RESET, ""), plain);
+    }
+}
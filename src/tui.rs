@@ -0,0 +1,543 @@
+// src/tui.rs
+
+/// The keys the screen lists in its header.
+const KEY_HELP: &str = "Tab switch panel · ↑/↓ move · Enter or 1-5 log · r reload · q quit";
+
+/// Terminals at least this wide show the two lists side by side.
+const SIDE_BY_SIDE_WIDTH: usize = 80;
+
+/// One problem listed in a panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// The LeetCode ID of the problem.
+    pub problem_id: i64,
+    /// The problem's name.
+    pub name: String,
+    /// Shown after the name, e.g. "2 days overdue" or "Week 3".
+    pub detail: String,
+}
+
+/// What `track tui` shows, as loaded from the database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overview {
+    /// The reviews due today or earlier, most overdue first.
+    pub due: Vec<Row>,
+    /// The next unattempted problem of each week.
+    pub next_up: Vec<Row>,
+    /// How many problems' latest attempt got each rating, in `ALL` order.
+    pub ratings: Vec<(AttemptRating, usize)>,
+}
+
+impl Overview {
+    /// Loads the overview of `deck`, or of every deck, as of `today`.
+    pub async fn load(
+        pool: &SqlitePool,
+        deck: Option<&str>,
+        today: NaiveDate,
+    ) -> anyhow::Result<Overview> {
+        let due = fetch_due_problems(pool, today, deck)
+            .await?
+            .into_iter()
+            .map(|review| Row {
+                detail: match review.days_overdue(today) {
+                    0 => "due today".to_string(),
+                    1 => "1 day overdue".to_string(),
+                    days => format!("{} days overdue", days),
+                },
+                problem_id: review.problem_id,
+                name: review.name,
+            })
+            .collect();
+        let next_up = fetch_next_unattempted_by_week(pool, deck)
+            .await?
+            .into_iter()
+            .map(|problem| Row {
+                detail: match problem.week {
+                    Some(week) => format!("Week {}", week),
+                    None => "Unassigned".to_string(),
+                },
+                problem_id: problem.id,
+                name: problem.name,
+            })
+            .collect();
+        let progress = fetch_all_progress(pool, None, deck).await?;
+        let ratings = AttemptRating::ALL
+            .iter()
+            .map(|&rating| {
+                let count = progress
+                    .iter()
+                    .filter(|item| item.attempt_rating == rating)
+                    .count();
+                (rating, count)
+            })
+            .collect();
+
+        Ok(Overview {
+            due,
+            next_up,
+            ratings,
+        })
+    }
+}
+
+/// The panels that list problems, which the cursor moves between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    /// The due reviews.
+    Due,
+    /// The next problem of each week.
+    NextUp,
+}
+
+/// What the caller should do after a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Just draw the screen again.
+    Redraw,
+    /// Load the overview again.
+    Reload,
+    /// Log an attempt, then load the overview again.
+    Log {
+        /// The LeetCode ID of the problem attempted.
+        problem_id: i64,
+        /// The rating picked.
+        rating: AttemptRating,
+    },
+    /// Leave the screen.
+    Quit,
+}
+
+/// The state of `track tui`: the overview, where the cursor is, and the line
+/// at the bottom.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    overview: Overview,
+    panel: Panel,
+    due_cursor: usize,
+    next_cursor: usize,
+    /// The problem a rating is being asked for, after Enter.
+    rating_for: Option<i64>,
+    /// The message at the bottom, such as what was just logged.
+    pub status: String,
+}
+
+impl Screen {
+    /// A screen on `overview`, with the cursor on the first due review, or on
+    /// the next problem up if nothing is due.
+    pub fn new(overview: Overview) -> Self {
+        let panel = if overview.due.is_empty() {
+            Panel::NextUp
+        } else {
+            Panel::Due
+        };
+        Screen {
+            overview,
+            panel,
+            due_cursor: 0,
+            next_cursor: 0,
+            rating_for: None,
+            status: String::new(),
+        }
+    }
+
+    /// Swaps in a freshly loaded overview, keeping the cursors in range.
+    pub fn set_overview(&mut self, overview: Overview) {
+        self.overview = overview;
+        self.due_cursor = self
+            .due_cursor
+            .min(self.overview.due.len().saturating_sub(1));
+        self.next_cursor = self
+            .next_cursor
+            .min(self.overview.next_up.len().saturating_sub(1));
+    }
+
+    /// The panel the cursor is in.
+    pub fn panel(&self) -> Panel {
+        self.panel
+    }
+
+    /// The problem under the cursor, if its panel lists any.
+    pub fn selected(&self) -> Option<&Row> {
+        match self.panel {
+            Panel::Due => self.overview.due.get(self.due_cursor),
+            Panel::NextUp => self.overview.next_up.get(self.next_cursor),
+        }
+    }
+
+    /// Reacts to `key`. While a rating is being asked for, only 1-5 and Esc
+    /// do anything.
+    pub fn handle_key(&mut self, key: &Key) -> Action {
+        if let Some(problem_id) = self.rating_for {
+            return match key {
+                Key::CtrlC => Action::Quit,
+                Key::Escape | Key::Backspace => {
+                    self.rating_for = None;
+                    self.status = "Nothing logged.".to_string();
+                    Action::Redraw
+                }
+                Key::Char(c) => match rating_for_key(*c) {
+                    Some(rating) => {
+                        self.rating_for = None;
+                        Action::Log { problem_id, rating }
+                    }
+                    None => Action::Redraw,
+                },
+                _ => Action::Redraw,
+            };
+        }
+
+        match key {
+            Key::Char('q') | Key::Escape | Key::CtrlC => Action::Quit,
+            Key::Char('r') => Action::Reload,
+            Key::Tab
+            | Key::BackTab
+            | Key::ArrowLeft
+            | Key::ArrowRight
+            | Key::Char('h')
+            | Key::Char('l') => {
+                self.panel = match self.panel {
+                    Panel::Due => Panel::NextUp,
+                    Panel::NextUp => Panel::Due,
+                };
+                Action::Redraw
+            }
+            Key::ArrowUp | Key::Char('k') => {
+                self.move_cursor(false);
+                Action::Redraw
+            }
+            Key::ArrowDown | Key::Char('j') => {
+                self.move_cursor(true);
+                Action::Redraw
+            }
+            Key::Enter | Key::Char('a') => {
+                match self.selected() {
+                    Some(row) => self.rating_for = Some(row.problem_id),
+                    None => self.status = "There's nothing here to log.".to_string(),
+                }
+                Action::Redraw
+            }
+            Key::Char(c) => match (rating_for_key(*c), self.selected()) {
+                (Some(rating), Some(row)) => Action::Log {
+                    problem_id: row.problem_id,
+                    rating,
+                },
+                _ => Action::Redraw,
+            },
+            _ => Action::Redraw,
+        }
+    }
+
+    fn move_cursor(&mut self, down: bool) {
+        let (cursor, len) = match self.panel {
+            Panel::Due => (&mut self.due_cursor, self.overview.due.len()),
+            Panel::NextUp => (&mut self.next_cursor, self.overview.next_up.len()),
+        };
+        *cursor = if down {
+            (*cursor + 1).min(len.saturating_sub(1))
+        } else {
+            cursor.saturating_sub(1)
+        };
+    }
+
+    /// Renders the screen as `height` lines, each `width` columns wide:
+    /// the key help, the two problem lists, the rating histogram and the
+    /// status line.
+    pub fn render(&self, width: usize, height: usize) -> Vec<String> {
+        let width = width.max(20);
+        let ratings_height = self.overview.ratings.len() + 2;
+        let lists_height = height.saturating_sub(ratings_height + 2).max(4);
+
+        let mut lines = vec![fit(&format!(" track  {}", KEY_HELP), width)];
+        let due = |width, height| {
+            self.list_panel(
+                Panel::Due,
+                &format!("Due reviews ({})", self.overview.due.len()),
+                "Nothing is due.",
+                width,
+                height,
+            )
+        };
+        let next_up = |width, height| {
+            self.list_panel(
+                Panel::NextUp,
+                "Next up by week",
+                "Every problem has been attempted.",
+                width,
+                height,
+            )
+        };
+        if width >= SIDE_BY_SIDE_WIDTH {
+            let left = width / 2;
+            let right = width - left;
+            let next_up = next_up(right, lists_height);
+            lines.extend(
+                due(left, lists_height)
+                    .into_iter()
+                    .zip(next_up)
+                    .map(|(left, right)| left + &right),
+            );
+        } else {
+            let top = lists_height / 2;
+            lines.extend(due(width, top));
+            lines.extend(next_up(width, lists_height - top));
+        }
+        lines.extend(self.ratings_panel(width, ratings_height));
+        lines.push(fit(&self.status_line(), width));
+
+        lines.truncate(height);
+        lines
+    }
+
+    /// One of the problem lists, boxed, scrolled so its cursor is in view.
+    fn list_panel(
+        &self,
+        panel: Panel,
+        title: &str,
+        empty: &str,
+        width: usize,
+        height: usize,
+    ) -> Vec<String> {
+        let (rows, cursor) = match panel {
+            Panel::Due => (&self.overview.due, self.due_cursor),
+            Panel::NextUp => (&self.overview.next_up, self.next_cursor),
+        };
+        let focused = self.panel == panel;
+        let inner = width.saturating_sub(2);
+        let visible = height.saturating_sub(2).max(1);
+        let first = (cursor + 1).saturating_sub(visible);
+        let detail_width = rows
+            .iter()
+            .map(|row| measure_text_width(&row.detail))
+            .max()
+            .unwrap_or(0);
+
+        let body: Vec<String> = if rows.is_empty() {
+            vec![fit(&format!("  {}", empty), inner)]
+        } else {
+            rows.iter()
+                .enumerate()
+                .skip(first)
+                .take(visible)
+                .map(|(index, row)| {
+                    let selected = focused && index == cursor;
+                    let start = format!(
+                        "{}#{:<5} ",
+                        if selected { "> " } else { "  " },
+                        row.problem_id
+                    );
+                    let name_width = inner.saturating_sub(start.len() + detail_width + 2);
+                    let line = fit(
+                        &format!(
+                            "{}{} {:>detail_width$}",
+                            start,
+                            fit(&row.name, name_width),
+                            row.detail
+                        ),
+                        inner,
+                    );
+                    if selected {
+                        style(line).reverse().to_string()
+                    } else {
+                        line
+                    }
+                })
+                .collect()
+        };
+        let title = if focused {
+            format!("[{}]", title)
+        } else {
+            title.to_string()
+        };
+        boxed(&title, &body, width, height)
+    }
+
+    /// How the latest ratings are spread, one bar per rating.
+    fn ratings_panel(&self, width: usize, height: usize) -> Vec<String> {
+        let attempted: usize = self.overview.ratings.iter().map(|(_, count)| count).sum();
+        let bar_width = width.saturating_sub(22);
+        let body: Vec<String> = self
+            .overview
+            .ratings
+            .iter()
+            .map(|&(rating, count)| {
+                let filled = if attempted == 0 {
+                    0
+                } else {
+                    (count * bar_width).div_ceil(attempted).min(bar_width)
+                };
+                format!(
+                    " {:<10} {:>4} {}",
+                    rating.to_string(),
+                    count,
+                    "#".repeat(filled)
+                )
+            })
+            .collect();
+        boxed("Latest ratings", &body, width, height)
+    }
+
+    /// The bottom line: the rating prompt while one is open, else the status.
+    fn status_line(&self) -> String {
+        let Some(problem_id) = self.rating_for else {
+            return format!(" {}", self.status);
+        };
+        let choices: Vec<String> = AttemptRating::ALL
+            .iter()
+            .rev()
+            .map(|rating| format!("{} {}", rating.score(), rating))
+            .collect();
+        format!(
+            " Rate #{}: {} (Esc cancels)",
+            problem_id,
+            choices.join(" · ")
+        )
+    }
+}
+
+/// The rating a digit key picks, from 1 (ShortFail) to 5 (Easy).
+fn rating_for_key(c: char) -> Option<AttemptRating> {
+    c.to_digit(10)
+        .and_then(|digit| AttemptRating::from_score(digit as u8))
+}
+
+/// `text`, cut to `width` columns with a trailing "…" if it's wider.
+fn shorten(text: &str, width: usize) -> Cow<'_, str> {
+    // truncate_str also cuts text that exactly fills the width.
+    if measure_text_width(text) <= width {
+        Cow::Borrowed(text)
+    } else if width == 0 {
+        Cow::Borrowed("")
+    } else {
+        truncate_str(text, width, "…")
+    }
+}
+
+/// `text` cut or padded to exactly `width` columns.
+fn fit(text: &str, width: usize) -> String {
+    pad_str(&shorten(text, width), width, Alignment::Left, None).into_owned()
+}
+
+/// `body` in a box `width` wide and `height` tall, with `title` in its top
+/// edge. Lines past the box's height are left out.
+fn boxed(title: &str, body: &[String], width: usize, height: usize) -> Vec<String> {
+    let inner = width.saturating_sub(2);
+    let title = shorten(title, inner.saturating_sub(3));
+    let top = format!("┌─ {} ", title);
+    let mut lines = vec![format!(
+        "{}{}┐",
+        top,
+        "─".repeat(width.saturating_sub(measure_text_width(&top) + 1))
+    )];
+    for index in 0..height.saturating_sub(2) {
+        let line = body.get(index).map(String::as_str).unwrap_or("");
+        lines.push(format!("│{}│", fit(line, inner)));
+    }
+    lines.push(format!("└{}┘", "─".repeat(inner)));
+    lines
+}
+
+use crate::db::{fetch_all_progress, fetch_due_problems, fetch_next_unattempted_by_week};
+use crate::problem_attempts::AttemptRating;
+use chrono::NaiveDate;
+use console::{Alignment, Key, measure_text_width, pad_str, style, truncate_str};
+use sqlx::SqlitePool;
+use std::borrow::Cow;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overview() -> Overview {
+        let row = |problem_id, name: &str, detail: &str| Row {
+            problem_id,
+            name: name.to_string(),
+            detail: detail.to_string(),
+        };
+        Overview {
+            due: vec![
+                row(1, "Two Sum", "3 days overdue"),
+                row(20, "Valid Parentheses", "due today"),
+            ],
+            next_up: vec![row(146, "LRU Cache", "Week 2")],
+            ratings: AttemptRating::ALL
+                .iter()
+                .map(|&rating| (rating, usize::from(rating == AttemptRating::Easy)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn keys_move_between_panels_and_log_the_selected_problem() {
+        let mut screen = Screen::new(overview());
+
+        assert_eq!(screen.handle_key(&Key::ArrowDown), Action::Redraw);
+        assert_eq!(screen.selected().unwrap().problem_id, 20);
+        // The cursor stops at the end of the list.
+        screen.handle_key(&Key::ArrowDown);
+        assert_eq!(screen.selected().unwrap().problem_id, 20);
+        assert_eq!(
+            screen.handle_key(&Key::Char('5')),
+            Action::Log {
+                problem_id: 20,
+                rating: AttemptRating::Easy
+            }
+        );
+
+        screen.handle_key(&Key::Tab);
+        assert_eq!(screen.panel(), Panel::NextUp);
+        screen.handle_key(&Key::Enter);
+        // While the rating is asked for, other keys do nothing.
+        assert_eq!(screen.handle_key(&Key::Char('q')), Action::Redraw);
+        assert_eq!(
+            screen.handle_key(&Key::Char('2')),
+            Action::Log {
+                problem_id: 146,
+                rating: AttemptRating::LongFail
+            }
+        );
+
+        screen.handle_key(&Key::Enter);
+        assert_eq!(screen.handle_key(&Key::Escape), Action::Redraw);
+        assert_eq!(screen.status, "Nothing logged.");
+        assert_eq!(screen.handle_key(&Key::Char('q')), Action::Quit);
+
+        let nothing_due = Screen::new(Overview {
+            due: Vec::new(),
+            ..overview()
+        });
+        assert_eq!(nothing_due.panel(), Panel::NextUp);
+
+        // A reload with fewer problems pulls the cursor back in range.
+        screen.handle_key(&Key::Tab);
+        screen.set_overview(Overview {
+            due: overview().due[..1].to_vec(),
+            ..overview()
+        });
+        assert_eq!(screen.selected().unwrap().problem_id, 1);
+    }
+
+    #[test]
+    fn renders_every_panel_within_the_terminal() {
+        let mut screen = Screen::new(overview());
+        for (width, height) in [(100, 20), (60, 30)] {
+            let lines = screen.render(width, height);
+            assert_eq!(lines.len(), height);
+            assert!(
+                lines.iter().all(|line| measure_text_width(line) == width),
+                "{}x{}",
+                width,
+                height
+            );
+            let text = lines.join("\n");
+            assert!(text.contains("[Due reviews (2)]"));
+            assert!(text.contains("> #1     Two Sum"));
+            assert!(text.contains("3 days overdue"));
+            assert!(text.contains("#146   LRU Cache"));
+            assert!(text.contains("Easy          1 #"));
+        }
+
+        screen.handle_key(&Key::Enter);
+        let lines = screen.render(100, 20);
+        assert!(lines[19].contains("Rate #1: 1 ShortFail"));
+    }
+}
@@ -0,0 +1,178 @@
+// src/webhook.rs
+
+/// How many times a webhook is tried before giving up.
+pub const WEBHOOK_TRIES: u32 = 3;
+
+/// How long to wait before the first retry. Each retry waits twice as long.
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How long one try may take before it's abandoned.
+const TIMEOUT_SECONDS: &str = "10";
+
+/// What `webhook_url` is sent after an attempt: the attempt event, plus a
+/// one-line summary in `content`, which Discord shows as the message.
+#[derive(Debug, serde::Serialize)]
+pub struct WebhookPayload<'a> {
+    /// A one-line summary of the attempt, e.g. "Solved Two Sum (Easy)".
+    pub content: String,
+    /// The attempt, the problem's progress and the overall counts.
+    #[serde(flatten)]
+    pub attempt: &'a AttemptEvent,
+}
+
+impl<'a> WebhookPayload<'a> {
+    /// The payload for `attempt`.
+    pub fn new(attempt: &'a AttemptEvent) -> Self {
+        WebhookPayload {
+            content: summarize(attempt),
+            attempt,
+        }
+    }
+}
+
+/// A one-line summary of `attempt` for humans, e.g. "Solved Two Sum (Easy),
+/// rated Easy, on attempt #2. 12 problems attempted, 3-day streak."
+pub fn summarize(attempt: &AttemptEvent) -> String {
+    let verb = if attempt.rating.score() >= AttemptRating::Messy.score() {
+        "Solved"
+    } else {
+        "Tried"
+    };
+    let difficulty = attempt
+        .difficulty
+        .map(|d| format!(" ({:?})", d))
+        .unwrap_or_default();
+    let streak = match attempt.current_streak {
+        0 => String::new(),
+        days => format!(", {}-day streak", days),
+    };
+    format!(
+        "{} {}{}, rated {}, on attempt #{}. {} problems attempted{}.",
+        verb,
+        attempt.name,
+        difficulty,
+        attempt.rating,
+        attempt.number_of_attempts,
+        attempt.problems_attempted,
+        streak
+    )
+}
+
+/// POSTs `payload` to `url` as JSON with curl, trying up to `WEBHOOK_TRIES`
+/// times with a doubling delay between tries.
+///
+/// # Errors
+/// Returns the last try's error if every try fails, whether curl couldn't
+/// connect or the server answered with an HTTP error.
+pub async fn post_webhook(url: &str, payload: &impl serde::Serialize) -> anyhow::Result<()> {
+    let body = serde_json::to_string(payload)?;
+    let mut delay = FIRST_RETRY_DELAY;
+    let mut tries = 1;
+    loop {
+        match post_once(url, &body).await {
+            Ok(()) => return Ok(()),
+            Err(e) if tries >= WEBHOOK_TRIES => {
+                return Err(e.context(format!(
+                    "The webhook to '{}' failed after {} tries",
+                    url, tries
+                )));
+            }
+            Err(_) => {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                tries += 1;
+            }
+        }
+    }
+}
+
+/// One try at POSTing `body` to `url`.
+async fn post_once(url: &str, body: &str) -> anyhow::Result<()> {
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            TIMEOUT_SECONDS,
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl for the webhook. Is curl installed?")?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin.write_all(body.as_bytes()).await?;
+    drop(stdin);
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("curl failed ({}): {}", output.status, stderr.trim());
+    }
+    Ok(())
+}
+
+use crate::hooks::AttemptEvent;
+use crate::problem_attempts::AttemptRating;
+use anyhow::Context;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problems::LeetCodeDifficulty;
+    use chrono::NaiveDate;
+
+    fn event(rating: AttemptRating, current_streak: u32) -> AttemptEvent {
+        AttemptEvent {
+            problem_id: 1,
+            name: "Two Sum".to_string(),
+            difficulty: Some(LeetCodeDifficulty::Easy),
+            rating,
+            score: rating.score(),
+            attempted_on: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            number_of_attempts: 2,
+            next_attempt_date: None,
+            problems_attempted: 12,
+            current_streak,
+            longest_streak: 5,
+        }
+    }
+
+    #[test]
+    fn summarizes_the_attempt_for_the_message() {
+        assert_eq!(
+            summarize(&event(AttemptRating::Easy, 3)),
+            "Solved Two Sum (Easy), rated Easy, on attempt #2. 12 problems attempted, 3-day streak."
+        );
+        assert_eq!(
+            summarize(&event(AttemptRating::LongFail, 0)),
+            "Tried Two Sum (Easy), rated LongFail, on attempt #2. 12 problems attempted."
+        );
+    }
+
+    #[test]
+    fn payload_puts_the_summary_beside_the_event() {
+        let attempt = event(AttemptRating::Hard, 1);
+        let payload = serde_json::to_value(WebhookPayload::new(&attempt)).unwrap();
+
+        assert!(
+            payload["content"]
+                .as_str()
+                .unwrap()
+                .starts_with("Solved Two Sum")
+        );
+        assert_eq!(payload["problem_id"], 1);
+        assert_eq!(payload["current_streak"], 1);
+    }
+}